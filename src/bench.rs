@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::memory;
+use crate::runner::{run_day, Part, RunError};
+
+const NOISE_FLOOR: Duration = Duration::from_micros(100);
+const MEMORY_NOISE_FLOOR: usize = 64 * 1024;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Phase {
+    Parse,
+    Part(Part),
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part(part) => write!(f, "part{}", part.number()),
+        }
+    }
+}
+
+impl FromStr for Phase {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "parse" => Ok(Phase::Parse),
+            "part1" => Ok(Phase::Part(Part::One)),
+            "part2" => Ok(Phase::Part(Part::Two)),
+            v => Err(format!("invalid phase: {}", v)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PhaseStats {
+    pub phase: Phase,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+    pub peak_memory: usize,
+}
+
+#[derive(Debug)]
+pub struct DayBench {
+    pub day: u8,
+    pub phases: Vec<PhaseStats>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BaselineEntry {
+    pub median: Duration,
+    pub peak_memory: usize,
+}
+
+pub type Baseline = BTreeMap<(u8, Phase), BaselineEntry>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Thresholds {
+    pub time_percent: f64,
+    pub memory_percent: f64,
+}
+
+pub fn bench_day(day: u8, input: &[u8], runs: usize) -> Result<DayBench, RunError> {
+    memory::enable();
+    let mut samples: Vec<(Phase, Vec<Duration>, usize)> = Vec::new();
+    let mut record = |phase, elapsed, peak_memory| {
+        if let Some((_, durations, peak)) = samples.iter_mut().find(|(v, ..)| *v == phase) {
+            durations.push(elapsed);
+            *peak = (*peak).max(peak_memory);
+        } else {
+            samples.push((phase, vec![elapsed], peak_memory));
+        }
+    };
+    for _ in 0..runs.max(1) {
+        let report = run_day(day, &mut &input[..], &[Part::One, Part::Two])?;
+        record(Phase::Parse, report.parse, report.parse_peak_memory);
+        for part in report.parts.iter() {
+            record(Phase::Part(part.part), part.elapsed, part.peak_memory);
+        }
+    }
+    Ok(DayBench {
+        day,
+        phases: samples
+            .into_iter()
+            .map(|(phase, durations, peak_memory)| summarize(phase, durations, peak_memory))
+            .collect(),
+    })
+}
+
+pub fn summarize(phase: Phase, mut durations: Vec<Duration>, peak_memory: usize) -> PhaseStats {
+    durations.sort();
+    let middle = durations.len() / 2;
+    let median = if durations.len().is_multiple_of(2) {
+        (durations[middle - 1] + durations[middle]) / 2
+    } else {
+        durations[middle]
+    };
+    PhaseStats {
+        phase,
+        min: durations[0],
+        median,
+        max: durations[durations.len() - 1],
+        peak_memory,
+    }
+}
+
+pub fn change_percent(stats: &PhaseStats, baseline: &BaselineEntry) -> f64 {
+    let baseline = baseline.median.as_secs_f64();
+    if baseline == 0.0 {
+        return 0.0;
+    }
+    (stats.median.as_secs_f64() - baseline) / baseline * 100.0
+}
+
+pub fn memory_change_percent(stats: &PhaseStats, baseline: &BaselineEntry) -> f64 {
+    if baseline.peak_memory == 0 {
+        return 0.0;
+    }
+    (stats.peak_memory as f64 - baseline.peak_memory as f64) / baseline.peak_memory as f64 * 100.0
+}
+
+pub fn is_time_regression(stats: &PhaseStats, baseline: &BaselineEntry, threshold: f64) -> bool {
+    stats.median > baseline.median + NOISE_FLOOR && change_percent(stats, baseline) > threshold
+}
+
+pub fn is_memory_regression(stats: &PhaseStats, baseline: &BaselineEntry, threshold: f64) -> bool {
+    stats.peak_memory > baseline.peak_memory + MEMORY_NOISE_FLOOR
+        && memory_change_percent(stats, baseline) > threshold
+}
+
+pub fn is_regression(
+    stats: &PhaseStats,
+    baseline: &BaselineEntry,
+    thresholds: &Thresholds,
+) -> bool {
+    is_time_regression(stats, baseline, thresholds.time_percent)
+        || is_memory_regression(stats, baseline, thresholds.memory_percent)
+}
+
+pub fn parse_baseline(content: &str) -> Result<Baseline, String> {
+    let mut baseline = Baseline::new();
+    for (number, line) in content.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || format!("line {}: invalid baseline entry: {}", number + 1, line);
+        let values: Vec<&str> = line.split_whitespace().collect();
+        if values.len() != 4 {
+            return Err(invalid());
+        }
+        let day = u8::from_str(values[0]).map_err(|_| invalid())?;
+        let phase = Phase::from_str(values[1]).map_err(|_| invalid())?;
+        let median = u64::from_str(values[2]).map_err(|_| invalid())?;
+        let peak_memory = usize::from_str(values[3]).map_err(|_| invalid())?;
+        baseline.insert(
+            (day, phase),
+            BaselineEntry {
+                median: Duration::from_nanos(median),
+                peak_memory,
+            },
+        );
+    }
+    Ok(baseline)
+}
+
+pub fn format_baseline(benches: &[DayBench]) -> String {
+    let mut result = String::from("# <day> <phase> <median ns> <peak memory bytes>\n");
+    for bench in benches.iter() {
+        for stats in bench.phases.iter() {
+            result += &format!(
+                "{} {} {} {}\n",
+                bench.day,
+                stats.phase,
+                stats.median.as_nanos(),
+                stats.peak_memory
+            );
+        }
+    }
+    result
+}
+
+#[test]
+fn summarize_test() {
+    let stats = summarize(
+        Phase::Parse,
+        vec![
+            Duration::from_millis(4),
+            Duration::from_millis(1),
+            Duration::from_millis(3),
+            Duration::from_millis(10),
+        ],
+        42,
+    );
+    assert_eq!(stats.min, Duration::from_millis(1));
+    assert_eq!(stats.median, Duration::from_micros(3500));
+    assert_eq!(stats.max, Duration::from_millis(10));
+}
+
+#[test]
+fn baseline_test() {
+    let bench = DayBench {
+        day: 6,
+        phases: vec![summarize(
+            Phase::Part(Part::Two),
+            vec![Duration::from_millis(2)],
+            1024,
+        )],
+    };
+    let baseline = parse_baseline(&format_baseline(&[bench])).unwrap();
+    let entry = baseline[&(6, Phase::Part(Part::Two))];
+    assert_eq!(entry.median, Duration::from_millis(2));
+    assert_eq!(entry.peak_memory, 1024);
+    let thresholds = |time_percent, memory_percent| Thresholds {
+        time_percent,
+        memory_percent,
+    };
+    let slower = summarize(Phase::Parse, vec![Duration::from_millis(3)], 0);
+    assert!(is_regression(&slower, &entry, &thresholds(10.0, 10.0)));
+    assert!(!is_regression(&slower, &entry, &thresholds(60.0, 10.0)));
+    let entry = BaselineEntry {
+        median: Duration::from_millis(2),
+        peak_memory: 1 << 20,
+    };
+    let larger = summarize(Phase::Parse, vec![Duration::from_millis(2)], 3 << 19);
+    assert_eq!(memory_change_percent(&larger, &entry), 50.0);
+    assert!(is_regression(&larger, &entry, &thresholds(10.0, 10.0)));
+    assert!(!is_regression(&larger, &entry, &thresholds(10.0, 60.0)));
+    let noise = summarize(Phase::Parse, vec![Duration::from_millis(2)], 100);
+    let entry = BaselineEntry {
+        median: Duration::from_millis(2),
+        peak_memory: 10,
+    };
+    assert!(!is_regression(&noise, &entry, &thresholds(10.0, 10.0)));
+}
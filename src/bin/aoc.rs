@@ -0,0 +1,738 @@
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use adventofcode_2022::bench::{self, Baseline, DayBench, Thresholds};
+use adventofcode_2022::generate;
+use adventofcode_2022::memory::TrackingAllocator;
+use adventofcode_2022::runner::{self, Format, Part, Report, DAYS};
+use adventofcode_2022::search::Limits;
+use adventofcode_2022::solution::Answer;
+use adventofcode_2022::validate;
+use adventofcode_2022::viewer;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+const USAGE: &str = "Usage:
+    aoc run <day|all> [--part 1|2] [--input FILE] [--input-dir DIR] [--format text|json]
+        [--jobs N] [--max-iterations N] [--max-states N] [--timeout SECONDS] [--max-memory MIB]
+    aoc bench <day|all> [--runs N] [--input-dir DIR] [--baseline FILE] [--save-baseline FILE]
+        [--threshold PERCENT] [--memory-threshold PERCENT]
+    aoc view <day> [--part 1|2] [--input FILE] [--input-dir DIR] [--speed N] [--fps N]
+        [--paused]
+    aoc generate <day|all> [--size N] [--seed N] [--output-dir DIR]
+    aoc validate <day|all> [--input FILE]
+
+Options:
+    --part 1|2              run only the given part (both parts by default)
+    --input FILE            read input from FILE, `-` for stdin (single day only, validate reads
+                            stdin by default)
+    --input-dir DIR         read input for day N from DIR/day_N.txt (default: var)
+    --format FORMAT         print reports as text or as one JSON object per day (default: text)
+    --jobs N                number of days to run at the same time (default: 1)
+    --max-iterations N      iterations budget of each search for days 16, 19 and 24 (day 19
+                            searches once per blueprint, day 24 once per trip)
+    --max-states N          states budget of each search for days 16, 19 and 24
+    --timeout SECONDS       time budget per part for days 16, 19 and 24
+    --max-memory MIB        approximate heap growth budget per part for days 16, 19 and 24,
+                            counted from the start of the part (requires --jobs 1)
+    --runs N                number of runs per day (default: 10)
+    --baseline FILE         compare median times and peak memory with a saved baseline and fail
+                            on regressions
+    --save-baseline FILE    save median times and peak memory as a new baseline
+    --threshold PERCENT     allowed median time increase over the baseline (default: 10)
+    --memory-threshold PERCENT
+                            allowed peak memory increase over the baseline (default: 10)
+    --speed N               simulation steps per frame, +/- change it while viewing (default: 1)
+    --fps N                 frames per second (default: 20)
+    --paused                start the viewer paused, space resumes and n steps
+    --size N                scale of the generated input: lines, monkeys, valves, map side, etc.
+    --seed N                seed for the generated input (random by default, printed to stderr)
+    --output-dir DIR        write generated input for day N to DIR/day_N.txt instead of stdout";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match parse_args(&args) {
+        Ok(Command::Run(command)) => run(command),
+        Ok(Command::Bench(command)) => run_bench(command),
+        Ok(Command::View(command)) => run_view(command),
+        Ok(Command::Generate(command)) => run_generate(command),
+        Ok(Command::Validate(command)) => run_validate(command),
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+enum Command {
+    Run(RunCommand),
+    Bench(BenchCommand),
+    View(ViewCommand),
+    Generate(GenerateCommand),
+    Validate(ValidateCommand),
+}
+
+struct RunCommand {
+    days: Vec<u8>,
+    parts: Vec<Part>,
+    input: Option<String>,
+    input_dir: PathBuf,
+    format: Format,
+    jobs: usize,
+    limits: Vec<(String, String)>,
+}
+
+struct BenchCommand {
+    days: Vec<u8>,
+    runs: usize,
+    input_dir: PathBuf,
+    baseline: Option<PathBuf>,
+    save_baseline: Option<PathBuf>,
+    thresholds: Thresholds,
+}
+
+struct ViewCommand {
+    day: u8,
+    part: Part,
+    input: Option<String>,
+    input_dir: PathBuf,
+    speed: usize,
+    fps: u32,
+    paused: bool,
+}
+
+struct GenerateCommand {
+    days: Vec<u8>,
+    size: Option<usize>,
+    seed: Option<u64>,
+    output_dir: Option<PathBuf>,
+}
+
+struct ValidateCommand {
+    days: Vec<u8>,
+    input: String,
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+    match args.next().map(|v| v.as_str()) {
+        Some("run") => Ok(Command::Run(parse_run_args(args)?)),
+        Some("bench") => Ok(Command::Bench(parse_bench_args(args)?)),
+        Some("view") => Ok(Command::View(parse_view_args(args)?)),
+        Some("generate") => Ok(Command::Generate(parse_generate_args(args)?)),
+        Some("validate") => Ok(Command::Validate(parse_validate_args(args)?)),
+        Some(v) => Err(format!("unknown command: {}", v)),
+        None => Err(String::from("command is not specified")),
+    }
+}
+
+fn parse_days(value: Option<&String>) -> Result<Vec<u8>, String> {
+    match value.map(|v| v.as_str()) {
+        Some("all") => Ok(DAYS.collect()),
+        Some(v) => match u8::from_str(v) {
+            Ok(day) if DAYS.contains(&day) => Ok(vec![day]),
+            _ => Err(format!("invalid day: {}", v)),
+        },
+        None => Err(String::from("day is not specified")),
+    }
+}
+
+fn parse_run_args(mut args: std::slice::Iter<String>) -> Result<RunCommand, String> {
+    let mut command = RunCommand {
+        days: parse_days(args.next())?,
+        parts: vec![Part::One, Part::Two],
+        input: None,
+        input_dir: PathBuf::from("var"),
+        format: Format::Text,
+        jobs: 1,
+        limits: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--part" => {
+                command.parts = match value()?.as_str() {
+                    "1" => vec![Part::One],
+                    "2" => vec![Part::Two],
+                    v => return Err(format!("invalid part: {}", v)),
+                }
+            }
+            "--input" => command.input = Some(value()?.clone()),
+            "--input-dir" => command.input_dir = PathBuf::from(value()?),
+            "--format" => command.format = value()?.parse()?,
+            "--jobs" => {
+                command.jobs = match usize::from_str(value()?) {
+                    Ok(v) if v > 0 => v,
+                    _ => return Err(String::from("invalid number of jobs")),
+                }
+            }
+            "--max-iterations" | "--max-states" | "--timeout" | "--max-memory" => {
+                let name = &arg[2..];
+                let value = value()?;
+                Limits::default().set_parameter(name, value)?;
+                command.limits.push((name.to_string(), value.clone()));
+            }
+            v => return Err(format!("unknown option: {}", v)),
+        }
+    }
+    if command.input.is_some() && command.days.len() > 1 {
+        return Err(String::from("--input can be used only with a single day"));
+    }
+    if command.jobs > 1 && command.limits.iter().any(|(name, _)| name == "max-memory") {
+        return Err(String::from("--max-memory can be used only with --jobs 1"));
+    }
+    Ok(command)
+}
+
+fn parse_bench_args(mut args: std::slice::Iter<String>) -> Result<BenchCommand, String> {
+    let mut command = BenchCommand {
+        days: parse_days(args.next())?,
+        runs: 10,
+        input_dir: PathBuf::from("var"),
+        baseline: None,
+        save_baseline: None,
+        thresholds: Thresholds {
+            time_percent: 10.0,
+            memory_percent: 10.0,
+        },
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--runs" => {
+                command.runs = match usize::from_str(value()?) {
+                    Ok(v) if v > 0 => v,
+                    _ => return Err(String::from("invalid number of runs")),
+                }
+            }
+            "--input-dir" => command.input_dir = PathBuf::from(value()?),
+            "--baseline" => command.baseline = Some(PathBuf::from(value()?)),
+            "--save-baseline" => command.save_baseline = Some(PathBuf::from(value()?)),
+            "--threshold" => {
+                command.thresholds.time_percent =
+                    f64::from_str(value()?).map_err(|_| String::from("invalid threshold"))?
+            }
+            "--memory-threshold" => {
+                command.thresholds.memory_percent =
+                    f64::from_str(value()?).map_err(|_| String::from("invalid memory threshold"))?
+            }
+            v => return Err(format!("unknown option: {}", v)),
+        }
+    }
+    Ok(command)
+}
+
+fn parse_view_args(mut args: std::slice::Iter<String>) -> Result<ViewCommand, String> {
+    let day = match parse_days(args.next())?.as_slice() {
+        [day] => *day,
+        _ => return Err(String::from("view needs a single day")),
+    };
+    let mut command = ViewCommand {
+        day,
+        part: Part::One,
+        input: None,
+        input_dir: PathBuf::from("var"),
+        speed: 1,
+        fps: 20,
+        paused: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--part" => {
+                command.part = match value()?.as_str() {
+                    "1" => Part::One,
+                    "2" => Part::Two,
+                    v => return Err(format!("invalid part: {}", v)),
+                }
+            }
+            "--input" => command.input = Some(value()?.clone()),
+            "--input-dir" => command.input_dir = PathBuf::from(value()?),
+            "--speed" => {
+                command.speed = match usize::from_str(value()?) {
+                    Ok(v) if v > 0 => v,
+                    _ => return Err(String::from("invalid speed")),
+                }
+            }
+            "--fps" => {
+                command.fps = match u32::from_str(value()?) {
+                    Ok(v) if v > 0 => v,
+                    _ => return Err(String::from("invalid fps")),
+                }
+            }
+            "--paused" => command.paused = true,
+            v => return Err(format!("unknown option: {}", v)),
+        }
+    }
+    Ok(command)
+}
+
+fn parse_generate_args(mut args: std::slice::Iter<String>) -> Result<GenerateCommand, String> {
+    let mut command = GenerateCommand {
+        days: parse_days(args.next())?,
+        size: None,
+        seed: None,
+        output_dir: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--size" => {
+                command.size = match usize::from_str(value()?) {
+                    Ok(v) if v > 0 => Some(v),
+                    _ => return Err(String::from("invalid size")),
+                }
+            }
+            "--seed" => {
+                command.seed =
+                    Some(u64::from_str(value()?).map_err(|_| String::from("invalid seed"))?)
+            }
+            "--output-dir" => command.output_dir = Some(PathBuf::from(value()?)),
+            v => return Err(format!("unknown option: {}", v)),
+        }
+    }
+    if command.output_dir.is_none() && command.days.len() > 1 {
+        return Err(String::from(
+            "--output-dir is required for more than one day",
+        ));
+    }
+    Ok(command)
+}
+
+fn parse_validate_args(mut args: std::slice::Iter<String>) -> Result<ValidateCommand, String> {
+    let mut command = ValidateCommand {
+        days: parse_days(args.next())?,
+        input: String::from("-"),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--input" => command.input = value()?.clone(),
+            v => return Err(format!("unknown option: {}", v)),
+        }
+    }
+    Ok(command)
+}
+
+fn run(command: RunCommand) -> ExitCode {
+    let start = Instant::now();
+    let mut results = Vec::new();
+    runner::run_days_parallel(
+        &command.days,
+        command.jobs,
+        |day| match &command.input {
+            Some(path) if path == "-" => run_day(day, &command, &mut std::io::stdin().lock()),
+            Some(path) => run_day_with_file(day, &command, &PathBuf::from(path)),
+            None => run_day_with_file(
+                day,
+                &command,
+                &command.input_dir.join(format!("day_{}.txt", day)),
+            ),
+        },
+        |day, result| {
+            match &result {
+                Ok(report) => match command.format {
+                    Format::Text => print_report(report),
+                    Format::Json => println!("{}", report.format_json()),
+                },
+                Err(error) => eprintln!("Day {}: {}", day, error),
+            }
+            results.push((day, result));
+        },
+    );
+    if command.days.len() > 1 && command.format == Format::Text {
+        print_summary(&results, &command.parts, start.elapsed(), command.jobs);
+    }
+    if results.iter().any(|(_, result)| result.is_err()) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn print_summary(
+    results: &[(u8, Result<Report, String>)],
+    parts: &[Part],
+    wall: Duration,
+    jobs: usize,
+) {
+    println!();
+    print!("{:<5}", "Day");
+    for part in parts.iter() {
+        print!("{:<22}", format!("Part {}", part.number()));
+    }
+    println!("{:>13}  Status", "Time");
+    let mut total = Duration::ZERO;
+    let mut failed = 0;
+    for (day, result) in results.iter() {
+        print!("{:<5}", day);
+        match result {
+            Ok(report) => {
+                total += report.elapsed();
+                for part in report.parts.iter() {
+                    print!("{:<22}", format_summary_answer(&part.answer));
+                }
+                let incomplete = report
+                    .parts
+                    .iter()
+                    .any(|v| matches!(v.answer, Answer::Incomplete(..)));
+                println!(
+                    "{:>13}  {}",
+                    format_duration(report.elapsed()),
+                    if incomplete { "incomplete" } else { "ok" }
+                );
+            }
+            Err(error) => {
+                failed += 1;
+                for _ in parts.iter() {
+                    print!("{:<22}", "-");
+                }
+                println!("{:>13}  {}", "-", error);
+            }
+        }
+    }
+    println!(
+        "Total: {} (wall: {}, jobs: {})",
+        format_duration(total),
+        format_duration(wall),
+        jobs
+    );
+    if failed > 0 {
+        println!("Failed: {} of {} days", failed, results.len());
+    }
+}
+
+fn format_summary_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Incomplete(incomplete) => match &incomplete.best {
+            Some(best) => format!("{}?", format_summary_answer(best)),
+            None => String::from("?"),
+        },
+        Answer::Image(image) => format!(
+            "image {}x{}",
+            image.rows.first().map_or(0, |v| v.chars().count()),
+            image.rows.len()
+        ),
+        answer => answer.to_string(),
+    }
+}
+
+fn run_day_with_file(day: u8, command: &RunCommand, path: &Path) -> Result<Report, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+    run_day(day, command, &mut std::io::BufReader::new(file))
+}
+
+fn run_day(day: u8, command: &RunCommand, input: &mut dyn BufRead) -> Result<Report, String> {
+    runner::run_day_with_parameters(day, &command.limits, input, &command.parts)
+        .map_err(|e| e.to_string())
+}
+
+fn run_view(command: ViewCommand) -> ExitCode {
+    let path = match &command.input {
+        Some(path) => PathBuf::from(path),
+        None => command.input_dir.join(format!("day_{}.txt", command.day)),
+    };
+    let scene = match std::fs::File::open(&path) {
+        Ok(file) => viewer::get_scene(
+            command.day,
+            &mut std::io::BufReader::new(file),
+            command.part,
+        )
+        .map_err(|e| format!("invalid input at {}", e)),
+        Err(e) => Err(format!("failed to open {}: {}", path.display(), e)),
+    };
+    let mut scene = match scene {
+        Ok(Some(scene)) => scene,
+        Ok(None) => {
+            eprintln!(
+                "Day {}: no viewer, days 14, 17, 23 and 24 can be viewed",
+                command.day
+            );
+            return ExitCode::FAILURE;
+        }
+        Err(error) => {
+            eprintln!("Day {}: {}", command.day, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let options = viewer::Options {
+        speed: command.speed,
+        frame: Duration::from_secs(1) / command.fps,
+        paused: command.paused,
+    };
+    match viewer::play(scene.as_mut(), options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Day {}: failed to write to terminal: {}", command.day, e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_generate(command: GenerateCommand) -> ExitCode {
+    let seed = command.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |v| v.as_nanos() as u64);
+        eprintln!("seed: {}", seed);
+        seed
+    });
+    if let Some(path) = &command.output_dir {
+        if let Err(e) = std::fs::create_dir_all(path) {
+            eprintln!("failed to create {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+    }
+    for &day in command.days.iter() {
+        let size = command.size.unwrap_or_else(|| generate::default_size(day));
+        let input = generate::generate(day, size, &mut generate::Rng::new(seed))
+            .expect("generator for every day");
+        match &command.output_dir {
+            Some(dir) => {
+                let path = dir.join(format!("day_{}.txt", day));
+                if let Err(e) = std::fs::write(&path, input) {
+                    eprintln!("failed to write {}: {}", path.display(), e);
+                    return ExitCode::FAILURE;
+                }
+            }
+            None => print!("{}", input),
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn run_validate(command: ValidateCommand) -> ExitCode {
+    let input = if command.input == "-" {
+        let mut buffer = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin().lock(), &mut buffer).map(|_| buffer)
+    } else {
+        std::fs::read(&command.input)
+    };
+    let input = match input {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("failed to read {}: {}", command.input, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut valid = true;
+    if let [day] = command.days.as_slice() {
+        match validate::validate_day(*day, &input) {
+            Ok(problems) => valid = print_problems(*day, &problems),
+            Err(error) => {
+                println!("Day {}: {}", day, error);
+                let matches = validate::detect_days(&input);
+                if !matches.is_empty() {
+                    println!("Input matches {}", format_days(&matches));
+                }
+                valid = false;
+            }
+        }
+    } else {
+        let matches = validate::detect_days(&input);
+        if matches.is_empty() {
+            println!("Input does not match any day");
+            valid = false;
+        }
+        for day in matches {
+            let problems = validate::validate_day(day, &input).unwrap_or_default();
+            valid &= print_problems(day, &problems);
+        }
+    }
+    if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn print_problems(day: u8, problems: &[String]) -> bool {
+    if problems.is_empty() {
+        println!("Day {}: valid", day);
+        return true;
+    }
+    println!(
+        "Day {}: {} problem{}",
+        day,
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" }
+    );
+    for problem in problems.iter() {
+        println!("  {}", problem);
+    }
+    false
+}
+
+fn format_days(days: &[u8]) -> String {
+    let days: Vec<String> = days.iter().map(|v| v.to_string()).collect();
+    format!(
+        "day{} {}",
+        if days.len() == 1 { "" } else { "s" },
+        days.join(", ")
+    )
+}
+
+fn run_bench(command: BenchCommand) -> ExitCode {
+    let baseline = match &command.baseline {
+        Some(path) => match read_baseline(path) {
+            Ok(v) => Some(v),
+            Err(error) => {
+                eprintln!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    let mut failed = false;
+    let mut regressions = 0;
+    let mut benches = Vec::new();
+    println!(
+        "{:<5}{:<7}{:>13}{:>13}{:>13}{:>13}{:>12}{:>12}",
+        "Day", "Phase", "Min", "Median", "Max", "Peak memory", "Time +/-", "Memory +/-"
+    );
+    for &day in command.days.iter() {
+        let path = command.input_dir.join(format!("day_{}.txt", day));
+        let bench = match std::fs::read(&path) {
+            Ok(input) => bench::bench_day(day, &input, command.runs).map_err(|e| e.to_string()),
+            Err(e) => Err(format!("failed to open {}: {}", path.display(), e)),
+        };
+        match bench {
+            Ok(bench) => {
+                regressions += print_bench(&bench, baseline.as_ref(), &command.thresholds);
+                benches.push(bench);
+            }
+            Err(error) => {
+                eprintln!("Day {}: {}", day, error);
+                failed = true;
+            }
+        }
+    }
+    if let Some(path) = &command.save_baseline {
+        if let Err(e) = std::fs::write(path, bench::format_baseline(&benches)) {
+            eprintln!("failed to write {}: {}", path.display(), e);
+            failed = true;
+        }
+    }
+    if regressions > 0 {
+        eprintln!(
+            "{} phase(s) are more than {}% slower or use more than {}% more memory than the baseline",
+            regressions, command.thresholds.time_percent, command.thresholds.memory_percent
+        );
+    }
+    if failed || regressions > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn read_baseline(path: &Path) -> Result<Baseline, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    bench::parse_baseline(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn print_bench(bench: &DayBench, baseline: Option<&Baseline>, thresholds: &Thresholds) -> usize {
+    let mut regressions = 0;
+    for stats in bench.phases.iter() {
+        let (time, memory) = match baseline.and_then(|v| v.get(&(bench.day, stats.phase))) {
+            Some(entry) => {
+                if bench::is_regression(stats, entry, thresholds) {
+                    regressions += 1;
+                }
+                let mark = |regression| if regression { "!" } else { "" };
+                (
+                    format!(
+                        "{:+.1}%{}",
+                        bench::change_percent(stats, entry),
+                        mark(bench::is_time_regression(
+                            stats,
+                            entry,
+                            thresholds.time_percent
+                        ))
+                    ),
+                    format!(
+                        "{:+.1}%{}",
+                        bench::memory_change_percent(stats, entry),
+                        mark(bench::is_memory_regression(
+                            stats,
+                            entry,
+                            thresholds.memory_percent
+                        ))
+                    ),
+                )
+            }
+            None => (String::from("-"), String::from("-")),
+        };
+        println!(
+            "{:<5}{:<7}{:>13}{:>13}{:>13}{:>13}{:>12}{:>12}",
+            bench.day,
+            stats.phase.to_string(),
+            format_duration(stats.min),
+            format_duration(stats.median),
+            format_duration(stats.max),
+            format_memory(stats.peak_memory),
+            time,
+            memory
+        );
+    }
+    regressions
+}
+
+fn print_report(report: &Report) {
+    println!(
+        "Day {} (parse: {})",
+        report.day,
+        format_duration(report.parse)
+    );
+    for part in report.parts.iter() {
+        let elapsed = format_duration(part.elapsed);
+        if let Answer::Image(image) = &part.answer {
+            println!("  Part {}: ({})", part.part.number(), elapsed);
+            for line in image.rows.iter() {
+                println!("    {}", line);
+            }
+        } else {
+            println!(
+                "  Part {}: {} ({})",
+                part.part.number(),
+                part.answer,
+                elapsed
+            );
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn format_memory(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
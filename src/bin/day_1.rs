@@ -0,0 +1,6 @@
+use adventofcode_2022::day_1::Day1;
+use adventofcode_2022::runner::run_main;
+
+fn main() {
+    run_main(Day1::default());
+}
@@ -0,0 +1,6 @@
+use adventofcode_2022::day_11::Day11;
+use adventofcode_2022::runner::run_main;
+
+fn main() {
+    run_main(Day11::default());
+}
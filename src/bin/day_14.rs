@@ -0,0 +1,6 @@
+use adventofcode_2022::day_14::Day14;
+use adventofcode_2022::runner::run_main;
+
+fn main() {
+    run_main(Day14::default());
+}
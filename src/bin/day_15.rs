@@ -0,0 +1,6 @@
+use adventofcode_2022::day_15::Day15;
+use adventofcode_2022::runner::run_main;
+
+fn main() {
+    run_main(Day15::default());
+}
@@ -0,0 +1,10 @@
+use adventofcode_2022::day_19::Day19;
+use adventofcode_2022::memory::TrackingAllocator;
+use adventofcode_2022::runner::run_main;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+fn main() {
+    run_main(Day19::default());
+}
@@ -0,0 +1,6 @@
+use adventofcode_2022::day_2::Day2;
+use adventofcode_2022::runner::run_main;
+
+fn main() {
+    run_main(Day2::default());
+}
@@ -0,0 +1,10 @@
+use adventofcode_2022::day_24::Day24;
+use adventofcode_2022::memory::TrackingAllocator;
+use adventofcode_2022::runner::run_main;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+fn main() {
+    run_main(Day24::default());
+}
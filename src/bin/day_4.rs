@@ -0,0 +1,6 @@
+use adventofcode_2022::day_4::Day4;
+use adventofcode_2022::runner::run_main;
+
+fn main() {
+    run_main(Day4);
+}
@@ -1,31 +1,235 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", get_max_calories_per_elf(std::io::stdin().lock()));
+use crate::parse::{lines, ParseError};
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+
+pub struct Day1 {
+    pub top: usize,
 }
 
-fn get_max_calories_per_elf(buffer: impl BufRead) -> (u64, u64) {
-    let mut max = 0;
-    let mut sum = 0;
-    let mut calories_per_elf = Vec::new();
-    for line in buffer.lines().map(|v| v.unwrap()) {
-        if line.is_empty() {
-            if max < sum {
-                max = sum;
-            }
-            calories_per_elf.push(sum);
-            sum = 0;
-        } else {
-            sum += u64::from_str(line.as_str()).unwrap();
+impl Default for Day1 {
+    fn default() -> Self {
+        Self { top: 3 }
+    }
+}
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    const PARAMETERS: &'static [&'static str] = &["top"];
+
+    type Puzzle = Inventory;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_calories_per_elf(input).map(Inventory::new)
+    }
+
+    fn part_one(&self, inventory: &Self::Puzzle) -> Self::PartOne {
+        find_max_calories(inventory.totals())
+    }
+
+    fn part_two(&self, inventory: &Self::Puzzle) -> Self::PartTwo {
+        inventory.top(self.top).iter().map(|elf| elf.calories).sum()
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "top" => self.top = parse_parameter(name, value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elf {
+    pub number: usize,
+    pub calories: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    calories_per_elf: Vec<u64>,
+}
+
+impl Inventory {
+    pub fn new(calories_per_elf: Vec<u64>) -> Self {
+        Self { calories_per_elf }
+    }
+
+    pub fn totals(&self) -> &[u64] {
+        &self.calories_per_elf
+    }
+
+    pub fn elves(&self) -> impl Iterator<Item = Elf> + '_ {
+        self.calories_per_elf
+            .iter()
+            .enumerate()
+            .map(|(index, calories)| Elf {
+                number: index + 1,
+                calories: *calories,
+            })
+    }
+
+    pub fn get(&self, number: usize) -> Option<Elf> {
+        let calories = *self.calories_per_elf.get(number.checked_sub(1)?)?;
+        Some(Elf { number, calories })
+    }
+
+    pub fn ranking(&self) -> Vec<Elf> {
+        let mut elves: Vec<Elf> = self.elves().collect();
+        elves.sort_by_key(|elf| (Reverse(elf.calories), elf.number));
+        elves
+    }
+
+    pub fn top(&self, k: usize) -> Vec<Elf> {
+        let mut top = TopK::new(k);
+        self.elves().for_each(|elf| top.push(elf));
+        top.into_sorted()
+    }
+
+    pub fn rank(&self, number: usize) -> Option<usize> {
+        let elf = self.get(number)?;
+        Some(1 + self.elves().filter(|v| v.calories > elf.calories).count())
+    }
+
+    pub fn percentile(&self, percent: f64) -> Option<u64> {
+        if self.calories_per_elf.is_empty() || !(0.0..=100.0).contains(&percent) {
+            return None;
+        }
+        let mut sorted = self.calories_per_elf.clone();
+        sorted.sort_unstable();
+        let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+
+    pub fn histogram(&self, bucket_size: u64) -> Vec<Bucket> {
+        if self.calories_per_elf.is_empty() {
+            return Vec::new();
+        }
+        let bucket_size = bucket_size.max(1);
+        let max = find_max_calories(&self.calories_per_elf);
+        let mut buckets: Vec<Bucket> = (0..=max / bucket_size)
+            .map(|index| Bucket {
+                start: index * bucket_size,
+                end: (index + 1) * bucket_size - 1,
+                count: 0,
+            })
+            .collect();
+        for calories in self.calories_per_elf.iter() {
+            buckets[(calories / bucket_size) as usize].count += 1;
+        }
+        buckets
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bucket {
+    pub start: u64,
+    pub end: u64,
+    pub count: usize,
+}
+
+pub fn format_histogram(buckets: &[Bucket], width: usize) -> String {
+    let max_count = buckets.iter().map(|v| v.count).max().unwrap_or(0).max(1);
+    let range_width = buckets
+        .iter()
+        .map(|v| format!("{}-{}", v.start, v.end).len())
+        .max()
+        .unwrap_or(0);
+    let mut result = String::new();
+    for bucket in buckets {
+        let range = format!("{}-{}", bucket.start, bucket.end);
+        let bar = "#".repeat((bucket.count * width).div_ceil(max_count));
+        result += &format!("{:>range_width$} | {} {}\n", range, bar, bucket.count);
+    }
+    result
+}
+
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<(u64, Reverse<usize>)>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub fn push(&mut self, elf: Elf) {
+        if self.k == 0 {
+            return;
+        }
+        self.heap.push(Reverse((elf.calories, Reverse(elf.number))));
+        if self.heap.len() > self.k {
+            self.heap.pop();
         }
     }
-    if max < sum {
-        max = sum;
+
+    pub fn into_sorted(self) -> Vec<Elf> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((calories, Reverse(number)))| Elf { number, calories })
+            .collect()
     }
-    calories_per_elf.push(sum);
+}
+
+pub fn stream_top_calories(buffer: impl BufRead, k: usize) -> Result<Vec<Elf>, ParseError> {
+    let mut top = TopK::new(k);
+    for_each_elf(buffer, |elf| top.push(elf))?;
+    Ok(top.into_sorted())
+}
+
+pub fn get_max_calories_per_elf(buffer: impl BufRead) -> Result<(u64, u64), ParseError> {
+    let calories_per_elf = parse_calories_per_elf(buffer)?;
+    Ok((
+        find_max_calories(&calories_per_elf),
+        sum_top_three_calories(&calories_per_elf),
+    ))
+}
+
+pub fn find_max_calories(calories_per_elf: &[u64]) -> u64 {
+    calories_per_elf.iter().copied().max().unwrap_or(0)
+}
+
+pub fn sum_top_three_calories(calories_per_elf: &[u64]) -> u64 {
+    let mut calories_per_elf = calories_per_elf.to_vec();
     calories_per_elf.sort_by_key(|v| u64::MAX - v);
-    (max, calories_per_elf.iter().take(3).sum())
+    calories_per_elf.iter().take(3).sum()
+}
+
+pub fn parse_calories_per_elf(buffer: impl BufRead) -> Result<Vec<u64>, ParseError> {
+    let mut calories_per_elf = Vec::new();
+    for_each_elf(buffer, |elf| calories_per_elf.push(elf.calories))?;
+    Ok(calories_per_elf)
+}
+
+fn for_each_elf(buffer: impl BufRead, mut f: impl FnMut(Elf)) -> Result<(), ParseError> {
+    let mut elf = Elf {
+        number: 1,
+        calories: 0,
+    };
+    for line in lines(buffer) {
+        let line = line?;
+        if line.text.is_empty() {
+            f(elf);
+            elf = Elf {
+                number: elf.number + 1,
+                calories: 0,
+            };
+        } else {
+            elf.calories += line.parse::<u64>(&line.text, "calories")?;
+        }
+    }
+    f(elf);
+    Ok(())
 }
 
 #[test]
@@ -46,5 +250,37 @@ fn example_test() {
 10000
 "#
     .as_bytes();
-    assert_eq!(get_max_calories_per_elf(buffer), (24000, 45000));
+    assert_eq!(get_max_calories_per_elf(buffer), Ok((24000, 45000)));
+}
+
+#[test]
+fn invalid_calories_test() {
+    let buffer = "1000\n\n20x0\n".as_bytes();
+    assert_eq!(
+        get_max_calories_per_elf(buffer),
+        Err(ParseError::new(3, 1, "20x0", "calories"))
+    );
+}
+
+#[test]
+fn inventory_test() {
+    let buffer = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+    let inventory = Inventory::new(parse_calories_per_elf(buffer.as_bytes()).unwrap());
+    let elf = |number, calories| Elf { number, calories };
+    assert_eq!(inventory.totals(), &[6000, 4000, 11000, 24000, 10000]);
+    assert_eq!(inventory.top(2), vec![elf(4, 24000), elf(3, 11000)]);
+    assert_eq!(inventory.top(9), inventory.ranking());
+    assert_eq!(
+        stream_top_calories(buffer.as_bytes(), 3),
+        Ok(inventory.top(3))
+    );
+    assert_eq!(inventory.rank(3), Some(2));
+    assert_eq!(inventory.rank(6), None);
+    assert_eq!(inventory.percentile(50.0), Some(10000));
+    assert_eq!(inventory.percentile(100.0), Some(24000));
+    assert_eq!(inventory.percentile(0.0), Some(4000));
+    assert_eq!(
+        format_histogram(&inventory.histogram(10000), 4),
+        "     0-9999 | #### 2\n10000-19999 | #### 2\n20000-29999 | ## 1\n"
+    );
 }
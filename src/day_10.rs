@@ -1,46 +1,120 @@
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    let (total_signal_strength, image) = compute_result(std::io::stdin().lock());
-    println!("{}\n{}", total_signal_strength, image);
+use serde_json::json;
+
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{parse_parameter, parse_parameter_list, unknown_parameter, Image, Solution};
+use crate::trace::{Trace, Tracer};
+
+pub struct Day10 {
+    pub cycles: Vec<usize>,
+    pub width: usize,
+    pub height: usize,
+    pub trace: Trace,
 }
 
-fn compute_result(input: impl BufRead) -> (i64, String) {
-    let instructions = parse_instructions(input);
-    (
-        sum_signal_strength_at(&instructions, &[20, 60, 100, 140, 180, 220]),
-        render_image(&instructions, 40, 6),
-    )
+impl Default for Day10 {
+    fn default() -> Self {
+        Self {
+            cycles: vec![20, 60, 100, 140, 180, 220],
+            width: 40,
+            height: 6,
+            trace: Trace::default(),
+        }
+    }
 }
 
-fn render_image(instructions: &[Instruction], width: usize, height: usize) -> String {
-    let mut buffer: Vec<u8> = std::iter::repeat(b'.').take(width * height).collect();
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const PARAMETERS: &'static [&'static str] = &["cycles", "width", "height", "trace"];
+
+    type Puzzle = Vec<Instruction>;
+    type PartOne = i64;
+    type PartTwo = Image;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_instructions(input)
+    }
+
+    fn part_one(&self, instructions: &Self::Puzzle) -> Self::PartOne {
+        sum_signal_strength_at(instructions, &self.cycles, self.trace.part(1))
+    }
+
+    fn part_two(&self, instructions: &Self::Puzzle) -> Self::PartTwo {
+        render_image(instructions, self.width, self.height, self.trace.part(2))
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "cycles" => {
+                self.cycles = parse_parameter_list(name, value)?;
+                self.cycles.sort();
+            }
+            "width" | "height" => match parse_parameter(name, value)? {
+                0 => return Err(format!("invalid value for --{}: {}", name, value)),
+                size if name == "width" => self.width = size,
+                size => self.height = size,
+            },
+            "trace" => self.trace = Trace::create(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+}
+
+pub fn compute_result(input: impl BufRead) -> Result<(i64, String), ParseError> {
+    let solution = Day10::default();
+    let instructions = solution.parse(input)?;
+    Ok((
+        solution.part_one(&instructions),
+        solution.part_two(&instructions).to_string(),
+    ))
+}
+
+pub fn render_image(
+    instructions: &[Instruction],
+    width: usize,
+    height: usize,
+    tracer: Tracer,
+) -> Image {
+    let mut buffer: Vec<u8> = std::iter::repeat_n(b'.', width * height).collect();
     let mut sprite_pos = 1;
     let mut draw_pos: usize = 0;
     for instruction in instructions.iter() {
         let (count, next_sprite_pos) = execute_instruction(instruction, sprite_pos);
         for _ in 0..count {
-            if (sprite_pos - 1..=sprite_pos + 1).contains(&((draw_pos % width) as i64)) {
+            if draw_pos >= buffer.len() {
+                break;
+            }
+            let lit = (sprite_pos - 1..=sprite_pos + 1).contains(&((draw_pos % width) as i64));
+            if lit {
                 buffer[draw_pos] = b'#';
             }
+            tracer.event(draw_pos, || {
+                json!({
+                    "event": "draw",
+                    "x": sprite_pos,
+                    "pixel": [draw_pos % width, draw_pos / width],
+                    "lit": lit,
+                })
+            });
             draw_pos += 1;
         }
         sprite_pos = next_sprite_pos;
     }
-    let mut result = String::new();
-    for i in 0..buffer.len() / width {
-        result.extend(
-            buffer[width * i..width * (i + 1)]
-                .iter()
-                .map(|v| *v as char),
-        );
-        result.push('\n');
+    Image {
+        rows: buffer
+            .chunks(width)
+            .map(|v| v.iter().map(|v| *v as char).collect())
+            .collect(),
     }
-    result
 }
 
-fn sum_signal_strength_at(instructions: &[Instruction], mut positions: &[usize]) -> i64 {
+pub fn sum_signal_strength_at(
+    instructions: &[Instruction],
+    mut positions: &[usize],
+    tracer: Tracer,
+) -> i64 {
     let mut x = 1;
     let mut total_signal_strength = 0;
     let mut cycle = 0;
@@ -48,11 +122,20 @@ fn sum_signal_strength_at(instructions: &[Instruction], mut positions: &[usize])
         let (count, next_x) = execute_instruction(instruction, x);
         for _ in 0..count {
             cycle += 1;
-            if !positions.is_empty() && cycle == positions[0] {
+            let sampled = !positions.is_empty() && cycle == positions[0];
+            if sampled {
                 let signal_strength = x * cycle as i64;
                 total_signal_strength += signal_strength;
                 positions = &positions[1..];
             }
+            tracer.event(cycle - 1, || {
+                json!({
+                    "event": "cycle",
+                    "cycle": cycle,
+                    "x": x,
+                    "sampled": sampled,
+                })
+            });
         }
         x = next_x;
     }
@@ -66,25 +149,22 @@ fn execute_instruction(value: &Instruction, x: i64) -> (usize, i64) {
     }
 }
 
-fn parse_instructions(input: impl BufRead) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|v| parse_instruction(&v.unwrap()))
-        .collect()
+pub fn parse_instructions(input: impl BufRead) -> Result<Vec<Instruction>, ParseError> {
+    lines(input).map(|v| parse_instruction(&v?)).collect()
 }
 
-fn parse_instruction(value: &str) -> Instruction {
-    if let Some(suffix) = value.strip_prefix("addx ") {
-        return Instruction::Add(i64::from_str(suffix).unwrap());
+fn parse_instruction(line: &Line) -> Result<Instruction, ParseError> {
+    if let Some(suffix) = line.text.strip_prefix("addx ") {
+        return Ok(Instruction::Add(line.parse(suffix, "integer")?));
     }
-    if value == "noop" {
-        return Instruction::Noop;
+    if line.text == "noop" {
+        return Ok(Instruction::Noop);
     }
-    unreachable!();
+    Err(line.error(&line.text, "addx or noop"))
 }
 
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Noop,
     Add(i64),
 }
@@ -96,7 +176,7 @@ addx 3
 addx -5
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer).0, 0);
+    assert_eq!(compute_result(buffer).unwrap().0, 0);
 }
 
 #[test]
@@ -251,7 +331,7 @@ noop
     .as_bytes();
     assert_eq!(
         compute_result(buffer),
-        (
+        Ok((
             13140,
             "##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
@@ -261,6 +341,18 @@ noop
 #######.......#######.......#######.....
 "
             .to_string()
-        )
+        ))
     );
 }
+
+#[test]
+fn small_screen_test() {
+    let mut solution = Day10::default();
+    assert!(solution.set_parameter("width", "0").is_err());
+    solution.set_parameter("width", "3").unwrap();
+    solution.set_parameter("height", "1").unwrap();
+    let instructions = solution
+        .parse("addx 1\naddx 1\nnoop\nnoop\n".as_bytes())
+        .unwrap();
+    assert_eq!(solution.part_two(&instructions).rows, vec!["###"]);
+}
@@ -1,40 +1,108 @@
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", figure_out_monkey_business(std::io::stdin().lock()));
+use serde_json::json;
+
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+use crate::trace::{Trace, Tracer};
+
+pub struct Day11 {
+    pub rounds: usize,
+    pub part_two_rounds: usize,
+    pub trace: Trace,
 }
 
-fn figure_out_monkey_business(input: impl BufRead) -> (u64, u64) {
-    let monkeys = parse_monkeys(input);
-    let common_divisor: u64 = monkeys
-        .iter()
-        .map(|v| v.test.condition.divisible_by)
-        .product();
-    (
-        simulate_monkeys(monkeys.clone(), 20, |v| v / 3),
-        simulate_monkeys(monkeys, 10000, |v| v % common_divisor),
-    )
+impl Default for Day11 {
+    fn default() -> Self {
+        Self {
+            rounds: 20,
+            part_two_rounds: 10000,
+            trace: Trace::default(),
+        }
+    }
 }
 
-fn simulate_monkeys<F>(mut monkeys: Vec<Monkey>, rounds: usize, reduce_worry_level: F) -> u64
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const PARAMETERS: &'static [&'static str] = &["rounds", "part-two-rounds", "trace"];
+
+    type Puzzle = Vec<Monkey>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_monkeys(input)
+    }
+
+    fn part_one(&self, monkeys: &Self::Puzzle) -> Self::PartOne {
+        simulate_monkeys(monkeys.clone(), self.rounds, self.trace.part(1), |v| v / 3)
+    }
+
+    fn part_two(&self, monkeys: &Self::Puzzle) -> Self::PartTwo {
+        let common_divisor: u64 = monkeys
+            .iter()
+            .map(|v| v.test.condition.divisible_by)
+            .product();
+        simulate_monkeys(
+            monkeys.clone(),
+            self.part_two_rounds,
+            self.trace.part(2),
+            |v| v % common_divisor,
+        )
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "rounds" => self.rounds = parse_parameter(name, value)?,
+            "part-two-rounds" => self.part_two_rounds = parse_parameter(name, value)?,
+            "trace" => self.trace = Trace::create(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+}
+
+pub fn figure_out_monkey_business(input: impl BufRead) -> Result<(u64, u64), ParseError> {
+    let solution = Day11::default();
+    let monkeys = solution.parse(input)?;
+    Ok((solution.part_one(&monkeys), solution.part_two(&monkeys)))
+}
+
+pub fn simulate_monkeys<F>(
+    mut monkeys: Vec<Monkey>,
+    rounds: usize,
+    tracer: Tracer,
+    reduce_worry_level: F,
+) -> u64
 where
     F: Fn(u64) -> u64,
 {
-    let mut inspections_by_monkey: Vec<u64> = std::iter::repeat(0).take(monkeys.len()).collect();
-    for _ in 0..rounds {
+    let mut inspections_by_monkey: Vec<u64> = std::iter::repeat_n(0, monkeys.len()).collect();
+    let mut step = 0;
+    for round in 0..rounds {
         for monkey_index in 0..monkeys.len() {
             while let Some(worry_level) = monkeys[monkey_index].starting_items.pop() {
                 inspections_by_monkey[monkey_index] += 1;
                 let monkey = &monkeys[monkey_index];
                 let new_worry_level =
                     reduce_worry_level(evaluate_expression(&monkey.operation, worry_level));
-                let next = if new_worry_level % monkey.test.condition.divisible_by == 0 {
+                let next = if new_worry_level.is_multiple_of(monkey.test.condition.divisible_by) {
                     monkey.test.if_true
                 } else {
                     monkey.test.if_false
                 };
                 monkeys[next].starting_items.push(new_worry_level);
+                tracer.event(step, || {
+                    json!({
+                        "event": "throw",
+                        "round": round + 1,
+                        "monkey": monkey_index,
+                        "from_worry_level": worry_level,
+                        "worry_level": new_worry_level,
+                        "to": next,
+                    })
+                });
+                step += 1;
             }
         }
     }
@@ -58,10 +126,12 @@ fn get_operand_value(operand: &Operand, old: u64) -> u64 {
     }
 }
 
-fn parse_monkeys(input: impl BufRead) -> Vec<Monkey> {
+pub fn parse_monkeys(input: impl BufRead) -> Result<Vec<Monkey>, ParseError> {
     let mut result = Vec::new();
-    for line in input.lines().map(|v| v.unwrap()) {
-        if line.starts_with("Monkey") {
+    let mut targets = Vec::new();
+    for line in lines(input) {
+        let line = line?;
+        if line.text.starts_with("Monkey") {
             result.push(Monkey {
                 starting_items: Default::default(),
                 operation: Expression {
@@ -71,48 +141,68 @@ fn parse_monkeys(input: impl BufRead) -> Vec<Monkey> {
                 },
                 test: Default::default(),
             });
-        } else if let Some(starting_items) = line.strip_prefix("  Starting items: ") {
-            result.last_mut().unwrap().starting_items = starting_items
+        } else if let Some(starting_items) = line.text.strip_prefix("  Starting items: ") {
+            last_monkey(&mut result, &line)?.starting_items = starting_items
                 .split(", ")
-                .map(|v| u64::from_str(v).unwrap())
-                .collect();
-        } else if let Some(expression) = line.strip_prefix("  Operation: new = ") {
-            let mut split = expression.split(' ');
-            let expression = &mut result.last_mut().unwrap().operation;
-            expression.first = parse_operand(split.next().unwrap());
-            expression.operation = parse_operation(split.next().unwrap());
-            expression.second = parse_operand(split.next().unwrap());
-        } else if let Some(divisible_by) = line.strip_prefix("  Test: divisible by ") {
-            result.last_mut().unwrap().test.condition = Condition {
-                divisible_by: u64::from_str(divisible_by).unwrap(),
+                .map(|v| line.parse(v, "worry level"))
+                .collect::<Result<_, _>>()?;
+        } else if let Some(expression) = line.text.strip_prefix("  Operation: new = ") {
+            let (first, tail) = line.split_once(expression, " ")?;
+            let (operation, second) = line.split_once(tail, " ")?;
+            last_monkey(&mut result, &line)?.operation = Expression {
+                first: parse_operand(&line, first)?,
+                operation: parse_operation(&line, operation)?,
+                second: parse_operand(&line, second)?,
             };
-        } else if let Some(monkey) = line.strip_prefix("    If true: throw to monkey ") {
-            result.last_mut().unwrap().test.if_true = usize::from_str(monkey).unwrap();
-        } else if let Some(monkey) = line.strip_prefix("    If false: throw to monkey ") {
-            result.last_mut().unwrap().test.if_false = usize::from_str(monkey).unwrap();
+        } else if let Some(value) = line.text.strip_prefix("  Test: divisible by ") {
+            let divisible_by = line.parse(value, "divisor")?;
+            if divisible_by == 0 {
+                return Err(line.error(value, "non-zero divisor"));
+            }
+            last_monkey(&mut result, &line)?.test.condition = Condition { divisible_by };
+        } else if let Some(monkey) = line.text.strip_prefix("    If true: throw to monkey ") {
+            last_monkey(&mut result, &line)?.test.if_true = line.parse(monkey, "monkey number")?;
+            targets.push(line.clone());
+        } else if let Some(monkey) = line.text.strip_prefix("    If false: throw to monkey ") {
+            last_monkey(&mut result, &line)?.test.if_false = line.parse(monkey, "monkey number")?;
+            targets.push(line.clone());
+        } else if !line.text.is_empty() {
+            return Err(line.error(&line.text, "monkey description"));
+        }
+    }
+    for line in targets.iter() {
+        let (_, monkey) = line.split_once(&line.text, "throw to monkey ")?;
+        if line.parse::<usize>(monkey, "monkey number")? >= result.len() {
+            return Err(line.error(monkey, "existing monkey number"));
         }
     }
-    result
+    Ok(result)
+}
+
+fn last_monkey<'a>(monkeys: &'a mut [Monkey], line: &Line) -> Result<&'a mut Monkey, ParseError> {
+    monkeys
+        .last_mut()
+        .ok_or_else(|| line.error(&line.text, "\"Monkey\" header"))
 }
 
-fn parse_operand(value: &str) -> Operand {
+fn parse_operand(line: &Line, value: &str) -> Result<Operand, ParseError> {
     if value == "old" {
-        Operand::Old
+        Ok(Operand::Old)
     } else {
-        Operand::Const(u64::from_str(value).unwrap())
+        Ok(Operand::Const(line.parse(value, "old or integer")?))
     }
 }
 
-fn parse_operation(value: &str) -> Operation {
+fn parse_operation(line: &Line, value: &str) -> Result<Operation, ParseError> {
     match value {
-        "+" => Operation::Plus,
-        "*" => Operation::Mult,
-        _ => unreachable!(),
+        "+" => Ok(Operation::Plus),
+        "*" => Ok(Operation::Mult),
+        _ => Err(line.error(value, "+ or *")),
     }
 }
 
 #[derive(Clone, Debug)]
-struct Monkey {
+pub struct Monkey {
     starting_items: Vec<u64>,
     operation: Expression,
     test: Test,
@@ -180,5 +270,5 @@ Monkey 3:
     If false: throw to monkey 1
 "#
     .as_bytes();
-    assert_eq!(figure_out_monkey_business(buffer), (10605, 2713310158));
+    assert_eq!(figure_out_monkey_business(buffer), Ok((10605, 2713310158)));
 }
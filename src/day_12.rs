@@ -1,108 +1,133 @@
-use std::cmp::Reverse;
-use std::collections::BinaryHeap;
 use std::io::BufRead;
 
-fn main() {
-    println!("{:?}", find_shortest_path_lengths(std::io::stdin().lock()));
-}
+use crate::grid::{parse_grid, Grid};
+use crate::parse::ParseError;
+use crate::search::{search, Budget, Problem};
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Puzzle = Heightmap;
+    type PartOne = Option<u64>;
+    type PartTwo = Option<u64>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_one(&self, heightmap: &Self::Puzzle) -> Self::PartOne {
+        find_shortest_path_length(heightmap.src?, heightmap.dst?, &heightmap.map)
+    }
 
-fn find_shortest_path_lengths(input: impl BufRead) -> (u64, u64) {
-    let (map, src, dst) = parse_input(input);
-    (
-        find_shortest_path_length(src, dst, &map),
-        map.values
+    fn part_two(&self, heightmap: &Self::Puzzle) -> Self::PartTwo {
+        let dst = heightmap.dst?;
+        heightmap
+            .map
+            .values()
             .iter()
             .enumerate()
             .filter(|(_, v)| **v == b'a')
-            .map(|(i, _)| find_shortest_path_length(i, dst, &map))
+            .filter_map(|(i, _)| find_shortest_path_length(i, dst, &heightmap.map))
             .min()
-            .unwrap(),
-    )
-}
+    }
 
-fn find_shortest_path_length(src: usize, dst: usize, map: &Grid<u8>) -> u64 {
-    let height = map.values.len() / map.width;
-    let mut indices = BinaryHeap::new();
-    let mut distances = Grid {
-        values: std::iter::repeat(u64::MAX).take(map.values.len()).collect(),
-        width: map.width,
-    };
-    distances.values[src] = 0;
-    indices.push((Reverse(0), src));
-    let mut try_push_neighbour = |cur, next, positions: &mut BinaryHeap<(Reverse<u64>, usize)>| {
-        if (map.values[next] as i16 - map.values[cur] as i16) > 1 {
-            return;
+    fn validate(&self, heightmap: &Self::Puzzle) -> Vec<String> {
+        let mut problems = Vec::new();
+        if heightmap.src.is_none() {
+            problems.push(String::from("start position S is missing"));
         }
-        let new_length = distances.values[cur] + 1;
-        if distances.values[next] <= new_length {
-            return;
-        }
-        distances.values[next] = new_length;
-        positions.push((Reverse(new_length), next));
-    };
-    while let Some((_, index)) = indices.pop() {
-        if index == dst {
-            break;
-        }
-        let (x, y) = map.position(index);
-        if x > 0 {
-            try_push_neighbour(index, map.index(x - 1, y), &mut indices);
-        }
-        if x < map.width - 1 {
-            try_push_neighbour(index, map.index(x + 1, y), &mut indices);
-        }
-        if y > 0 {
-            try_push_neighbour(index, map.index(x, y - 1), &mut indices);
-        }
-        if y < height - 1 {
-            try_push_neighbour(index, map.index(x, y + 1), &mut indices);
+        if heightmap.dst.is_none() {
+            problems.push(String::from("best signal position E is missing"));
         }
+        problems
     }
-    distances.values[dst]
 }
 
-fn parse_input(input: impl BufRead) -> (Grid<u8>, usize, usize) {
-    let mut values = Vec::new();
-    let mut width = 0;
-    let mut src = (0, 0);
-    let mut dst = (0, 0);
-    for (y, line) in input.lines().map(|v| v.unwrap()).enumerate() {
-        width = line.len();
-        for (x, v) in line.bytes().enumerate() {
-            match v {
-                b'S' => {
-                    values.push(b'a');
-                    src = (x, y);
-                }
-                b'E' => {
-                    values.push(b'z');
-                    dst = (x, y);
-                }
-                v => values.push(v),
-            }
-        }
-    }
-    let grid = Grid { values, width };
-    let src_index = grid.index(src.0, src.1);
-    let dst_index = grid.index(dst.0, dst.1);
-    (grid, src_index, dst_index)
+pub struct Heightmap {
+    pub map: Grid<u8>,
+    pub src: Option<usize>,
+    pub dst: Option<usize>,
+}
+
+pub fn find_shortest_path_lengths(
+    input: impl BufRead,
+) -> Result<(Option<u64>, Option<u64>), ParseError> {
+    let puzzle = Day12.parse(input)?;
+    Ok((Day12.part_one(&puzzle), Day12.part_two(&puzzle)))
+}
+
+pub fn find_shortest_path_length(src: usize, dst: usize, map: &Grid<u8>) -> Option<u64> {
+    let mut problem = Climbing { map, dst };
+    search(&mut problem, (src, 0), Budget::UNLIMITED)
+        .ok()
+        .map(|v| v.state.1)
 }
 
-struct Grid<T> {
-    values: Vec<T>,
-    width: usize,
+struct Climbing<'a> {
+    map: &'a Grid<u8>,
+    dst: usize,
 }
 
-impl<T> Grid<T> {
-    fn index(&self, x: usize, y: usize) -> usize {
-        x + y * self.width
+impl Problem for Climbing<'_> {
+    type State = (usize, u64);
+    type Key = usize;
+    type Cost = u64;
+
+    fn key(&self, (index, _): &Self::State) -> Self::Key {
+        *index
+    }
+
+    fn cost(&self, (_, length): &Self::State) -> Self::Cost {
+        *length
+    }
+
+    fn heuristic(&self, (index, _): &Self::State) -> Self::Cost {
+        let (x, y) = self.map.position(*index);
+        let (dst_x, dst_y) = self.map.position(self.dst);
+        (x.abs_diff(dst_x) + y.abs_diff(dst_y)) as u64
     }
 
-    fn position(&self, index: usize) -> (usize, usize) {
-        (index % self.width, index / self.width)
+    fn is_goal(&self, (index, _): &Self::State) -> bool {
+        *index == self.dst
+    }
+
+    fn successors(&mut self, (index, length): &Self::State, successors: &mut Vec<Self::State>) {
+        let (x, y) = self.map.position(*index);
+        for (next_x, next_y) in self.map.neighbors4(x, y) {
+            if (self.map[(next_x, next_y)] as i16 - self.map[(x, y)] as i16) > 1 {
+                continue;
+            }
+            successors.push((self.map.index(next_x, next_y), length + 1));
+        }
     }
 }
 
+pub fn parse_input(input: impl BufRead) -> Result<Heightmap, ParseError> {
+    let mut src = None;
+    let mut dst = None;
+    let map = parse_grid(input, "elevation letter", |v, position| match v {
+        'S' => {
+            src = Some(position);
+            Some(b'a')
+        }
+        'E' => {
+            dst = Some(position);
+            Some(b'z')
+        }
+        v if v.is_ascii_lowercase() => Some(v as u8),
+        _ => None,
+    })?;
+    Ok(Heightmap {
+        src: src.map(|(x, y)| map.index(x, y)),
+        dst: dst.map(|(x, y)| map.index(x, y)),
+        map,
+    })
+}
+
 #[test]
 fn example_test() {
     let buffer = r#"Sabqponm
@@ -112,7 +137,7 @@ acctuvwj
 abdefghi
 "#
     .as_bytes();
-    assert_eq!(find_shortest_path_lengths(buffer), (31, 29));
+    assert_eq!(find_shortest_path_lengths(buffer), Ok((Some(31), Some(29))));
 }
 
 #[test]
@@ -120,7 +145,7 @@ fn example_test_1() {
     let buffer = r#"SabcdefghijklmnopqrstuvwxyzE
 "#
     .as_bytes();
-    assert_eq!(find_shortest_path_lengths(buffer), (27, 26));
+    assert_eq!(find_shortest_path_lengths(buffer), Ok((Some(27), Some(26))));
 }
 
 #[test]
@@ -128,7 +153,7 @@ fn example_test_2() {
     let buffer = r#"SabcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyzE
 "#
     .as_bytes();
-    assert_eq!(find_shortest_path_lengths(buffer), (53, 26));
+    assert_eq!(find_shortest_path_lengths(buffer), Ok((Some(53), Some(26))));
 }
 
 #[test]
@@ -136,5 +161,29 @@ fn example_test_3() {
     let buffer = r#"abefijmnqruvyz
 ScdghklopstwxE"#
         .as_bytes();
-    assert_eq!(find_shortest_path_lengths(buffer), (27, 26));
+    assert_eq!(find_shortest_path_lengths(buffer), Ok((Some(27), Some(26))));
+}
+
+#[test]
+fn invalid_heightmap_test() {
+    let problems = |buffer: &str| Day12.validate(&parse_input(buffer.as_bytes()).unwrap());
+    assert_eq!(
+        problems("abc\nabE\n"),
+        vec![String::from("start position S is missing")]
+    );
+    assert_eq!(
+        problems("abc\nabc\n"),
+        vec![
+            String::from("start position S is missing"),
+            String::from("best signal position E is missing"),
+        ]
+    );
+    assert_eq!(
+        find_shortest_path_lengths("Sbc\nabc\n".as_bytes()),
+        Ok((None, None))
+    );
+    assert_eq!(
+        find_shortest_path_lengths("SaE\n".as_bytes()),
+        Ok((None, None))
+    );
 }
@@ -2,23 +2,42 @@ use serde_json::Value;
 use std::cmp::Ordering;
 use std::io::BufRead;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
-}
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+
+    type Puzzle = Vec<Pair>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_input(input)
+    }
 
-fn compute_result(input: impl BufRead) -> (usize, usize) {
-    let pairs = parse_input(input);
-    (
-        sum_indices_of_the_pairs_in_the_right_order(&pairs),
+    fn part_one(&self, pairs: &Self::Puzzle) -> Self::PartOne {
+        sum_indices_of_the_pairs_in_the_right_order(pairs)
+    }
+
+    fn part_two(&self, pairs: &Self::Puzzle) -> Self::PartTwo {
         multiply_indices_of_the_divider_packets(
-            &pairs,
+            pairs,
             &Packet::List(vec![Packet::List(vec![Packet::Number(2)])]),
             &Packet::List(vec![Packet::List(vec![Packet::Number(6)])]),
-        ),
-    )
+        )
+    }
+}
+
+pub fn compute_result(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let pairs = Day13.parse(input)?;
+    Ok((Day13.part_one(&pairs), Day13.part_two(&pairs)))
 }
 
-fn multiply_indices_of_the_divider_packets(
+pub fn multiply_indices_of_the_divider_packets(
     pairs: &[Pair],
     first_divider: &Packet,
     second_divider: &Packet,
@@ -34,7 +53,7 @@ fn multiply_indices_of_the_divider_packets(
     (first_index + 1) * (second_index + 2)
 }
 
-fn sum_indices_of_the_pairs_in_the_right_order(pairs: &[Pair]) -> usize {
+pub fn sum_indices_of_the_pairs_in_the_right_order(pairs: &[Pair]) -> usize {
     pairs
         .iter()
         .enumerate()
@@ -43,7 +62,7 @@ fn sum_indices_of_the_pairs_in_the_right_order(pairs: &[Pair]) -> usize {
         .sum()
 }
 
-fn compare_packets(left: &Packet, right: &Packet) -> std::cmp::Ordering {
+pub fn compare_packets(left: &Packet, right: &Packet) -> std::cmp::Ordering {
     match (left, right) {
         (Packet::Number(l), Packet::Number(r)) => l.cmp(r),
         (Packet::List(_), Packet::Number(_)) => {
@@ -66,41 +85,58 @@ fn compare_lists(left_values: &[Packet], right_values: &[Packet]) -> std::cmp::O
     left_values.len().cmp(&right_values.len())
 }
 
-fn parse_input(input: impl BufRead) -> Vec<Pair> {
+pub fn parse_input(input: impl BufRead) -> Result<Vec<Pair>, ParseError> {
     let mut values = Vec::new();
     let mut first = None;
     let mut second = None;
-    for line in input.lines().map(|v| v.unwrap()) {
-        if line.is_empty() {
-            values.push((first.take().unwrap(), second.take().unwrap()));
+    let mut lines_count = 0;
+    for line in lines(input) {
+        let line = line?;
+        lines_count = line.number;
+        if line.text.is_empty() {
+            match (first.take(), second.take()) {
+                (Some(first), Some(second)) => values.push((first, second)),
+                _ => return Err(line.error(&line.text, "packet")),
+            }
         } else if first.is_none() {
-            first = Some(parse_list(&line));
+            first = Some(parse_list(&line)?);
         } else if second.is_none() {
-            second = Some(parse_list(&line));
+            second = Some(parse_list(&line)?);
+        } else {
+            return Err(line.error(&line.text, "empty line"));
         }
     }
-    if let (Some(first), Some(second)) = (first, second) {
-        values.push((first, second));
+    match (first, second) {
+        (Some(first), Some(second)) => values.push((first, second)),
+        (None, None) => (),
+        _ => return Err(ParseError::new(lines_count + 1, 1, "", "packet")),
     }
-    values
+    Ok(values)
 }
 
-fn parse_list(value: &str) -> Packet {
-    make_packet(&serde_json::from_str::<Value>(value).unwrap())
+fn parse_list(line: &Line) -> Result<Packet, ParseError> {
+    let value = serde_json::from_str::<Value>(&line.text).map_err(|e| {
+        let column = e.column().max(1).min(line.text.len().max(1));
+        let token = line.text.get(column - 1..column).unwrap_or("");
+        ParseError::new(line.number, column, token, "packet")
+    })?;
+    make_packet(&value).ok_or_else(|| line.error(&line.text, "list of non-negative integers"))
 }
 
-fn make_packet(value: &Value) -> Packet {
+fn make_packet(value: &Value) -> Option<Packet> {
     match value {
-        Value::Number(v) => Packet::Number(v.as_u64().unwrap()),
-        Value::Array(values) => Packet::List(values.iter().map(make_packet).collect()),
-        _ => unreachable!(),
+        Value::Number(v) => Some(Packet::Number(v.as_u64()?)),
+        Value::Array(values) => Some(Packet::List(
+            values.iter().map(make_packet).collect::<Option<_>>()?,
+        )),
+        _ => None,
     }
 }
 
-type Pair = (Packet, Packet);
+pub type Pair = (Packet, Packet);
 
 #[derive(Clone)]
-enum Packet {
+pub enum Packet {
     Number(u64),
     List(Vec<Packet>),
 }
@@ -113,7 +149,7 @@ impl PartialEq<Self> for Packet {
 
 impl PartialOrd for Packet {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(compare_packets(self, other))
+        Some(self.cmp(other))
     }
 }
 
@@ -152,5 +188,5 @@ fn example_test() {
 [1,[2,[3,[4,[5,6,0]]]],8,9]
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (13, 140));
+    assert_eq!(compute_result(buffer), Ok((13, 140)));
 }
@@ -1,23 +1,62 @@
 use std::collections::HashSet;
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", count_sand_positions(std::io::stdin().lock()));
+use serde_json::json;
+
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{unknown_parameter, Solution};
+use crate::trace::{Trace, Tracer};
+use crate::viewer::{Bounds, Scene};
+
+#[derive(Default)]
+pub struct Day14 {
+    pub trace: Trace,
 }
 
-fn count_sand_positions(input: impl BufRead) -> (usize, usize) {
-    let grid = parse_map(input);
-    (
-        count_stable_sand_positions(grid.clone()),
-        count_stable_sand_positions_with_floor(grid),
-    )
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    const PARAMETERS: &'static [&'static str] = &["trace"];
+
+    type Puzzle = HashSet<(usize, usize)>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_map(input)
+    }
+
+    fn part_one(&self, map: &Self::Puzzle) -> Self::PartOne {
+        count_stable_sand_positions(map.clone(), self.trace.part(1))
+    }
+
+    fn part_two(&self, map: &Self::Puzzle) -> Self::PartTwo {
+        count_stable_sand_positions_with_floor(map.clone(), self.trace.part(2))
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "trace" => self.trace = Trace::create(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+}
+
+pub fn count_sand_positions(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let grid = parse_map(input)?;
+    Ok((
+        count_stable_sand_positions(grid.clone(), Tracer::disabled()),
+        count_stable_sand_positions_with_floor(grid, Tracer::disabled()),
+    ))
 }
 
 const START_X: usize = 500;
 const START_Y: usize = 0;
 
-fn count_stable_sand_positions_with_floor(mut map: HashSet<(usize, usize)>) -> usize {
+pub fn count_stable_sand_positions_with_floor(
+    mut map: HashSet<(usize, usize)>,
+    tracer: Tracer,
+) -> usize {
     let mut stable_sand_count = 0;
     let max_y = *map.iter().map(|(_, y)| y).max().unwrap() + 1;
     loop {
@@ -32,6 +71,10 @@ fn count_stable_sand_positions_with_floor(mut map: HashSet<(usize, usize)>) -> u
             }
         }
         map.insert((x, y));
+        tracer.event(
+            stable_sand_count,
+            || json!({"event": "rest", "x": x, "y": y}),
+        );
         stable_sand_count += 1;
         if x == START_X && y == START_Y {
             break;
@@ -40,7 +83,7 @@ fn count_stable_sand_positions_with_floor(mut map: HashSet<(usize, usize)>) -> u
     stable_sand_count
 }
 
-fn count_stable_sand_positions(mut map: HashSet<(usize, usize)>) -> usize {
+pub fn count_stable_sand_positions(mut map: HashSet<(usize, usize)>, tracer: Tracer) -> usize {
     let mut stable_sand_count = 0;
     let max_y = *map.iter().map(|(_, y)| y).max().unwrap();
     loop {
@@ -55,15 +98,20 @@ fn count_stable_sand_positions(mut map: HashSet<(usize, usize)>) -> usize {
             }
         };
         if !contains {
+            tracer.event(stable_sand_count, || json!({"event": "fall", "x": x}));
             break;
         }
         map.insert((x, y));
+        tracer.event(
+            stable_sand_count,
+            || json!({"event": "rest", "x": x, "y": y}),
+        );
         stable_sand_count += 1;
     }
     stable_sand_count
 }
 
-fn try_move_sand(map: &HashSet<(usize, usize)>, x: &mut usize, y: &mut usize) -> bool {
+pub fn try_move_sand(map: &HashSet<(usize, usize)>, x: &mut usize, y: &mut usize) -> bool {
     if !map.contains(&(*x, *y + 1)) {
         *y += 1;
         true
@@ -80,13 +128,108 @@ fn try_move_sand(map: &HashSet<(usize, usize)>, x: &mut usize, y: &mut usize) ->
     }
 }
 
-fn parse_map(input: impl BufRead) -> HashSet<(usize, usize)> {
+pub struct SandScene {
+    rocks: HashSet<(usize, usize)>,
+    map: HashSet<(usize, usize)>,
+    max_y: usize,
+    floor: bool,
+    grain: (usize, usize),
+    count: usize,
+    x_range: (usize, usize),
+    finished: bool,
+}
+
+impl SandScene {
+    pub fn new(map: HashSet<(usize, usize)>, floor: bool) -> Self {
+        let max_y = map.iter().map(|(_, y)| *y).max().unwrap_or(0);
+        let min_x = map.iter().map(|(x, _)| *x).min().unwrap_or(START_X);
+        let max_x = map.iter().map(|(x, _)| *x).max().unwrap_or(START_X);
+        Self {
+            rocks: map.clone(),
+            map,
+            max_y,
+            floor,
+            grain: (START_X, START_Y),
+            count: 0,
+            x_range: (min_x.min(START_X), max_x.max(START_X)),
+            finished: false,
+        }
+    }
+}
+
+impl Scene for SandScene {
+    fn bounds(&self) -> Bounds {
+        Bounds {
+            x: self.x_range.0 as i64 - 2..self.x_range.1 as i64 + 3,
+            y: START_Y as i64..self.max_y as i64 + 3,
+        }
+    }
+
+    fn focus(&self) -> (i64, i64) {
+        (self.grain.0 as i64, self.grain.1 as i64)
+    }
+
+    fn tile(&self, x: i64, y: i64) -> char {
+        if x < 0 || y < 0 {
+            return '.';
+        }
+        let position = (x as usize, y as usize);
+        if position == self.grain && !self.finished {
+            '@'
+        } else if self.rocks.contains(&position) {
+            '#'
+        } else if self.map.contains(&position) {
+            'o'
+        } else if self.floor && position.1 == self.max_y + 2 {
+            '#'
+        } else if position == (START_X, START_Y) {
+            '+'
+        } else {
+            '.'
+        }
+    }
+
+    fn status(&self) -> String {
+        format!("sand at rest: {}", self.count)
+    }
+
+    fn step(&mut self) -> bool {
+        if self.finished {
+            return false;
+        }
+        let (mut x, mut y) = self.grain;
+        if (!self.floor || y <= self.max_y) && try_move_sand(&self.map, &mut x, &mut y) {
+            self.grain = (x, y);
+            self.finished = !self.floor && y > self.max_y;
+            return !self.finished;
+        }
+        self.map.insert(self.grain);
+        self.count += 1;
+        self.x_range = (self.x_range.0.min(x), self.x_range.1.max(x));
+        self.finished = self.grain == (START_X, START_Y);
+        self.grain = (START_X, START_Y);
+        !self.finished
+    }
+}
+
+pub fn parse_map(input: impl BufRead) -> Result<HashSet<(usize, usize)>, ParseError> {
     let mut rocks = HashSet::new();
-    for line in input.lines().map(|v| v.unwrap()) {
-        let split = line.split(" -> ");
-        for (a, b) in split.clone().zip(split.skip(1)) {
-            let (ax, ay) = parse_position(a);
-            let (bx, by) = parse_position(b);
+    let mut count = 0;
+    for line in lines(input) {
+        let line = line?;
+        count = line.number;
+        let positions = line
+            .text
+            .split(" -> ")
+            .map(|v| parse_position(&line, v).map(|position| (v, position)))
+            .collect::<Result<Vec<_>, _>>()?;
+        rocks.insert(positions[0].1);
+        for pair in positions.windows(2) {
+            let (_, (ax, ay)) = pair[0];
+            let (b, (bx, by)) = pair[1];
+            if ax != bx && ay != by {
+                return Err(line.error(b, "horizontal or vertical segment end"));
+            }
             if ax == bx {
                 for y in ay.min(by)..=ay.max(by) {
                     rocks.insert((ax, y));
@@ -98,12 +241,21 @@ fn parse_map(input: impl BufRead) -> HashSet<(usize, usize)> {
             }
         }
     }
-    rocks
+    if rocks.is_empty() {
+        return Err(ParseError::end_of_input(
+            count + 1,
+            "at least one rock path",
+        ));
+    }
+    Ok(rocks)
 }
 
-fn parse_position(value: &str) -> (usize, usize) {
-    let (x, y) = value.split_once(',').unwrap();
-    (usize::from_str(x).unwrap(), usize::from_str(y).unwrap())
+fn parse_position(line: &Line, value: &str) -> Result<(usize, usize), ParseError> {
+    let (x, y) = line.split_once(value, ",")?;
+    Ok((
+        line.parse(x, "x coordinate")?,
+        line.parse(y, "y coordinate")?,
+    ))
 }
 
 #[test]
@@ -112,5 +264,20 @@ fn example_test() {
 503,4 -> 502,4 -> 502,9 -> 494,9
 "#
     .as_bytes();
-    assert_eq!(count_sand_positions(buffer), (24, 93));
+    assert_eq!(count_sand_positions(buffer), Ok((24, 93)));
+}
+
+#[test]
+fn sand_scene_test() {
+    let buffer = r#"498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9
+"#
+    .as_bytes();
+    let map = parse_map(buffer).unwrap();
+    for (floor, expected) in [(false, "sand at rest: 24"), (true, "sand at rest: 93")] {
+        let mut scene = SandScene::new(map.clone(), floor);
+        while scene.step() {}
+        assert_eq!(scene.status(), expected);
+        assert_eq!(scene.tile(500, 0), if floor { 'o' } else { '+' });
+    }
 }
@@ -1,22 +1,83 @@
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!(
-        "{:?}",
-        compute_result(std::io::stdin().lock(), 2000000, 0, 4000000)
-    );
+use crate::interval::IntervalSet;
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+
+pub struct Day15 {
+    pub y: i64,
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Default for Day15 {
+    fn default() -> Self {
+        Self {
+            y: 2000000,
+            min: 0,
+            max: 4000000,
+        }
+    }
 }
 
-fn compute_result(input: impl BufRead, y: i64, min: i64, max: i64) -> (usize, i64) {
-    let sensors = parse_sensors(input);
-    (
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    const PARAMETERS: &'static [&'static str] = &["row", "min", "max"];
+
+    type Puzzle = Vec<Sensor>;
+    type PartOne = usize;
+    type PartTwo = Option<i64>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_sensors(input)
+    }
+
+    fn part_one(&self, sensors: &Self::Puzzle) -> Self::PartOne {
+        count_positions_without_beacon(sensors, self.y)
+    }
+
+    fn part_two(&self, sensors: &Self::Puzzle) -> Self::PartTwo {
+        find_tuning_frequency(sensors, self.min, self.max)
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "row" => self.y = parse_parameter(name, value)?,
+            "min" | "max" => {
+                let value: i64 = parse_parameter(name, value)?;
+                let (min, max) = if name == "min" {
+                    (value, self.max)
+                } else {
+                    (self.min, value)
+                };
+                if min > max {
+                    return Err(format!(
+                        "invalid value for --{}: {} (--min {} is greater than --max {})",
+                        name, value, min, max
+                    ));
+                }
+                (self.min, self.max) = (min, max);
+            }
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+}
+
+pub fn compute_result(
+    input: impl BufRead,
+    y: i64,
+    min: i64,
+    max: i64,
+) -> Result<(usize, Option<i64>), ParseError> {
+    let sensors = parse_sensors(input)?;
+    Ok((
         count_positions_without_beacon(&sensors, y),
         find_tuning_frequency(&sensors, min, max),
-    )
+    ))
 }
 
-fn find_tuning_frequency(sensors: &[Sensor], min: i64, max: i64) -> i64 {
+pub fn find_tuning_frequency(sensors: &[Sensor], min: i64, max: i64) -> Option<i64> {
     for x in min..=max {
         let mut y = min;
         while y <= max {
@@ -35,63 +96,61 @@ fn find_tuning_frequency(sensors: &[Sensor], min: i64, max: i64) -> i64 {
                 y += dy;
                 continue;
             }
-            return x * 4000000 + y;
+            return Some(x * 4000000 + y);
         }
     }
-    unreachable!()
+    None
 }
 
-fn count_positions_without_beacon(sensors: &[Sensor], y: i64) -> usize {
-    let min_x = sensors
+pub fn count_positions_without_beacon(sensors: &[Sensor], y: i64) -> usize {
+    let beacons: IntervalSet = sensors
         .iter()
-        .map(|v| v.position.0 - v.radius)
-        .min()
-        .unwrap();
-    let max_x = sensors
+        .filter(|v| v.beacon.1 == y)
+        .map(|v| v.beacon.0..=v.beacon.0)
+        .collect();
+    get_row_coverage(sensors, y).difference(&beacons).len() as usize
+}
+
+pub fn get_row_coverage(sensors: &[Sensor], y: i64) -> IntervalSet {
+    sensors
         .iter()
-        .map(|v| v.position.0 + v.radius)
-        .max()
-        .unwrap();
-    (min_x..=max_x)
-        .map(|x| {
-            sensors
-                .iter()
-                .any(|v| v.beacon != (x, y) && manhattan_distance(&v.position, &(x, y)) <= v.radius)
-                as usize
+        .map(|v| {
+            let dx = v.radius - (v.position.1 - y).abs();
+            v.position.0 - dx..=v.position.0 + dx
         })
-        .sum()
+        .collect()
 }
 
-fn manhattan_distance(a: &(i64, i64), b: &(i64, i64)) -> i64 {
+pub fn manhattan_distance(a: &(i64, i64), b: &(i64, i64)) -> i64 {
     (a.0 - b.0).abs() + (a.1 - b.1).abs()
 }
 
-fn parse_sensors(input: impl BufRead) -> Vec<Sensor> {
-    input
-        .lines()
+pub fn parse_sensors(input: impl BufRead) -> Result<Vec<Sensor>, ParseError> {
+    lines(input)
         .map(|v| {
-            let line = v.unwrap();
-            let (sensor, beacon) = line.split_once(": closest beacon is at ").unwrap();
-            let sensor_position = parse_position(sensor.strip_prefix("Sensor at ").unwrap());
-            let beacon_position = parse_position(beacon);
-            Sensor {
+            let line = v?;
+            let (sensor, beacon) = line.split_once(&line.text, ": closest beacon is at ")?;
+            let sensor_position = parse_position(&line, line.strip_prefix(sensor, "Sensor at ")?)?;
+            let beacon_position = parse_position(&line, beacon)?;
+            Ok(Sensor {
                 position: sensor_position,
                 beacon: beacon_position,
                 radius: manhattan_distance(&sensor_position, &beacon_position),
-            }
+            })
         })
         .collect()
 }
 
-fn parse_position(value: &str) -> (i64, i64) {
-    let (x, y) = value.split_once(", ").unwrap();
-    (
-        i64::from_str(x.strip_prefix("x=").unwrap()).unwrap(),
-        i64::from_str(y.strip_prefix("y=").unwrap()).unwrap(),
-    )
+fn parse_position(line: &Line, value: &str) -> Result<(i64, i64), ParseError> {
+    let (x, y) = line.split_once(value, ", ")?;
+    Ok((
+        line.parse(line.strip_prefix(x, "x=")?, "x coordinate")?,
+        line.parse(line.strip_prefix(y, "y=")?, "y coordinate")?,
+    ))
 }
 
-struct Sensor {
+#[derive(Debug, Clone)]
+pub struct Sensor {
     position: (i64, i64),
     beacon: (i64, i64),
     radius: i64,
@@ -115,5 +174,75 @@ Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer, 10, 0, 20), (26, 56000011));
+    assert_eq!(compute_result(buffer, 10, 0, 20), Ok((26, Some(56000011))));
+    let covered = "Sensor at x=5, y=5: closest beacon is at x=5, y=15\n".as_bytes();
+    assert_eq!(compute_result(covered, 0, 0, 10), Ok((11, None)));
+    let mut solution = Day15::default();
+    assert_eq!(
+        solution
+            .set_parameter("min", "10")
+            .and(solution.set_parameter("max", "5")),
+        Err(String::from(
+            "invalid value for --max: 5 (--min 10 is greater than --max 5)"
+        ))
+    );
+}
+
+#[cfg(test)]
+fn find_uncovered_positions(sensors: &[Sensor], min: i64, max: i64) -> Vec<(i64, i64)> {
+    (min..=max)
+        .flat_map(|x| (min..=max).map(move |y| (x, y)))
+        .filter(|p| {
+            sensors
+                .iter()
+                .all(|v| manhattan_distance(&v.position, p) > v.radius)
+        })
+        .collect()
+}
+
+#[test]
+fn tuning_frequency_property_test() {
+    use crate::property::{check, expect_eq, shrink_vec};
+
+    const MAX: i64 = 20;
+    check(
+        "find_tuning_frequency",
+        200,
+        |rng| {
+            let hidden = (rng.range(0, MAX), rng.range(0, MAX));
+            let mut positions = vec![
+                (-rng.range(0, 5), -rng.range(0, 5)),
+                (-rng.range(0, 5), MAX + rng.range(0, 5)),
+                (MAX + rng.range(0, 5), -rng.range(0, 5)),
+                (MAX + rng.range(0, 5), MAX + rng.range(0, 5)),
+            ];
+            for _ in 0..rng.range(0, 8) {
+                positions.push((rng.range(-5, MAX + 5), rng.range(-5, MAX + 5)));
+            }
+            let mut sensors = Vec::new();
+            for (i, position) in positions.into_iter().enumerate() {
+                let limit = manhattan_distance(&position, &hidden) - 1;
+                if limit < 0 {
+                    continue;
+                }
+                let radius = if i < 4 { limit } else { rng.range(0, limit) };
+                let dx = rng.range(-radius, radius);
+                let dy = (radius - dx.abs()) * if rng.chance(1, 2) { 1 } else { -1 };
+                sensors.push(Sensor {
+                    position,
+                    beacon: (position.0 + dx, position.1 + dy),
+                    radius,
+                });
+            }
+            sensors
+        },
+        |sensors| shrink_vec(sensors, |_| Vec::new()),
+        |sensors| match find_uncovered_positions(sensors, 0, MAX).as_slice() {
+            [(x, y)] => expect_eq(
+                find_tuning_frequency(sensors, 0, MAX),
+                Some(x * 4000000 + y),
+            ),
+            _ => Ok(()),
+        },
+    );
 }
@@ -1,22 +1,91 @@
 use std::cmp::Reverse;
-use std::collections::hash_map::Entry;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
+use crate::parse::{lines, ParseError};
+use crate::search::{search, Budget, Limits, Problem, SearchError};
+use crate::solution::{parse_parameter, Solution};
+
+pub struct Day16 {
+    pub max_minute: u8,
+    pub teach_elephant_time: u8,
+    pub limits: Limits,
+}
+
+impl Default for Day16 {
+    fn default() -> Self {
+        Self {
+            max_minute: 30,
+            teach_elephant_time: 4,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    const PARAMETERS: &'static [&'static str] = &[
+        "minutes",
+        "teach-time",
+        "max-iterations",
+        "max-states",
+        "timeout",
+        "max-memory",
+    ];
+
+    type Puzzle = Context;
+    type PartOne = Result<u16, SearchError<u16>>;
+    type PartTwo = Result<u16, SearchError<u16>>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        make_context(input)
+    }
+
+    fn part_one(&self, context: &Self::Puzzle) -> Self::PartOne {
+        find_max_released_pressure(context, self.max_minute, self.budget())
+    }
+
+    fn part_two(&self, context: &Self::Puzzle) -> Self::PartTwo {
+        find_max_released_pressure_with_elephant(
+            context,
+            self.max_minute,
+            self.teach_elephant_time,
+            self.budget(),
+        )
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "minutes" => self.max_minute = parse_parameter(name, value)?,
+            "teach-time" => self.teach_elephant_time = parse_parameter(name, value)?,
+            _ => self.limits.set_parameter(name, value)?,
+        }
+        Ok(())
+    }
+}
+
+impl Day16 {
+    fn budget(&self) -> Budget {
+        self.limits.start(Budget::states(MAX_STATES))
+    }
 }
 
-const MAX_MINUTE: u8 = 30;
 const OPEN_VALVE_TIME: u8 = 1;
 const MOVE_TIME: u8 = 1;
-const TEACH_ELEPHANT_TIME: u8 = 4;
 const MAX_STATES: usize = 32000000;
 
-fn compute_result(input: impl BufRead) -> (u16, u16) {
-    let (nodes, start) = parse_valve_graph(input);
-    let context = Context {
+pub fn compute_result(input: impl BufRead) -> Result<(u16, u16), ParseError> {
+    let context = make_context(input)?;
+    let solution = Day16::default();
+    Ok((
+        solution.part_one(&context).expect("max released pressure"),
+        solution.part_two(&context).expect("max released pressure"),
+    ))
+}
+
+pub fn make_context(input: impl BufRead) -> Result<Context, ParseError> {
+    let (nodes, start) = parse_valve_graph(input)?;
+    Ok(Context {
         start,
         valves_with_non_zero_flow_rate: nodes
             .iter()
@@ -26,55 +95,66 @@ fn compute_result(input: impl BufRead) -> (u16, u16) {
             .collect(),
         max_flow_rate: nodes.iter().map(|v| v.flow_rate).sum(),
         nodes,
-    };
-    (
-        find_max_released_pressure(&context),
-        find_max_released_pressure_with_elephant(&context),
+    })
+}
+
+pub fn find_max_released_pressure(
+    context: &Context,
+    max_minute: u8,
+    budget: Budget,
+) -> Result<u16, SearchError<u16>> {
+    search(
+        &mut ValveOpening {
+            context,
+            max_minute,
+        },
+        context.make_initial_state(0),
+        budget,
     )
+    .map(|v| v.state.released_pressure)
+    .map_err(|e| e.map_best(|v| v.released_pressure))
 }
 
-fn find_max_released_pressure(context: &Context) -> u16 {
-    let mut states = vec![context.make_initial_state()];
-    let mut new_states = BinaryHeap::new();
-    new_states.push((0, 0));
-    let mut max_released_pressure = 0;
-    let mut visited: HashMap<Key, usize> = HashMap::new();
-    visited.insert(make_state_key(&states[0]), 0);
-    while let Some((_, state_index)) = new_states.pop() {
-        if states[state_index].minute == MAX_MINUTE {
-            max_released_pressure = states[state_index].released_pressure;
-            break;
-        }
-        if states.len() >= MAX_STATES {
-            continue;
-        }
-        let flow_rate = context.get_total_flow_rate(&states[state_index].open_valves);
-        for action in context.generate_actions(0, &states[state_index]) {
-            let mut new_state = states[state_index].clone();
-            let duration = apply_action(0, &action, &mut new_state);
+struct ValveOpening<'a> {
+    context: &'a Context,
+    max_minute: u8,
+}
+
+impl Problem for ValveOpening<'_> {
+    type State = State;
+    type Key = Key;
+    type Cost = i32;
+
+    fn key(&self, state: &State) -> Key {
+        make_state_key(state)
+    }
+
+    fn cost(&self, state: &State) -> i32 {
+        get_cost(state)
+    }
+
+    fn heuristic(&self, state: &State) -> i32 {
+        self.context.get_heuristic(state, self.max_minute)
+    }
+
+    fn is_goal(&self, state: &State) -> bool {
+        state.minute == self.max_minute
+    }
+
+    fn fallback(&self, state: &State) -> Option<State> {
+        Some(self.context.make_idle_state(state, self.max_minute))
+    }
+
+    fn successors(&mut self, state: &State, successors: &mut Vec<State>) {
+        let flow_rate = self.context.get_total_flow_rate(&state.open_valves);
+        for action in self.context.generate_actions(0, state, self.max_minute) {
+            let mut new_state = state.clone();
+            let duration = apply_action(0, &action, &mut new_state, self.max_minute);
             new_state.released_pressure += flow_rate * duration as u16;
             new_state.minute += duration;
-            let priority = context.get_priority(&new_state);
-            let new_state_index = match visited.entry(make_state_key(&new_state)) {
-                Entry::Occupied(v) => {
-                    let new_state_index = *v.get();
-                    if states[new_state_index].released_pressure >= new_state.released_pressure {
-                        continue;
-                    }
-                    states[new_state_index].released_pressure = new_state.released_pressure;
-                    new_state_index
-                }
-                Entry::Vacant(v) => {
-                    let new_state_index = states.len();
-                    v.insert(new_state_index);
-                    states.push(new_state);
-                    new_state_index
-                }
-            };
-            new_states.push((priority, new_state_index));
+            successors.push(new_state);
         }
     }
-    max_released_pressure
 }
 
 type Key = (u8, usize, Vec<usize>);
@@ -85,32 +165,70 @@ fn make_state_key(state: &State) -> Key {
     (state.minute, state.positions[0], open_valves)
 }
 
-fn find_max_released_pressure_with_elephant(context: &Context) -> u16 {
-    let mut states = vec![context.make_initial_state_with_elephant()];
-    let mut new_states = BinaryHeap::new();
-    new_states.push((0, 0));
-    let mut max_released_pressure = 0;
-    let mut visited: HashMap<FullKey, usize> = HashMap::new();
-    visited.insert(make_state_key_full(&states[0]), 0);
-    while let Some((_, state_index)) = new_states.pop() {
-        if states[state_index].minute == MAX_MINUTE {
-            max_released_pressure = states[state_index].released_pressure;
-            break;
-        }
-        if states.len() >= MAX_STATES {
-            continue;
-        }
-        let flow_rate = context.get_total_flow_rate(&states[state_index].open_valves);
-        let actions1 = context.generate_actions(0, &states[state_index]);
-        let actions2 = context.generate_actions(1, &states[state_index]);
+pub fn find_max_released_pressure_with_elephant(
+    context: &Context,
+    max_minute: u8,
+    teach_elephant_time: u8,
+    budget: Budget,
+) -> Result<u16, SearchError<u16>> {
+    search(
+        &mut ValveOpeningWithElephant {
+            context,
+            max_minute,
+        },
+        context.make_initial_state(teach_elephant_time.min(max_minute)),
+        budget,
+    )
+    .map(|v| v.state.released_pressure)
+    .map_err(|e| e.map_best(|v| v.released_pressure))
+}
+
+struct ValveOpeningWithElephant<'a> {
+    context: &'a Context,
+    max_minute: u8,
+}
+
+impl Problem for ValveOpeningWithElephant<'_> {
+    type State = State;
+    type Key = FullKey;
+    type Cost = i32;
+
+    fn key(&self, state: &State) -> FullKey {
+        make_state_key_full(state)
+    }
+
+    fn cost(&self, state: &State) -> i32 {
+        get_cost(state)
+    }
+
+    fn heuristic(&self, state: &State) -> i32 {
+        self.context.get_heuristic(state, self.max_minute)
+    }
+
+    fn is_goal(&self, state: &State) -> bool {
+        state.minute == self.max_minute
+    }
+
+    fn fallback(&self, state: &State) -> Option<State> {
+        Some(self.context.make_idle_state(state, self.max_minute))
+    }
+
+    fn successors(&mut self, state: &State, successors: &mut Vec<State>) {
+        let flow_rate = self.context.get_total_flow_rate(&state.open_valves);
+        let actions1 = self.context.generate_actions(0, state, self.max_minute);
+        let actions2 = self.context.generate_actions(1, state, self.max_minute);
         for action1 in actions1.iter() {
             for action2 in actions2.iter() {
-                if are_conflicting_actions(action1, action2, &states[state_index]) {
+                let action2 = if !are_conflicting_actions(action1, action2, state) {
+                    action2
+                } else if actions2.len() == 1 {
+                    &Action::Idle
+                } else {
                     continue;
-                }
-                let mut new_state = states[state_index].clone();
-                let duration1 = apply_action(0, action1, &mut new_state);
-                let duration2 = apply_action(1, action2, &mut new_state);
+                };
+                let mut new_state = state.clone();
+                let duration1 = apply_action(0, action1, &mut new_state, self.max_minute);
+                let duration2 = apply_action(1, action2, &mut new_state, self.max_minute);
                 let duration = duration1.min(duration2);
                 new_state.busy[0] = duration1;
                 new_state.busy[1] = duration2;
@@ -119,29 +237,10 @@ fn find_max_released_pressure_with_elephant(context: &Context) -> u16 {
                 for busy in new_state.busy.iter_mut() {
                     *busy -= duration;
                 }
-                let priority = context.get_priority(&new_state);
-                let new_state_index = match visited.entry(make_state_key_full(&new_state)) {
-                    Entry::Occupied(v) => {
-                        let new_state_index = *v.get();
-                        if states[new_state_index].released_pressure >= new_state.released_pressure
-                        {
-                            continue;
-                        }
-                        states[new_state_index].released_pressure = new_state.released_pressure;
-                        new_state_index
-                    }
-                    Entry::Vacant(v) => {
-                        let new_state_index = states.len();
-                        v.insert(new_state_index);
-                        states.push(new_state);
-                        new_state_index
-                    }
-                };
-                new_states.push((priority, new_state_index));
+                successors.push(new_state);
             }
         }
     }
-    max_released_pressure
 }
 
 type FullKey = (u8, [usize; 2], [bool; 2], Vec<usize>);
@@ -157,6 +256,10 @@ fn make_state_key_full(state: &State) -> FullKey {
     )
 }
 
+fn get_cost(state: &State) -> i32 {
+    -(state.released_pressure as i32)
+}
+
 fn are_conflicting_actions(a: &Action, b: &Action, state: &State) -> bool {
     match (a, b) {
         (Action::OpenValve, Action::OpenValve) => state.positions[0] == state.positions[1],
@@ -167,17 +270,17 @@ fn are_conflicting_actions(a: &Action, b: &Action, state: &State) -> bool {
     }
 }
 
-struct Context {
+pub struct Context {
     start: usize,
-    valves_with_non_zero_flow_rate: HashMap<usize, Vec<u8>>,
+    valves_with_non_zero_flow_rate: BTreeMap<usize, Vec<u8>>,
     max_flow_rate: u16,
     nodes: Vec<Node>,
 }
 
 impl Context {
-    fn make_initial_state(&self) -> State {
+    fn make_initial_state(&self, minute: u8) -> State {
         State {
-            minute: 0,
+            minute,
             released_pressure: 0,
             positions: [self.start; 2],
             busy: [0; 2],
@@ -185,21 +288,19 @@ impl Context {
         }
     }
 
-    fn make_initial_state_with_elephant(&self) -> State {
-        State {
-            minute: TEACH_ELEPHANT_TIME,
-            released_pressure: 0,
-            positions: [self.start; 2],
-            busy: [0; 2],
-            open_valves: Vec::new(),
-        }
+    fn make_idle_state(&self, state: &State, max_minute: u8) -> State {
+        let mut result = state.clone();
+        result.released_pressure +=
+            self.get_total_flow_rate(&state.open_valves) * (max_minute - state.minute) as u16;
+        result.minute = max_minute;
+        result
     }
 
-    fn get_priority(&self, state: &State) -> u16 {
-        state.released_pressure + self.max_flow_rate * (MAX_MINUTE - state.minute) as u16
+    fn get_heuristic(&self, state: &State, max_minute: u8) -> i32 {
+        -(self.max_flow_rate as i32 * (max_minute - state.minute) as i32)
     }
 
-    fn generate_actions(&self, agent: usize, state: &State) -> Vec<Action> {
+    fn generate_actions(&self, agent: usize, state: &State, max_minute: u8) -> Vec<Action> {
         let mut actions = Vec::new();
         if state.busy[agent] > 0 {
             actions.push(Action::Busy {
@@ -215,7 +316,7 @@ impl Context {
                         continue;
                     }
                     let duration = distances[position] * MOVE_TIME;
-                    if state.minute + duration > MAX_MINUTE {
+                    if state.minute + duration > max_minute {
                         continue;
                     }
                     actions.push(Action::MoveTo {
@@ -236,10 +337,10 @@ impl Context {
     }
 }
 
-fn apply_action(agent: usize, action: &Action, state: &mut State) -> u8 {
+fn apply_action(agent: usize, action: &Action, state: &mut State, max_minute: u8) -> u8 {
     match action {
         Action::Busy { duration } => *duration,
-        Action::Idle => MAX_MINUTE - state.minute,
+        Action::Idle => max_minute - state.minute,
         Action::OpenValve => {
             assert!(!state.open_valves.contains(&state.positions[agent]));
             state.open_valves.push(state.positions[agent]);
@@ -261,7 +362,7 @@ enum Action {
 }
 
 fn find_all_shortest_paths(src: usize, nodes: &[Node]) -> Vec<u8> {
-    let mut distances: Vec<u8> = std::iter::repeat(u8::MAX).take(nodes.len()).collect();
+    let mut distances: Vec<u8> = std::iter::repeat_n(u8::MAX, nodes.len()).collect();
     distances[src] = 0;
     let mut to_visit = BinaryHeap::new();
     to_visit.push((Reverse(0), src));
@@ -287,49 +388,66 @@ struct State {
     open_valves: Vec<usize>,
 }
 
-fn parse_valve_graph(input: impl BufRead) -> (Vec<Node>, usize) {
-    let valves: Vec<Valve> = input
-        .lines()
+pub fn parse_valve_graph(input: impl BufRead) -> Result<(Vec<Node>, usize), ParseError> {
+    let mut lines_count = 0;
+    let valves: Vec<Valve> = lines(input)
         .map(|v| {
-            let line = v.unwrap();
-            let tail1 = line.strip_prefix("Valve ").unwrap();
-            let (name, tail2) = tail1.split_once(" has flow rate=").unwrap();
-            let (flow_rate, tail3) = tail2.split_once("; ").unwrap();
-            Valve {
-                name: name.to_string(),
-                flow_rate: u16::from_str(flow_rate).unwrap(),
-                tunnels_to: if let Some(tunnels) = tail3.strip_prefix("tunnels lead to valves ") {
-                    tunnels.split(", ").map(|v| v.to_string()).collect()
+            let line = v?;
+            lines_count = line.number;
+            let tail1 = line.strip_prefix(&line.text, "Valve ")?;
+            let (name, tail2) = line.split_once(tail1, " has flow rate=")?;
+            let (flow_rate, tail3) = line.split_once(tail2, "; ")?;
+            let tunnels: Vec<&str> =
+                if let Some(tunnels) = tail3.strip_prefix("tunnels lead to valves ") {
+                    tunnels.split(", ").collect()
                 } else if let Some(tunnel) = tail3.strip_prefix("tunnel leads to valve ") {
-                    vec![tunnel.to_string()]
+                    vec![tunnel]
                 } else {
-                    unreachable!()
-                },
-            }
+                    return Err(line.error(tail3, "tunnels description"));
+                };
+            Ok(Valve {
+                name: name.to_string(),
+                flow_rate: line.parse(flow_rate, "flow rate")?,
+                tunnels_to: tunnels
+                    .iter()
+                    .map(|v| (v.to_string(), line.number, line.column(v)))
+                    .collect(),
+            })
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
     let mut valve_index_by_name = HashMap::new();
     for (i, valve) in valves.iter().enumerate() {
         valve_index_by_name.insert(valve.name.as_str(), i);
     }
-    (
-        valves
-            .iter()
-            .map(|v| Node {
+    let nodes = valves
+        .iter()
+        .map(|v| {
+            Ok(Node {
                 flow_rate: v.flow_rate,
                 tunnels_to: v
                     .tunnels_to
                     .iter()
-                    .map(|v| valve_index_by_name[v.as_str()])
-                    .collect(),
+                    .map(|(name, line, column)| {
+                        valve_index_by_name
+                            .get(name.as_str())
+                            .copied()
+                            .ok_or_else(|| {
+                                ParseError::new(*line, *column, name, "defined valve name")
+                            })
+                    })
+                    .collect::<Result<_, _>>()?,
             })
-            .collect(),
-        valve_index_by_name["AA"],
-    )
+        })
+        .collect::<Result<_, _>>()?;
+    let start = valve_index_by_name
+        .get("AA")
+        .copied()
+        .ok_or_else(|| ParseError::new(lines_count + 1, 1, "", "definition of valve AA"))?;
+    Ok((nodes, start))
 }
 
 #[derive(Debug)]
-struct Node {
+pub struct Node {
     flow_rate: u16,
     tunnels_to: Vec<usize>,
 }
@@ -337,7 +455,7 @@ struct Node {
 struct Valve {
     name: String,
     flow_rate: u16,
-    tunnels_to: Vec<String>,
+    tunnels_to: Vec<(String, usize, usize)>,
 }
 
 #[test]
@@ -354,5 +472,31 @@ Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (1651, 1707));
+    assert_eq!(compute_result(buffer), Ok((1651, 1707)));
+}
+
+#[test]
+fn single_valve_test() {
+    let buffer = r#"Valve AA has flow rate=0; tunnel leads to valve BB
+Valve BB has flow rate=10; tunnel leads to valve AA
+"#
+    .as_bytes();
+    assert_eq!(compute_result(buffer), Ok((280, 240)));
+}
+
+#[test]
+fn budget_exhausted_test() {
+    let buffer = r#"Valve AA has flow rate=0; tunnels lead to valves DD, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA
+"#
+    .as_bytes();
+    let context = make_context(buffer).unwrap();
+    let error = find_max_released_pressure(&context, 30, Budget::iterations(2)).unwrap_err();
+    assert_eq!(error.best(), Some(&(28 * 20)));
+    assert_eq!(
+        error.to_string().split(" in ").next(),
+        Some("iterations budget exhausted after 2 iterations with 5 states")
+    );
 }
@@ -1,9 +1,60 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
 use std::ops::Range;
 
-fn main() {
-    println!("{:?}", find_max_tower_height(std::io::stdin().lock()));
+use serde_json::json;
+
+use crate::grid::Grid;
+use crate::parse::{position, read_to_end, ParseError};
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+use crate::trace::{Trace, Tracer};
+use crate::viewer::{Bounds, Scene};
+
+pub struct Day17 {
+    pub rocks: usize,
+    pub part_two_rocks: usize,
+    pub trace: Trace,
+}
+
+impl Default for Day17 {
+    fn default() -> Self {
+        Self {
+            rocks: COUNT1,
+            part_two_rocks: COUNT2,
+            trace: Trace::default(),
+        }
+    }
+}
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    const PARAMETERS: &'static [&'static str] = &["rocks", "part-two-rocks", "trace"];
+
+    type Puzzle = Vec<i64>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_jet_directions(input)
+    }
+
+    fn part_one(&self, jet_directions: &Self::Puzzle) -> Self::PartOne {
+        simulate_falling_rocks(jet_directions, self.rocks, self.trace.part(1))
+    }
+
+    fn part_two(&self, jet_directions: &Self::Puzzle) -> Self::PartTwo {
+        find_tower_height_by_loop_pattern(jet_directions, self.part_two_rocks, self.trace.part(2))
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "rocks" => self.rocks = parse_parameter(name, value)?,
+            "part-two-rocks" => self.part_two_rocks = parse_parameter(name, value)?,
+            "trace" => self.trace = Trace::create(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
 }
 
 const LEVEL: [u8; 7] = [b'.'; 7];
@@ -18,75 +69,253 @@ const ROCK_SHAPES: [&[&[u8]]; 5] = [
 ];
 const COUNT1: usize = 2022;
 const COUNT2: usize = 1000000000000;
+const SURFACE_DEPTH: usize = 64;
+
+type StateKey = (usize, usize, Vec<(usize, usize)>);
 
-fn find_max_tower_height(input: impl BufRead) -> (usize, usize) {
-    let jet_directions = parse_jet_directions(input);
-    (simulate_falling_rocks(&jet_directions, COUNT1).1, {
-        let (levels, _, count_per_height) = simulate_falling_rocks(&jet_directions, COUNT1 * 3);
-        let pattern = find_loop_pattern(&levels);
-        let count_per_pattern = count_per_height[&pattern.end] - count_per_height[&pattern.start];
-        let base_height = (COUNT2 / count_per_pattern) * (pattern.end - pattern.start);
-        let repeats = COUNT2 / count_per_pattern;
-        let base_count = count_per_pattern * repeats;
-        let left_count = COUNT2 - base_count;
-        base_height
-            + count_per_height
-                .iter()
-                .find(|(_, n)| **n == left_count)
-                .map(|(h, _)| h)
-                .unwrap()
-    })
-}
-
-fn simulate_falling_rocks(
+pub fn find_max_tower_height(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let solution = Day17::default();
+    let jet_directions = solution.parse(input)?;
+    Ok((
+        solution.part_one(&jet_directions),
+        solution.part_two(&jet_directions),
+    ))
+}
+
+pub fn find_tower_height_by_loop_pattern(
     jet_directions: &[i64],
     count: usize,
-) -> (Levels, usize, HashMap<usize, usize>) {
-    let mut levels: Levels = std::iter::repeat(LEVEL).take(3).collect();
-    let mut rock_type_generator = Generator::new(5);
-    let mut jet_direction_generator = Generator::new(jet_directions.len());
-    let mut occupied_lines = 0;
-    let mut count_per_occupied_lines = HashMap::new();
+    tracer: Tracer,
+) -> usize {
+    let (heights, pattern) = find_loop_pattern(jet_directions, count);
+    let Some(pattern) = pattern else {
+        return heights[count];
+    };
+    tracer.event(pattern.end, || {
+        json!({
+            "event": "loop",
+            "start": heights[pattern.start],
+            "end": heights[pattern.end],
+            "rocks": pattern.len(),
+        })
+    });
+    let repeats = (count - pattern.start) / pattern.len();
+    let left_count = (count - pattern.start) % pattern.len();
+    heights[pattern.start + left_count] + repeats * (heights[pattern.end] - heights[pattern.start])
+}
+
+pub fn find_loop_pattern(
+    jet_directions: &[i64],
+    count: usize,
+) -> (Vec<usize>, Option<Range<usize>>) {
+    let mut chamber = Chamber::new(jet_directions.to_vec());
+    let mut seen = HashMap::new();
+    let mut heights = vec![0];
+    for i in 1..=count {
+        chamber.drop_rock();
+        heights.push(chamber.occupied_lines);
+        let Some(key) = chamber.make_state_key() else {
+            continue;
+        };
+        if let Some(start) = seen.insert(key, i) {
+            return (heights, Some(start..i));
+        }
+    }
+    (heights, None)
+}
+
+pub fn simulate_falling_rocks(jet_directions: &[i64], count: usize, tracer: Tracer) -> usize {
+    let mut chamber = Chamber::new(jet_directions.to_vec());
     for i in 0..count {
-        let rock_type = rock_type_generator.next();
-        while occupied_lines + 3 + ROCK_TYPE_HEIGHTS[rock_type] > levels.len() {
-            levels.push(LEVEL);
-        }
-        let mut rock_x = 2;
-        let mut rock_y = (occupied_lines + 3 + ROCK_TYPE_HEIGHTS[rock_type] - 1) as i64;
-        let max_x = LEVEL.len() as i64 - ROCK_TYPE_WIDTHS[rock_type];
-        let max_y = ROCK_TYPE_HEIGHTS[rock_type] as i64 - 1;
+        let rock = chamber.drop_rock();
+        tracer.event(i, || {
+            json!({
+                "event": "rest",
+                "rock": rock.rock_type,
+                "x": rock.x,
+                "y": rock.y,
+                "height": chamber.occupied_lines,
+            })
+        });
+    }
+    chamber.occupied_lines
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Rock {
+    rock_type: usize,
+    x: i64,
+    y: i64,
+}
+
+pub struct Chamber {
+    jet_directions: Vec<i64>,
+    levels: Levels,
+    rock_type_generator: Generator,
+    jet_direction_generator: Generator,
+    occupied_lines: usize,
+    rock: Option<Rock>,
+}
+
+impl Chamber {
+    pub fn new(jet_directions: Vec<i64>) -> Self {
+        Self {
+            jet_direction_generator: Generator::new(jet_directions.len()),
+            jet_directions,
+            levels: Grid::new(LEVEL.len(), 3, b'.'),
+            rock_type_generator: Generator::new(5),
+            occupied_lines: 0,
+            rock: None,
+        }
+    }
+
+    pub fn drop_rock(&mut self) -> Rock {
         loop {
-            let shift_x = jet_directions[jet_direction_generator.next()];
-            if (0..=max_x).contains(&(rock_x + shift_x))
-                && can_place(rock_x + shift_x, rock_y, rock_type, &levels)
-            {
-                rock_x += shift_x;
+            if let Some(rock) = self.step() {
+                return rock;
             }
-            if rock_y <= max_y || !can_place(rock_x, rock_y - 1, rock_type, &levels) {
-                render_rock(rock_x, rock_y, rock_type, &mut levels);
-                occupied_lines = occupied_lines.max(rock_y as usize + 1);
-                break;
+        }
+    }
+
+    pub fn step(&mut self) -> Option<Rock> {
+        let mut rock = match self.rock {
+            Some(v) => v,
+            None => self.spawn_rock(),
+        };
+        let max_x = LEVEL.len() as i64 - ROCK_TYPE_WIDTHS[rock.rock_type];
+        let max_y = ROCK_TYPE_HEIGHTS[rock.rock_type] as i64 - 1;
+        let shift_x = self.jet_directions[self.jet_direction_generator.next()];
+        if (0..=max_x).contains(&(rock.x + shift_x))
+            && can_place(rock.x + shift_x, rock.y, rock.rock_type, &self.levels)
+        {
+            rock.x += shift_x;
+        }
+        if rock.y <= max_y || !can_place(rock.x, rock.y - 1, rock.rock_type, &self.levels) {
+            render_rock(rock.x, rock.y, rock.rock_type, &mut self.levels);
+            self.occupied_lines = self.occupied_lines.max(rock.y as usize + 1);
+            self.rock = None;
+            return Some(rock);
+        }
+        rock.y -= 1;
+        self.rock = Some(rock);
+        None
+    }
+
+    fn make_state_key(&self) -> Option<StateKey> {
+        let top = self.occupied_lines;
+        let mut surface = HashSet::new();
+        let mut to_visit: Vec<(usize, usize)> = (0..LEVEL.len()).map(|x| (x, top)).collect();
+        while let Some((x, y)) = to_visit.pop() {
+            let next = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1))];
+            for (x, y) in next {
+                if x < LEVEL.len()
+                    && y < top
+                    && self.levels[(x, y)] == b'.'
+                    && surface.insert((x, top - y))
+                {
+                    if top - y > SURFACE_DEPTH {
+                        return None;
+                    }
+                    to_visit.push((x, y));
+                }
             }
-            rock_y -= 1;
         }
-        count_per_occupied_lines.insert(occupied_lines, i + 1);
+        let mut surface: Vec<(usize, usize)> = surface.into_iter().collect();
+        surface.sort();
+        Some((
+            self.rock_type_generator.value,
+            self.jet_direction_generator.value,
+            surface,
+        ))
+    }
+
+    fn spawn_rock(&mut self) -> Rock {
+        let rock_type = self.rock_type_generator.next();
+        while self.occupied_lines + 3 + ROCK_TYPE_HEIGHTS[rock_type] > self.levels.height() {
+            self.levels.push_row(LEVEL);
+        }
+        Rock {
+            rock_type,
+            x: 2,
+            y: (self.occupied_lines + 3 + ROCK_TYPE_HEIGHTS[rock_type] - 1) as i64,
+        }
     }
-    (levels, occupied_lines, count_per_occupied_lines)
 }
 
-fn find_loop_pattern(levels: &[[u8; 7]]) -> Range<usize> {
-    for size in (2..=levels.len() / 3).rev() {
-        for start in 0..size {
-            if levels[start..start + size] == levels[start + size..start + 2 * size] {
-                return start..start + size;
-            }
+pub struct RockScene {
+    chamber: Chamber,
+    rocks: usize,
+    count: usize,
+}
+
+impl RockScene {
+    pub fn new(jet_directions: Vec<i64>, rocks: usize) -> Self {
+        Self {
+            chamber: Chamber::new(jet_directions),
+            rocks,
+            count: 0,
+        }
+    }
+
+    fn is_falling_rock_at(&self, x: i64, y: i64) -> bool {
+        let Some(rock) = self.chamber.rock else {
+            return false;
+        };
+        let (dx, dy) = (x - rock.x, rock.y - y);
+        let shape = ROCK_SHAPES[rock.rock_type];
+        (0..shape.len() as i64).contains(&dy)
+            && (0..shape[dy as usize].len() as i64).contains(&dx)
+            && shape[dy as usize][dx as usize] != b'.'
+    }
+}
+
+impl Scene for RockScene {
+    fn bounds(&self) -> Bounds {
+        Bounds {
+            x: -1..LEVEL.len() as i64 + 1,
+            y: 1 - self.chamber.levels.height() as i64..2,
+        }
+    }
+
+    fn focus(&self) -> (i64, i64) {
+        match self.chamber.rock {
+            Some(rock) => (rock.x, -rock.y),
+            None => (0, -(self.chamber.occupied_lines as i64)),
         }
     }
-    unreachable!()
+
+    fn tile(&self, x: i64, y: i64) -> char {
+        let level = -y;
+        let is_wall = x == -1 || x == LEVEL.len() as i64;
+        match (is_wall, level == -1) {
+            (true, true) => '+',
+            (true, false) => '|',
+            (false, true) => '-',
+            _ if level < 0 || level >= self.chamber.levels.height() as i64 => '.',
+            _ if self.is_falling_rock_at(x, level) => '@',
+            _ => self.chamber.levels[(x as usize, level as usize)] as char,
+        }
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "rocks: {}, tower height: {}",
+            self.count, self.chamber.occupied_lines
+        )
+    }
+
+    fn step(&mut self) -> bool {
+        if self.count >= self.rocks {
+            return false;
+        }
+        if self.chamber.step().is_some() {
+            self.count += 1;
+        }
+        self.count < self.rocks
+    }
 }
 
-type Levels = Vec<[u8; 7]>;
+pub type Levels = Grid<u8>;
 
 fn can_place(rock_x: i64, rock_y: i64, rock_type: usize, levels: &Levels) -> bool {
     for (y, line) in ROCK_SHAPES[rock_type].iter().enumerate() {
@@ -94,7 +323,7 @@ fn can_place(rock_x: i64, rock_y: i64, rock_type: usize, levels: &Levels) -> boo
             if *tile == b'.' {
                 continue;
             }
-            if levels[rock_y as usize - y][rock_x as usize + x] != b'.' {
+            if levels[(rock_x as usize + x, rock_y as usize - y)] != b'.' {
                 return false;
             }
         }
@@ -108,7 +337,7 @@ fn render_rock(rock_x: i64, rock_y: i64, rock_type: usize, levels: &mut Levels)
             if *tile == b'.' {
                 continue;
             }
-            levels[rock_y as usize - y][rock_x as usize + x] = *tile;
+            levels[(rock_x as usize + x, rock_y as usize - y)] = *tile;
         }
     }
 }
@@ -133,15 +362,24 @@ impl Generator {
     }
 }
 
-fn parse_jet_directions(mut input: impl BufRead) -> Vec<i64> {
-    let mut buffer = Vec::new();
-    input.read_to_end(&mut buffer).unwrap();
-    buffer
+pub fn parse_jet_directions(input: impl BufRead) -> Result<Vec<i64>, ParseError> {
+    let buffer = read_to_end(input)?;
+    let length = buffer.trim_ascii_end().len();
+    if length == 0 {
+        let (line, _) = position(&buffer, buffer.len());
+        return Err(ParseError::end_of_input(line, "at least one < or >"));
+    }
+    buffer[..length]
         .iter()
-        .map(|v| match v {
-            b'<' => -1,
-            b'>' => 1,
-            _ => unreachable!(),
+        .enumerate()
+        .map(|(i, v)| match v {
+            b'<' => Ok(-1),
+            b'>' => Ok(1),
+            _ => {
+                let (line, column) = position(&buffer, i);
+                let token = String::from_utf8_lossy(&buffer[i..i + 1]);
+                Err(ParseError::new(line, column, &token, "< or >"))
+            }
         })
         .collect()
 }
@@ -149,5 +387,61 @@ fn parse_jet_directions(mut input: impl BufRead) -> Vec<i64> {
 #[test]
 fn example_test() {
     let buffer = r#">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>"#.as_bytes();
-    assert_eq!(find_max_tower_height(buffer), (3068, 1514285714288));
+    assert_eq!(find_max_tower_height(buffer), Ok((3068, 1514285714288)));
+}
+
+#[test]
+fn loop_pattern_test() {
+    for jet_directions in [vec![1], vec![-1, -1, 1], vec![-1, 1, -1, 1, 1]] {
+        for count in [1, 100, 5000] {
+            assert_eq!(
+                find_tower_height_by_loop_pattern(&jet_directions, count, Tracer::disabled()),
+                simulate_falling_rocks(&jet_directions, count, Tracer::disabled())
+            );
+        }
+    }
+}
+
+#[test]
+fn loop_pattern_property_test() {
+    use crate::property::{check, expect_eq, shrink_integer, shrink_vec};
+
+    check(
+        "find_loop_pattern",
+        100,
+        |rng| {
+            let jet_directions: Vec<i64> = (0..rng.range(1, 40))
+                .map(|_| *rng.choose(&[-1, 1]))
+                .collect();
+            (jet_directions, rng.range(0, 3000) as usize)
+        },
+        |(jet_directions, count)| {
+            let mut result: Vec<(Vec<i64>, usize)> = shrink_vec(jet_directions, |_| Vec::new())
+                .into_iter()
+                .filter(|v| !v.is_empty())
+                .map(|v| (v, *count))
+                .collect();
+            result.extend(
+                shrink_integer(*count as i64)
+                    .into_iter()
+                    .map(|v| (jet_directions.clone(), v as usize)),
+            );
+            result
+        },
+        |(jet_directions, count)| {
+            if let (heights, Some(pattern)) = find_loop_pattern(jet_directions, *count) {
+                let growth = heights[pattern.end] - heights[pattern.start];
+                let simulated =
+                    |rocks| simulate_falling_rocks(jet_directions, rocks, Tracer::disabled());
+                expect_eq(
+                    simulated(pattern.end + 2 * pattern.len()) - simulated(pattern.end),
+                    2 * growth,
+                )?;
+            }
+            expect_eq(
+                find_tower_height_by_loop_pattern(jet_directions, *count, Tracer::disabled()),
+                simulate_falling_rocks(jet_directions, *count, Tracer::disabled()),
+            )
+        },
+    );
 }
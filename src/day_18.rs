@@ -2,24 +2,50 @@ use std::cmp::Reverse;
 use std::collections::hash_map::Entry;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
+use crate::parse::{lines, ParseError};
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+
+    type Puzzle = HashSet<Cube>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_cubes(input)
+    }
+
+    fn part_one(&self, cubes: &Self::Puzzle) -> Self::PartOne {
+        count_unconnected_cube_sides(cubes, &mut HashMap::new())
+    }
+
+    fn part_two(&self, cubes: &Self::Puzzle) -> Self::PartTwo {
+        let mut free = HashMap::new();
+        count_unconnected_cube_sides(cubes, &mut free);
+        calculate_exterior_surface_area(cubes, &free)
+    }
 }
 
-type Cube = (i8, i8, i8);
+pub type Cube = (i8, i8, i8);
 
-fn compute_result(input: impl BufRead) -> (usize, usize) {
-    let cubes = parse_cubes(input);
+pub fn compute_result(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let cubes = parse_cubes(input)?;
     let mut free = HashMap::new();
-    (
+    Ok((
         count_unconnected_cube_sides(&cubes, &mut free),
         calculate_exterior_surface_area(&cubes, &free),
-    )
+    ))
 }
 
-fn count_unconnected_cube_sides(cubes: &HashSet<Cube>, free: &mut HashMap<Cube, usize>) -> usize {
+pub fn count_unconnected_cube_sides(
+    cubes: &HashSet<Cube>,
+    free: &mut HashMap<Cube, usize>,
+) -> usize {
     let mut try_add_free = |x, y, z| {
         if !cubes.contains(&(x, y, z)) {
             free.entry((x, y, z)).and_modify(|v| *v += 1).or_insert(1);
@@ -41,7 +67,10 @@ fn count_unconnected_cube_sides(cubes: &HashSet<Cube>, free: &mut HashMap<Cube,
         .sum()
 }
 
-fn calculate_exterior_surface_area(cubes: &HashSet<Cube>, free: &HashMap<Cube, usize>) -> usize {
+pub fn calculate_exterior_surface_area(
+    cubes: &HashSet<Cube>,
+    free: &HashMap<Cube, usize>,
+) -> usize {
     let min_x = cubes.iter().map(|&(x, _, _)| x).min().unwrap() - 1;
     let min_y = cubes.iter().map(|&(_, y, _)| y).min().unwrap() - 1;
     let min_z = cubes.iter().map(|&(_, _, z)| z).min().unwrap() - 1;
@@ -94,20 +123,24 @@ fn calculate_exterior_surface_area(cubes: &HashSet<Cube>, free: &HashMap<Cube, u
     result
 }
 
-fn parse_cubes(input: impl BufRead) -> HashSet<Cube> {
-    input
-        .lines()
-        .map(|v| {
-            let line = v.unwrap();
-            let (x, tail) = line.split_once(',').unwrap();
-            let (y, z) = tail.split_once(',').unwrap();
-            (
-                i8::from_str(x).unwrap(),
-                i8::from_str(y).unwrap(),
-                i8::from_str(z).unwrap(),
-            )
-        })
-        .collect()
+pub fn parse_cubes(input: impl BufRead) -> Result<HashSet<Cube>, ParseError> {
+    let mut cubes = HashSet::new();
+    let mut count = 0;
+    for line in lines(input) {
+        let line = line?;
+        count = line.number;
+        let (x, tail) = line.split_once(&line.text, ",")?;
+        let (y, z) = line.split_once(tail, ",")?;
+        cubes.insert((
+            line.parse(x, "x coordinate")?,
+            line.parse(y, "y coordinate")?,
+            line.parse(z, "z coordinate")?,
+        ));
+    }
+    if cubes.is_empty() {
+        return Err(ParseError::end_of_input(count + 1, "at least one cube"));
+    }
+    Ok(cubes)
 }
 
 #[test]
@@ -116,7 +149,7 @@ fn example_test_0() {
 2,1,1
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (10, 10));
+    assert_eq!(compute_result(buffer), Ok((10, 10)));
 }
 
 #[test]
@@ -136,5 +169,5 @@ fn example_test() {
 2,3,5
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (64, 58));
+    assert_eq!(compute_result(buffer), Ok((64, 58)));
 }
@@ -1,76 +1,223 @@
-use std::cmp::Reverse;
-use std::collections::hash_map::Entry;
-use std::collections::{BinaryHeap, HashMap};
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock(), 3));
+use crate::parse::{lines, ParseError};
+use crate::search::{search, Budget, Limits, Problem, SearchError};
+use crate::solution::{parse_parameter, Solution};
+
+pub struct Day19 {
+    pub blueprints_left: usize,
+    pub minutes: u8,
+    pub part_two_minutes: u8,
+    pub limits: Limits,
+}
+
+impl Default for Day19 {
+    fn default() -> Self {
+        Self {
+            blueprints_left: 3,
+            minutes: 24,
+            part_two_minutes: 32,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    const PARAMETERS: &'static [&'static str] = &[
+        "blueprints",
+        "minutes",
+        "part-two-minutes",
+        "max-iterations",
+        "max-states",
+        "timeout",
+        "max-memory",
+    ];
+
+    type Puzzle = Vec<Blueprint>;
+    type PartOne = Result<u64, SearchError<u64>>;
+    type PartTwo = Result<u64, SearchError<u64>>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_blueprints(input)
+    }
+
+    fn part_one(&self, blueprints: &Self::Puzzle) -> Self::PartOne {
+        sum_quality_levels(blueprints, self.minutes, self.budget())
+    }
+
+    fn part_two(&self, blueprints: &Self::Puzzle) -> Self::PartTwo {
+        multiply_max_geodes(
+            blueprints,
+            self.blueprints_left,
+            self.part_two_minutes,
+            self.budget(),
+        )
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "blueprints" => self.blueprints_left = parse_parameter(name, value)?,
+            "minutes" => self.minutes = parse_parameter(name, value)?,
+            "part-two-minutes" => self.part_two_minutes = parse_parameter(name, value)?,
+            _ => self.limits.set_parameter(name, value)?,
+        }
+        Ok(())
+    }
+}
+
+impl Day19 {
+    fn budget(&self) -> Budget {
+        self.limits.start(Budget::iterations(MAX_ITERATIONS))
+    }
 }
 
 const MAX_ITERATIONS: usize = 20_000_000;
 
-fn compute_result(input: impl BufRead, blueprints_left: usize) -> (u64, u64) {
-    let blueprints = parse_blueprints(input);
-    (
-        blueprints
-            .iter()
-            .map(|blueprint| blueprint.id as u64 * find_max_geodes(blueprint, 24) as u64)
-            .sum(),
-        blueprints
-            .iter()
-            .take(blueprints_left)
-            .map(|blueprint| find_max_geodes(blueprint, 32) as u64)
-            .product(),
+pub fn compute_result(
+    input: impl BufRead,
+    blueprints_left: usize,
+) -> Result<(u64, u64), ParseError> {
+    let solution = Day19 {
+        blueprints_left,
+        ..Default::default()
+    };
+    let blueprints = solution.parse(input)?;
+    Ok((
+        solution.part_one(&blueprints).expect("max geodes"),
+        solution.part_two(&blueprints).expect("max geodes"),
+    ))
+}
+
+pub fn sum_quality_levels(
+    blueprints: &[Blueprint],
+    minutes: u8,
+    budget: Budget,
+) -> Result<u64, SearchError<u64>> {
+    combine_max_geodes(
+        blueprints.iter().map(|blueprint| {
+            find_max_geodes(blueprint, minutes, budget).map_or_else(
+                |e| Err(e.map_best(|v| blueprint.id as u64 * v as u64)),
+                |v| Ok(blueprint.id as u64 * v as u64),
+            )
+        }),
+        0,
+        |a, b| a + b,
+    )
+}
+
+pub fn multiply_max_geodes(
+    blueprints: &[Blueprint],
+    blueprints_left: usize,
+    minutes: u8,
+    budget: Budget,
+) -> Result<u64, SearchError<u64>> {
+    combine_max_geodes(
+        blueprints.iter().take(blueprints_left).map(|blueprint| {
+            find_max_geodes(blueprint, minutes, budget)
+                .map(|v| v as u64)
+                .map_err(|e| e.map_best(|v| v as u64))
+        }),
+        1,
+        |a, b| a * b,
     )
 }
 
-fn find_max_geodes(blueprint: &Blueprint, max_minute: u8) -> u8 {
+fn combine_max_geodes(
+    results: impl Iterator<Item = Result<u64, SearchError<u64>>>,
+    initial: u64,
+    combine: impl Fn(u64, u64) -> u64,
+) -> Result<u64, SearchError<u64>> {
+    let mut value = initial;
+    let mut exhausted = None;
+    for result in results {
+        match result {
+            Ok(v) => value = combine(value, v),
+            Err(SearchError::BudgetExhausted { limit, best, stats }) => {
+                value = combine(value, best.unwrap_or(0));
+                exhausted = Some(match exhausted {
+                    Some((limit, total)) => (limit, total + stats),
+                    None => (limit, stats),
+                });
+            }
+            Err(SearchError::NotFound) => return Err(SearchError::NotFound),
+        }
+    }
+    match exhausted {
+        Some((limit, stats)) => Err(SearchError::BudgetExhausted {
+            limit,
+            best: Some(value),
+            stats,
+        }),
+        None => Ok(value),
+    }
+}
+
+pub fn find_max_geodes(
+    blueprint: &Blueprint,
+    max_minute: u8,
+    budget: Budget,
+) -> Result<u8, SearchError<u8>> {
     let initial_state = State {
         ore_robots: 1,
         ..Default::default()
     };
-    let mut visited = HashMap::<StateKey, usize>::new();
-    visited.insert(make_state_key(&initial_state), 0);
-    let mut states = vec![initial_state];
-    let mut incoming = BinaryHeap::new();
-    incoming.push((Reverse(0), 0));
-    let mut actions = Vec::new();
-    let mut iterations = 0;
-    while let Some((Reverse(_), state_index)) = incoming.pop() {
-        if states[state_index].minute == max_minute {
-            return states[state_index].geodes;
-        }
-        iterations += 1;
-        assert!(iterations < MAX_ITERATIONS, "iterations={}", iterations);
-        actions.clear();
-        generate_actions(blueprint, &states[state_index], &mut actions);
-        for action in actions.iter() {
-            let mut new_state = states[state_index].clone();
-            apply_action(blueprint, action, &mut new_state);
-            let cost = get_cost(&new_state);
-            match visited.entry(make_state_key(&new_state)) {
-                Entry::Occupied(v) => {
-                    if get_cost(&states[*v.get()]) > cost {
-                        incoming.push((
-                            Reverse(cost + get_heuristic(max_minute, &new_state)),
-                            *v.get(),
-                        ));
-                        states[*v.get()] = new_state;
-                    }
-                }
-                Entry::Vacant(v) => {
-                    v.insert(states.len());
-                    incoming.push((
-                        Reverse(cost + get_heuristic(max_minute, &new_state)),
-                        states.len(),
-                    ));
-                    states.push(new_state);
-                }
-            }
+    search(
+        &mut Mining {
+            blueprint,
+            max_minute,
+            actions: Vec::new(),
+        },
+        initial_state,
+        budget,
+    )
+    .map(|v| v.state.geodes)
+    .map_err(|e| e.map_best(|v| v.geodes))
+}
+
+struct Mining<'a> {
+    blueprint: &'a Blueprint,
+    max_minute: u8,
+    actions: Vec<Action>,
+}
+
+impl Problem for Mining<'_> {
+    type State = State;
+    type Key = StateKey;
+    type Cost = i64;
+
+    fn key(&self, state: &State) -> StateKey {
+        make_state_key(state)
+    }
+
+    fn cost(&self, state: &State) -> i64 {
+        get_cost(state)
+    }
+
+    fn heuristic(&self, state: &State) -> i64 {
+        get_heuristic(self.max_minute, state)
+    }
+
+    fn is_goal(&self, state: &State) -> bool {
+        state.minute == self.max_minute
+    }
+
+    fn fallback(&self, state: &State) -> Option<State> {
+        let mut result = state.clone();
+        result.geodes += state.geode_robots * (self.max_minute - state.minute);
+        result.minute = self.max_minute;
+        Some(result)
+    }
+
+    fn successors(&mut self, state: &State, successors: &mut Vec<State>) {
+        self.actions.clear();
+        generate_actions(self.blueprint, state, &mut self.actions);
+        for action in self.actions.iter() {
+            let mut new_state = state.clone();
+            apply_action(self.blueprint, action, &mut new_state);
+            successors.push(new_state);
         }
     }
-    unreachable!();
 }
 
 fn get_cost(state: &State) -> i64 {
@@ -152,47 +299,45 @@ fn generate_actions(blueprint: &Blueprint, state: &State, actions: &mut Vec<Acti
     }
 }
 
-fn parse_blueprints(input: impl BufRead) -> Vec<Blueprint> {
-    input
-        .lines()
+pub fn parse_blueprints(input: impl BufRead) -> Result<Vec<Blueprint>, ParseError> {
+    lines(input)
         .map(|v| {
-            let line = v.unwrap();
-            let blueprint = line.strip_prefix("Blueprint ").unwrap();
-            let (id, tail1) = blueprint.split_once(": Each ore robot costs ").unwrap();
-            let (ore_robot_ore, tail2) = tail1.split_once(" ore. Each clay robot costs ").unwrap();
-            let (clay_robot_ore, tail3) = tail2
-                .split_once(" ore. Each obsidian robot costs ")
-                .unwrap();
+            let line = v?;
+            let blueprint = line.strip_prefix(&line.text, "Blueprint ")?;
+            let (id, tail1) = line.split_once(blueprint, ": Each ore robot costs ")?;
+            let (ore_robot_ore, tail2) = line.split_once(tail1, " ore. Each clay robot costs ")?;
+            let (clay_robot_ore, tail3) =
+                line.split_once(tail2, " ore. Each obsidian robot costs ")?;
             let (obsidian_robot, tail4) =
-                tail3.split_once(" clay. Each geode robot costs ").unwrap();
-            let (geode_robot, _) = tail4.split_once(" obsidian.").unwrap();
+                line.split_once(tail3, " clay. Each geode robot costs ")?;
+            let (geode_robot, _) = line.split_once(tail4, " obsidian.")?;
             let (obsidian_robot_ore, obsidian_robot_clay) =
-                obsidian_robot.split_once(" ore and ").unwrap();
+                line.split_once(obsidian_robot, " ore and ")?;
             let (geode_robot_ore, geode_robot_obsidian) =
-                geode_robot.split_once(" ore and ").unwrap();
-            Blueprint {
-                id: u8::from_str(id).unwrap(),
+                line.split_once(geode_robot, " ore and ")?;
+            Ok(Blueprint {
+                id: line.parse(id, "blueprint id")?,
                 ore_robot_cost: OreRobotCost {
-                    ore: u8::from_str(ore_robot_ore).unwrap(),
+                    ore: line.parse(ore_robot_ore, "ore amount")?,
                 },
                 clay_robot_cost: ClayRobotCost {
-                    ore: u8::from_str(clay_robot_ore).unwrap(),
+                    ore: line.parse(clay_robot_ore, "ore amount")?,
                 },
                 obsidian_robot_cost: ObsidianRobotCost {
-                    ore: u8::from_str(obsidian_robot_ore).unwrap(),
-                    clay: u8::from_str(obsidian_robot_clay).unwrap(),
+                    ore: line.parse(obsidian_robot_ore, "ore amount")?,
+                    clay: line.parse(obsidian_robot_clay, "clay amount")?,
                 },
                 geode_robot_cost: GeodeRobotCost {
-                    ore: u8::from_str(geode_robot_ore).unwrap(),
-                    obsidian: u8::from_str(geode_robot_obsidian).unwrap(),
+                    ore: line.parse(geode_robot_ore, "ore amount")?,
+                    obsidian: line.parse(geode_robot_obsidian, "obsidian amount")?,
                 },
-            }
+            })
         })
         .collect()
 }
 
 #[derive(Default, Debug)]
-struct Blueprint {
+pub struct Blueprint {
     id: u8,
     ore_robot_cost: OreRobotCost,
     clay_robot_cost: ClayRobotCost,
@@ -201,23 +346,23 @@ struct Blueprint {
 }
 
 #[derive(Default, Debug)]
-struct OreRobotCost {
+pub struct OreRobotCost {
     ore: u8,
 }
 
 #[derive(Default, Debug)]
-struct ClayRobotCost {
+pub struct ClayRobotCost {
     ore: u8,
 }
 
 #[derive(Default, Debug)]
-struct ObsidianRobotCost {
+pub struct ObsidianRobotCost {
     ore: u8,
     clay: u8,
 }
 
 #[derive(Default, Debug)]
-struct GeodeRobotCost {
+pub struct GeodeRobotCost {
     ore: u8,
     obsidian: u8,
 }
@@ -273,5 +418,5 @@ fn example_test() {
 Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer, 1), (33, 56));
+    assert_eq!(compute_result(buffer, 1), Ok((33, 56)));
 }
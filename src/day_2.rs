@@ -1,120 +1,400 @@
-use std::io::BufRead;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
-fn main() {
-    println!("{:?}", get_total_score_for_guide(std::io::stdin().lock()));
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{unknown_parameter, Solution};
+
+#[derive(Default)]
+pub struct Day2 {
+    pub game: Game,
+}
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    const PARAMETERS: &'static [&'static str] = &["rules"];
+
+    type Puzzle = Guide;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_guide(input, &self.game)
+    }
+
+    fn part_one(&self, guide: &Self::Puzzle) -> Self::PartOne {
+        get_total_score_for_play_guide(&self.game, guide)
+    }
+
+    fn part_two(&self, guide: &Self::Puzzle) -> Self::PartTwo {
+        get_total_score_for_outcome_guide(&self.game, guide)
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "rules" => self.game = load_game(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
 }
 
-fn get_total_score_for_guide(buffer: impl BufRead) -> (u64, u64) {
-    let lines: Vec<String> = buffer.lines().map(|v| v.unwrap()).collect();
-    (
-        get_total_score_for_play_guide(&lines),
-        get_total_score_for_outcome_guide(&lines),
-    )
+pub fn get_total_score_for_guide(buffer: impl BufRead) -> Result<(u64, u64), ParseError> {
+    let game = Game::default();
+    let guide = parse_guide(buffer, &game)?;
+    Ok((
+        get_total_score_for_play_guide(&game, &guide),
+        get_total_score_for_outcome_guide(&game, &guide),
+    ))
 }
 
-fn get_total_score_for_play_guide(lines: &[String]) -> u64 {
+pub fn get_total_score_for_play_guide(game: &Game, guide: &Guide) -> u64 {
     let mut total_score = 0;
-    for line in lines {
-        let mut values = line.split(' ');
-        let opponent = parse_opponent_play(values.next().unwrap());
-        let my = parse_my_play(values.next().unwrap());
-        total_score += get_round_score(opponent, my) + get_play_score(my);
+    for (opponent, column, count) in guide.rounds() {
+        let my = game.get_my_play_by_column(column);
+        total_score += game.get_score(opponent, my) * count;
     }
     total_score
 }
 
-fn get_total_score_for_outcome_guide(lines: &[String]) -> u64 {
+pub fn get_total_score_for_outcome_guide(game: &Game, guide: &Guide) -> u64 {
     let mut total_score = 0;
-    for line in lines {
-        let mut values = line.split(' ');
-        let opponent = parse_opponent_play(values.next().unwrap());
-        let my = get_my_play(parse_outcome(values.next().unwrap()), opponent);
-        total_score += get_round_score(opponent, my) + get_play_score(my);
+    for (opponent, column, count) in guide.rounds() {
+        let my = game.get_my_play_by_outcome_column(opponent, column);
+        total_score += game.get_score(opponent, my) * count;
     }
     total_score
 }
 
-fn get_my_play(outcome: Outcome, opponent: Play) -> Play {
-    match outcome {
-        Outcome::Lose => match opponent {
-            Play::Rock => Play::Scissors,
-            Play::Paper => Play::Rock,
-            Play::Scissors => Play::Paper,
-        },
-        Outcome::Draw => opponent,
-        Outcome::Win => match opponent {
-            Play::Rock => Play::Paper,
-            Play::Paper => Play::Scissors,
-            Play::Scissors => Play::Rock,
-        },
+pub fn get_max_score(game: &Game, guide: &Guide) -> u64 {
+    let mut total_score = 0;
+    for (opponent, _, count) in guide.rounds() {
+        let best = (0..game.size())
+            .map(|my| game.get_score(opponent, my))
+            .max()
+            .unwrap_or(0);
+        total_score += best * count;
     }
+    total_score
 }
 
-fn parse_outcome(value: &str) -> Outcome {
-    match value {
-        "X" => Outcome::Lose,
-        "Y" => Outcome::Draw,
-        "Z" => Outcome::Win,
-        _ => unreachable!(),
+pub fn get_total_score_for_decoding(game: &Game, guide: &Guide, decoding: &[usize]) -> u64 {
+    let mut total_score = 0;
+    for (opponent, column, count) in guide.rounds() {
+        total_score += game.get_score(opponent, decoding[column]) * count;
     }
+    total_score
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum Outcome {
-    Lose,
-    Draw,
-    Win,
+pub fn find_best_decoding(game: &Game, guide: &Guide) -> (Vec<usize>, u64) {
+    find_decoding(game, guide, |score, best| score > best)
+}
+
+pub fn find_worst_decoding(game: &Game, guide: &Guide) -> (Vec<usize>, u64) {
+    find_decoding(game, guide, |score, best| score < best)
+}
+
+fn find_decoding(
+    game: &Game,
+    guide: &Guide,
+    is_better: impl Fn(u64, u64) -> bool,
+) -> (Vec<usize>, u64) {
+    let mut decoding: Vec<usize> = (0..game.size()).collect();
+    let mut best = (
+        decoding.clone(),
+        get_total_score_for_decoding(game, guide, &decoding),
+    );
+    while next_permutation(&mut decoding) {
+        let score = get_total_score_for_decoding(game, guide, &decoding);
+        if is_better(score, best.1) {
+            best = (decoding.clone(), score);
+        }
+    }
+    best
 }
 
-fn get_play_score(play: Play) -> u64 {
-    match play {
-        Play::Rock => 1,
-        Play::Paper => 2,
-        Play::Scissors => 3,
+fn next_permutation(values: &mut [usize]) -> bool {
+    let Some(i) = (1..values.len()).rev().find(|i| values[i - 1] < values[*i]) else {
+        return false;
+    };
+    let j = (i..values.len())
+        .rev()
+        .find(|j| values[i - 1] < values[*j])
+        .unwrap();
+    values.swap(i - 1, j);
+    values[i..].reverse();
+    true
+}
+
+pub fn get_expected_score(
+    game: &Game,
+    guide: &Guide,
+    decoding: &[usize],
+    distribution: &[f64],
+) -> Result<f64, String> {
+    let distribution = normalize_distribution(game, distribution)?;
+    let mut total_score = 0.0;
+    for (_, column, count) in guide.rounds() {
+        total_score +=
+            get_expected_round_score(game, decoding[column], &distribution) * count as f64;
     }
+    Ok(total_score)
 }
 
-fn get_round_score(opponent: Play, my: Play) -> u64 {
-    if opponent == my {
-        3
-    } else if beats(opponent, my) {
-        0
-    } else {
-        6
+pub fn find_best_response(game: &Game, distribution: &[f64]) -> Result<(usize, f64), String> {
+    let distribution = normalize_distribution(game, distribution)?;
+    let mut best = (0, f64::NEG_INFINITY);
+    for my in 0..game.size() {
+        let score = get_expected_round_score(game, my, &distribution);
+        if score > best.1 {
+            best = (my, score);
+        }
     }
+    Ok(best)
+}
+
+fn get_expected_round_score(game: &Game, my: usize, distribution: &[f64]) -> f64 {
+    distribution
+        .iter()
+        .enumerate()
+        .map(|(opponent, p)| p * game.get_score(opponent, my) as f64)
+        .sum()
 }
 
-fn beats(a: Play, b: Play) -> bool {
-    match a {
-        Play::Rock => matches!(b, Play::Scissors),
-        Play::Paper => matches!(b, Play::Rock),
-        Play::Scissors => matches!(b, Play::Paper),
+fn normalize_distribution(game: &Game, distribution: &[f64]) -> Result<Vec<f64>, String> {
+    if distribution.len() != game.size() {
+        return Err(format!(
+            "expected {} probabilities, found {}",
+            game.size(),
+            distribution.len()
+        ));
+    }
+    if distribution.iter().any(|p| !p.is_finite() || *p < 0.0) {
+        return Err(String::from("probabilities must be non-negative numbers"));
+    }
+    let sum: f64 = distribution.iter().sum();
+    if sum <= 0.0 {
+        return Err(String::from("probabilities must not all be zero"));
     }
+    Ok(distribution.iter().map(|p| p / sum).collect())
 }
 
-fn parse_opponent_play(value: &str) -> Play {
-    match value {
-        "A" => Play::Rock,
-        "B" => Play::Paper,
-        "C" => Play::Scissors,
-        _ => unreachable!(),
+pub fn parse_guide(input: impl BufRead, game: &Game) -> Result<Guide, ParseError> {
+    let mut guide = Guide::new(game.size());
+    for line in lines(input) {
+        let line = line?;
+        let (opponent, column) = line.split_once(&line.text, " ")?;
+        guide.add(
+            parse_opponent_play(&line, opponent, game)?,
+            parse_column(&line, column, game)?,
+        );
     }
+    Ok(guide)
 }
 
-fn parse_my_play(value: &str) -> Play {
-    match value {
-        "X" => Play::Rock,
-        "Y" => Play::Paper,
-        "Z" => Play::Scissors,
-        _ => unreachable!(),
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Guide {
+    size: usize,
+    counts: Vec<u64>,
+}
+
+impl Guide {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            counts: vec![0; size * size],
+        }
+    }
+
+    pub fn add(&mut self, opponent: usize, column: usize) {
+        self.counts[opponent * self.size + column] += 1;
+    }
+
+    pub fn count(&self, opponent: usize, column: usize) -> u64 {
+        self.counts[opponent * self.size + column]
     }
+
+    pub fn rounds(&self) -> impl Iterator<Item = (usize, usize, u64)> + '_ {
+        (0..self.size).flat_map(move |opponent| {
+            (0..self.size).map(move |column| (opponent, column, self.count(opponent, column)))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Play {
+    pub name: String,
+    pub opponent_symbol: String,
+    pub column_symbol: String,
+    pub score: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Game {
+    plays: Vec<Play>,
+    outcome_scores: [u64; 3],
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum Play {
-    Rock,
-    Paper,
-    Scissors,
+impl Default for Game {
+    fn default() -> Self {
+        let play = |name: &str, opponent_symbol: &str, column_symbol: &str, score| Play {
+            name: String::from(name),
+            opponent_symbol: String::from(opponent_symbol),
+            column_symbol: String::from(column_symbol),
+            score,
+        };
+        Self {
+            plays: vec![
+                play("rock", "A", "X", 1),
+                play("paper", "B", "Y", 2),
+                play("scissors", "C", "Z", 3),
+            ],
+            outcome_scores: [0, 3, 6],
+        }
+    }
+}
+
+impl Game {
+    pub fn new(plays: Vec<Play>, outcome_scores: [u64; 3]) -> Result<Self, String> {
+        if plays.len() < 3 || plays.len().is_multiple_of(2) {
+            return Err(format!(
+                "expected an odd number of plays, at least 3, found {}",
+                plays.len()
+            ));
+        }
+        for (i, play) in plays.iter().enumerate() {
+            for other in plays[..i].iter() {
+                if other.name == play.name {
+                    return Err(format!("duplicate play name {}", play.name));
+                }
+                if other.opponent_symbol == play.opponent_symbol {
+                    return Err(format!(
+                        "duplicate opponent symbol {}",
+                        play.opponent_symbol
+                    ));
+                }
+                if other.column_symbol == play.column_symbol {
+                    return Err(format!("duplicate column symbol {}", play.column_symbol));
+                }
+            }
+        }
+        Ok(Self {
+            plays,
+            outcome_scores,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.plays.len()
+    }
+
+    pub fn plays(&self) -> &[Play] {
+        &self.plays
+    }
+
+    pub fn beats(&self, a: usize, b: usize) -> bool {
+        let distance = (a + self.size() - b) % self.size();
+        (1..=self.size() / 2).contains(&distance)
+    }
+
+    pub fn get_outcome(&self, opponent: usize, my: usize) -> Outcome {
+        if opponent == my {
+            Outcome::Draw
+        } else if self.beats(opponent, my) {
+            Outcome::Lose
+        } else {
+            Outcome::Win
+        }
+    }
+
+    pub fn get_round_score(&self, opponent: usize, my: usize) -> u64 {
+        self.outcome_scores[self.get_outcome(opponent, my) as usize]
+    }
+
+    pub fn get_play_score(&self, play: usize) -> u64 {
+        self.plays[play].score
+    }
+
+    pub fn get_score(&self, opponent: usize, my: usize) -> u64 {
+        self.get_round_score(opponent, my) + self.get_play_score(my)
+    }
+
+    pub fn get_my_play_by_column(&self, column: usize) -> usize {
+        column
+    }
+
+    pub fn get_my_play_by_outcome_column(&self, opponent: usize, column: usize) -> usize {
+        (opponent + column + self.size() - self.size() / 2) % self.size()
+    }
+
+    fn format_symbols<'a>(&'a self, symbol: impl Fn(&'a Play) -> &'a str) -> String {
+        let symbols: Vec<&str> = self.plays.iter().map(symbol).collect();
+        let (last, rest) = symbols.split_last().unwrap();
+        format!("{} or {}", rest.join(", "), last)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+pub fn parse_opponent_play(line: &Line, value: &str, game: &Game) -> Result<usize, ParseError> {
+    game.plays
+        .iter()
+        .position(|v| v.opponent_symbol == value)
+        .ok_or_else(|| line.error(value, game.format_symbols(|v| &v.opponent_symbol)))
+}
+
+pub fn parse_column(line: &Line, value: &str, game: &Game) -> Result<usize, ParseError> {
+    game.plays
+        .iter()
+        .position(|v| v.column_symbol == value)
+        .ok_or_else(|| line.error(value, game.format_symbols(|v| &v.column_symbol)))
+}
+
+pub fn load_game(path: &str) -> Result<Game, String> {
+    let file = File::open(path).map_err(|e| format!("failed to open rules {}: {}", path, e))?;
+    parse_game(BufReader::new(file)).map_err(|e| format!("invalid rules {}: {}", path, e))
+}
+
+pub fn parse_game(input: impl BufRead) -> Result<Game, String> {
+    let mut plays = Vec::new();
+    let mut outcome_scores = [0, 3, 6];
+    for line in lines(input) {
+        let line = line.map_err(|e| e.to_string())?;
+        let text = line.text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = text.split_once(' ').unwrap_or((text, ""));
+        let values: Vec<&str> = rest.split_whitespace().collect();
+        match (keyword, values.as_slice()) {
+            ("play", [name, opponent_symbol, column_symbol, score]) => plays.push(Play {
+                name: name.to_string(),
+                opponent_symbol: opponent_symbol.to_string(),
+                column_symbol: column_symbol.to_string(),
+                score: line.parse(score, "play score").map_err(|e| e.to_string())?,
+            }),
+            ("outcomes", [lose, draw, win]) => {
+                for (score, value) in outcome_scores.iter_mut().zip([lose, draw, win]) {
+                    *score = line
+                        .parse(value, "outcome score")
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            _ => {
+                return Err(line
+                    .error(
+                        text,
+                        "\"play NAME OPPONENT COLUMN SCORE\" or \"outcomes LOSE DRAW WIN\"",
+                    )
+                    .to_string())
+            }
+        }
+    }
+    Game::new(plays, outcome_scores)
 }
 
 #[test]
@@ -124,5 +404,76 @@ B X
 C Z
 "#
     .as_bytes();
-    assert_eq!(get_total_score_for_guide(buffer), (15, 12));
+    assert_eq!(get_total_score_for_guide(buffer), Ok((15, 12)));
+}
+
+#[test]
+fn invalid_column_test() {
+    let buffer = "A Y\nB W\n".as_bytes();
+    assert_eq!(
+        get_total_score_for_guide(buffer),
+        Err(ParseError::new(2, 3, "W", "X, Y or Z"))
+    );
+}
+
+#[test]
+fn chunked_input_test() {
+    use crate::parse::ChunkedReader;
+
+    let buffer = "A Y\nB X\nC Z\nA Y\n".as_bytes();
+    let game = Game::default();
+    let guide = parse_guide(ChunkedReader::new(buffer, 1), &game).unwrap();
+    assert_eq!(guide.count(0, 1), 2);
+    assert_eq!(get_total_score_for_play_guide(&game, &guide), 23);
+    assert_eq!(get_total_score_for_outcome_guide(&game, &guide), 16);
+}
+
+#[test]
+fn custom_game_test() {
+    let rules = r#"# rock, paper, scissors, lizard, Spock
+play rock A V 1
+play spock B W 5
+play paper C X 2
+play lizard D Y 4
+play scissors E Z 3
+outcomes 0 1 2
+"#
+    .as_bytes();
+    let game = parse_game(rules).unwrap();
+    assert!(game.beats(2, 1));
+    assert!(game.beats(3, 1));
+    assert!(game.beats(0, 4));
+    assert!(!game.beats(4, 0));
+    let guide = parse_guide("A Z\nB V\nE X\nD W\n".as_bytes(), &game).unwrap();
+    assert_eq!(get_total_score_for_play_guide(&game, &guide), 11);
+    assert_eq!(get_total_score_for_outcome_guide(&game, &guide), 13);
+    assert_eq!(
+        parse_guide("F V\n".as_bytes(), &game),
+        Err(ParseError::new(1, 1, "F", "A, B, C, D or E"))
+    );
+    assert_eq!(
+        parse_game("play rock A X 1\nplay paper B Y 2\n".as_bytes()),
+        Err(String::from(
+            "expected an odd number of plays, at least 3, found 2"
+        ))
+    );
+}
+
+#[test]
+fn optimizer_test() {
+    let game = Game::default();
+    let guide = parse_guide("A Y\nB X\nC Z\n".as_bytes(), &game).unwrap();
+    assert_eq!(get_max_score(&game, &guide), 8 + 9 + 7);
+    assert_eq!(get_total_score_for_decoding(&game, &guide, &[0, 1, 2]), 15);
+    assert_eq!(find_best_decoding(&game, &guide), (vec![2, 1, 0], 24));
+    assert_eq!(find_worst_decoding(&game, &guide), (vec![0, 2, 1], 6));
+    assert_eq!(
+        get_expected_score(&game, &guide, &[0, 1, 2], &[1.0, 1.0, 1.0]),
+        Ok(15.0)
+    );
+    assert_eq!(find_best_response(&game, &[0.0, 0.0, 2.0]), Ok((0, 7.0)));
+    assert_eq!(
+        find_best_response(&game, &[1.0, 1.0]),
+        Err(String::from("expected 3 probabilities, found 2"))
+    );
 }
@@ -1,20 +1,55 @@
 use std::cmp::Ordering;
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
+use crate::parse::{lines, ParseError};
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+
+pub struct Day20 {
+    pub key: i64,
+}
+
+impl Default for Day20 {
+    fn default() -> Self {
+        Self { key: 811589153 }
+    }
+}
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    const PARAMETERS: &'static [&'static str] = &["key"];
+
+    type Puzzle = Vec<i64>;
+    type PartOne = i64;
+    type PartTwo = i64;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_numbers(input)
+    }
+
+    fn part_one(&self, numbers: &Self::Puzzle) -> Self::PartOne {
+        get_groove_coordinates(numbers, 1, 1)
+    }
+
+    fn part_two(&self, numbers: &Self::Puzzle) -> Self::PartTwo {
+        get_groove_coordinates(numbers, 10, self.key)
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "key" => self.key = parse_parameter(name, value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
 }
 
-fn compute_result(input: impl BufRead) -> (i64, i64) {
-    let numbers = parse_numbers(input);
-    (
-        get_groove_coordinates(&numbers, 1, 1),
-        get_groove_coordinates(&numbers, 10, 811589153),
-    )
+pub fn compute_result(input: impl BufRead) -> Result<(i64, i64), ParseError> {
+    let solution = Day20::default();
+    let numbers = solution.parse(input)?;
+    Ok((solution.part_one(&numbers), solution.part_two(&numbers)))
 }
 
-fn get_groove_coordinates(numbers: &[i64], rounds: usize, key: i64) -> i64 {
+pub fn get_groove_coordinates(numbers: &[i64], rounds: usize, key: i64) -> i64 {
     let zero_index = numbers
         .iter()
         .enumerate()
@@ -88,11 +123,17 @@ impl OrderedNumbers {
     }
 }
 
-fn parse_numbers(input: impl BufRead) -> Vec<i64> {
-    input
-        .lines()
-        .map(|v| i64::from_str(&v.unwrap()).unwrap())
-        .collect()
+pub fn parse_numbers(input: impl BufRead) -> Result<Vec<i64>, ParseError> {
+    let numbers: Vec<i64> = lines(input)
+        .map(|v| {
+            let line = v?;
+            line.parse(&line.text, "integer")
+        })
+        .collect::<Result<_, _>>()?;
+    if !numbers.contains(&0) {
+        return Err(ParseError::end_of_input(numbers.len() + 1, "number 0"));
+    }
+    Ok(numbers)
 }
 
 #[test]
@@ -106,5 +147,53 @@ fn example_test() {
 4
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (3, 1623178306));
+    assert_eq!(compute_result(buffer), Ok((3, 1623178306)));
+}
+
+#[cfg(test)]
+fn get_groove_coordinates_naively(numbers: &[i64], rounds: usize, key: i64) -> i64 {
+    let mut mixed: Vec<(usize, i64)> = numbers.iter().map(|v| v * key).enumerate().collect();
+    for _ in 0..rounds {
+        for index in 0..numbers.len() {
+            let position = mixed.iter().position(|v| v.0 == index).unwrap();
+            let number = mixed.remove(position);
+            let new_position = (position as i64 + number.1).rem_euclid(mixed.len() as i64);
+            mixed.insert(new_position as usize, number);
+        }
+    }
+    let zero_index = numbers.iter().position(|v| *v == 0).unwrap();
+    let zero_position = mixed.iter().position(|v| v.0 == zero_index).unwrap();
+    [1000, 2000, 3000]
+        .iter()
+        .map(|v| mixed[(zero_position + v) % mixed.len()].1)
+        .sum()
+}
+
+#[test]
+fn mixing_property_test() {
+    use crate::property::{check, expect_eq, shrink_integer, shrink_vec};
+
+    check(
+        "get_groove_coordinates",
+        200,
+        |rng| {
+            let mut numbers: Vec<i64> = (0..rng.range(1, 20)).map(|_| rng.range(-30, 30)).collect();
+            numbers.insert(rng.below(numbers.len() + 1), 0);
+            numbers
+        },
+        |numbers| shrink_vec(numbers, |v| shrink_integer(*v)),
+        |numbers| {
+            if numbers.len() < 2 || !numbers.contains(&0) {
+                return Ok(());
+            }
+            expect_eq(
+                get_groove_coordinates(numbers, 1, 1),
+                get_groove_coordinates_naively(numbers, 1, 1),
+            )?;
+            expect_eq(
+                get_groove_coordinates(numbers, 10, 811589153),
+                get_groove_coordinates_naively(numbers, 10, 811589153),
+            )
+        },
+    );
 }
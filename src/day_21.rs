@@ -1,13 +1,55 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
-use std::str::{Bytes, FromStr};
+use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+
+    type Puzzle = Equations;
+    type PartOne = Option<i64>;
+    type PartTwo = Option<i64>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_values_and_expressions(input)
+    }
+
+    fn part_one(&self, (values, expressions): &Self::Puzzle) -> Self::PartOne {
+        find_problems(values, expressions)
+            .is_empty()
+            .then(|| calculate_root(values.clone(), expressions))
+    }
+
+    fn part_two(&self, (values, expressions): &Self::Puzzle) -> Self::PartTwo {
+        find_problems(values, expressions)
+            .is_empty()
+            .then(|| find_proper_humn(values.clone(), expressions))
+    }
+
+    fn validate(&self, (values, expressions): &Self::Puzzle) -> Vec<String> {
+        find_problems(values, expressions)
+    }
 }
 
-fn compute_result(input: impl BufRead) -> (i64, i64) {
-    let equations = parse_equations(input);
+pub fn compute_result(input: impl BufRead) -> Result<(Option<i64>, Option<i64>), ParseError> {
+    let (values, expressions) = parse_values_and_expressions(input)?;
+    if !find_problems(&values, &expressions).is_empty() {
+        return Ok((None, None));
+    }
+    Ok((
+        Some(calculate_root(values.clone(), &expressions)),
+        Some(find_proper_humn(values, &expressions)),
+    ))
+}
+
+pub fn parse_values_and_expressions(input: impl BufRead) -> Result<Equations, ParseError> {
+    let equations = parse_equations(input)?;
     let mut values = HashMap::new();
     let mut expressions = HashMap::new();
     for (variable, definition) in equations.iter() {
@@ -20,13 +62,10 @@ fn compute_result(input: impl BufRead) -> (i64, i64) {
             }
         }
     }
-    (
-        calculate_root(values.clone(), &expressions),
-        find_proper_humn(values, &expressions),
-    )
+    Ok((values, expressions))
 }
 
-fn calculate_root(
+pub fn calculate_root(
     mut values: HashMap<Variable, i64>,
     expressions: &HashMap<Variable, Expression>,
 ) -> i64 {
@@ -34,21 +73,19 @@ fn calculate_root(
     values[b"root"]
 }
 
-fn find_proper_humn(
+pub fn find_proper_humn(
     values: HashMap<Variable, i64>,
     expressions: &HashMap<Variable, Expression>,
 ) -> i64 {
-    let low = 0;
-    let (low_first, low_second) = find_root_operands_with_humn(low, values.clone(), expressions);
-    if low_first < low_second {
-        find_proper_humn_impl(low, values, expressions, |a, b| a < b)
-    } else {
-        find_proper_humn_impl(low, values, expressions, |a, b| a > b)
+    let (first, second) = find_root_operands_with_humn(0, values.clone(), expressions);
+    match first.cmp(&second) {
+        Ordering::Less => find_proper_humn_impl(values, expressions, |a, b| a < b),
+        Ordering::Greater => find_proper_humn_impl(values, expressions, |a, b| a > b),
+        Ordering::Equal => 0,
     }
 }
 
 fn find_proper_humn_impl<F>(
-    mut low: i64,
     values: HashMap<Variable, i64>,
     expressions: &HashMap<Variable, Expression>,
     less: F,
@@ -56,35 +93,25 @@ fn find_proper_humn_impl<F>(
 where
     F: Fn(i64, i64) -> bool,
 {
+    let is_less = |humn| {
+        let (first, second) = find_root_operands_with_humn(humn, values.clone(), expressions);
+        less(first, second)
+    };
+    let mut low = 0;
     let mut high = 1;
-    loop {
-        let (high_first, high_second) =
-            find_root_operands_with_humn(high, values.clone(), expressions);
-        if !less(high_first, high_second) {
-            break;
-        }
+    while is_less(high) {
+        low = high;
         high *= 2;
     }
-    let result = loop {
-        let value = (low + high) / 2;
-        let (first, second) = find_root_operands_with_humn(value, values.clone(), expressions);
-        if first == second {
-            break value;
-        } else if less(first, second) {
+    while high - low > 1 {
+        let value = low + (high - low) / 2;
+        if is_less(value) {
             low = value;
         } else {
             high = value;
         }
-    };
-    let mut min_value = result;
-    loop {
-        let (first, second) =
-            find_root_operands_with_humn(min_value - 1, values.clone(), expressions);
-        if first != second {
-            break min_value;
-        }
-        min_value -= 1;
     }
+    high
 }
 
 fn find_root_operands_with_humn(
@@ -100,7 +127,7 @@ fn find_root_operands_with_humn(
     )
 }
 
-fn calculate(
+pub fn calculate(
     src: &Variable,
     expressions: &HashMap<Variable, Expression>,
     values: &mut HashMap<Variable, i64>,
@@ -135,73 +162,130 @@ fn calculate(
     }
 }
 
-fn parse_equations(input: impl BufRead) -> Vec<(Variable, Definition)> {
-    input
-        .lines()
-        .map(|v| {
-            let line = v.unwrap();
-            let (variable, definition) = line.split_once(": ").unwrap();
-            (
-                make_variable(variable.bytes()),
-                if let Ok(value) = i64::from_str(definition) {
-                    Definition::Value(value)
-                } else if let Some((first, second)) = definition.split_once(" + ") {
-                    Definition::Expression(Expression {
-                        first: make_variable(first.bytes()),
-                        second: make_variable(second.bytes()),
-                        operation: Operation::Sum,
-                    })
-                } else if let Some((first, second)) = definition.split_once(" - ") {
-                    Definition::Expression(Expression {
-                        first: make_variable(first.bytes()),
-                        second: make_variable(second.bytes()),
-                        operation: Operation::Sub,
-                    })
-                } else if let Some((first, second)) = definition.split_once(" * ") {
-                    Definition::Expression(Expression {
-                        first: make_variable(first.bytes()),
-                        second: make_variable(second.bytes()),
-                        operation: Operation::Mul,
-                    })
-                } else if let Some((first, second)) = definition.split_once(" / ") {
-                    Definition::Expression(Expression {
-                        first: make_variable(first.bytes()),
-                        second: make_variable(second.bytes()),
-                        operation: Operation::Div,
-                    })
-                } else {
-                    unreachable!();
-                },
+pub fn find_problems(
+    values: &HashMap<Variable, i64>,
+    expressions: &HashMap<Variable, Expression>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    if !expressions.contains_key(b"root") {
+        problems.push(String::from("root is not defined by an expression"));
+    }
+    if !values.contains_key(b"humn") {
+        problems.push(String::from("humn is not defined by a number"));
+    }
+    let mut undefined: Vec<String> = expressions
+        .iter()
+        .flat_map(|(variable, v)| [(variable, v.first), (variable, v.second)])
+        .filter(|(_, v)| !values.contains_key(v) && !expressions.contains_key(v))
+        .map(|(variable, v)| {
+            format!(
+                "{} uses undefined variable {}",
+                format_variable(variable),
+                format_variable(&v)
             )
         })
-        .collect()
+        .collect();
+    undefined.sort();
+    problems.extend(undefined);
+    if let Some(variable) = find_cycle(b"root", expressions) {
+        problems.push(format!("{} depends on itself", format_variable(&variable)));
+    }
+    problems
+}
+
+fn find_cycle(src: &Variable, expressions: &HashMap<Variable, Expression>) -> Option<Variable> {
+    let mut finished = HashSet::new();
+    let mut path = HashSet::new();
+    let mut to_visit = vec![(*src, false)];
+    while let Some((variable, leaving)) = to_visit.pop() {
+        if leaving {
+            path.remove(&variable);
+            finished.insert(variable);
+            continue;
+        }
+        if finished.contains(&variable) {
+            continue;
+        }
+        if !path.insert(variable) {
+            return Some(variable);
+        }
+        to_visit.push((variable, true));
+        if let Some(expression) = expressions.get(&variable) {
+            to_visit.push((expression.first, false));
+            to_visit.push((expression.second, false));
+        }
+    }
+    None
 }
 
-fn make_variable(bytes: Bytes) -> Variable {
+fn format_variable(variable: &Variable) -> String {
+    String::from_utf8_lossy(variable)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+pub fn parse_equations(input: impl BufRead) -> Result<Vec<(Variable, Definition)>, ParseError> {
+    lines(input).map(|v| parse_equation(&v?)).collect()
+}
+
+fn parse_equation(line: &Line) -> Result<(Variable, Definition), ParseError> {
+    let (variable, definition) = line.split_once(&line.text, ": ")?;
+    Ok((
+        make_variable(line, variable)?,
+        if let Ok(value) = i64::from_str(definition) {
+            Definition::Value(value)
+        } else {
+            parse_expression(line, definition)?
+        },
+    ))
+}
+
+fn parse_expression(line: &Line, definition: &str) -> Result<Definition, ParseError> {
+    let (first, tail) = line.split_once(definition, " ")?;
+    let (operation, second) = line.split_once(tail, " ")?;
+    Ok(Definition::Expression(Expression {
+        first: make_variable(line, first)?,
+        second: make_variable(line, second)?,
+        operation: match operation {
+            "+" => Operation::Sum,
+            "-" => Operation::Sub,
+            "*" => Operation::Mul,
+            "/" => Operation::Div,
+            _ => return Err(line.error(operation, "+, -, * or /")),
+        },
+    }))
+}
+
+fn make_variable(line: &Line, value: &str) -> Result<Variable, ParseError> {
+    if value.is_empty() || value.len() > 4 {
+        return Err(line.error(value, "variable name of at most 4 bytes"));
+    }
     let mut result = [0u8; 4];
-    for (i, v) in bytes.enumerate() {
+    for (i, v) in value.bytes().enumerate() {
         result[i] = v;
     }
-    result
+    Ok(result)
 }
 
-type Variable = [u8; 4];
+pub type Variable = [u8; 4];
+
+pub type Equations = (HashMap<Variable, i64>, HashMap<Variable, Expression>);
 
 #[derive(Debug)]
-enum Definition {
+pub enum Definition {
     Value(i64),
     Expression(Expression),
 }
 
 #[derive(Debug, Clone)]
-struct Expression {
+pub struct Expression {
     first: Variable,
     second: Variable,
     operation: Operation,
 }
 
 #[derive(Debug, Clone)]
-enum Operation {
+pub enum Operation {
     Sum,
     Sub,
     Mul,
@@ -227,5 +311,200 @@ drzm: hmdt - zczc
 hmdt: 32
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (152, 301));
+    assert_eq!(compute_result(buffer), Ok((Some(152), Some(301))));
+}
+
+#[test]
+fn invalid_equations_test() {
+    let problems = |buffer: &str| {
+        let (values, expressions) = parse_values_and_expressions(buffer.as_bytes()).unwrap();
+        find_problems(&values, &expressions)
+    };
+    let mut cycle = problems("root: pppw + sjmn\npppw: sjmn * 2\nsjmn: pppw - humn\nhumn: 5\n");
+    let last = cycle.pop().unwrap();
+    assert!(
+        last == "pppw depends on itself" || last == "sjmn depends on itself",
+        "{}",
+        last
+    );
+    assert_eq!(cycle, vec![String::from("pppw uses undefined variable 2")]);
+    assert_eq!(
+        problems("root: aaaa + bbbb\naaaa: cccc * 2\n"),
+        vec![
+            String::from("humn is not defined by a number"),
+            String::from("aaaa uses undefined variable 2"),
+            String::from("aaaa uses undefined variable cccc"),
+            String::from("root uses undefined variable bbbb"),
+        ]
+    );
+    assert_eq!(
+        problems("root: 5\nhumn: 5\n"),
+        vec![String::from("root is not defined by an expression")]
+    );
+    assert_eq!(
+        problems(""),
+        vec![
+            String::from("root is not defined by an expression"),
+            String::from("humn is not defined by a number"),
+        ]
+    );
+    assert_eq!(
+        compute_result("root: aaaa + bbbb\naaaa: 5\nhumn: 1\n".as_bytes()),
+        Ok((None, None))
+    );
+    assert_eq!(
+        parse_values_and_expressions("root: aaaa ? bbbb\n".as_bytes()).err(),
+        Some(ParseError::new(1, 12, "?", "+, -, * or /"))
+    );
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone)]
+enum Tree {
+    Humn,
+    Value(i64),
+    Expression(Box<Tree>, char, Box<Tree>),
+}
+
+#[cfg(test)]
+impl Tree {
+    fn generate(rng: &mut crate::generate::Rng, depth: usize, humn: bool) -> Self {
+        if depth == 0 || rng.chance(1, 3) {
+            return if humn {
+                Tree::Humn
+            } else {
+                Tree::Value(rng.range(1, 9))
+            };
+        }
+        let operation = *rng.choose(&['+', '-', '*', '/']);
+        let humn_first = humn && (operation == '/' || rng.chance(1, 2));
+        Tree::Expression(
+            Box::new(Self::generate(rng, depth - 1, humn_first)),
+            operation,
+            Box::new(Self::generate(rng, depth - 1, humn && !humn_first)),
+        )
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        match self {
+            Tree::Humn => Vec::new(),
+            Tree::Value(value) => crate::property::shrink_integer(*value)
+                .into_iter()
+                .map(Tree::Value)
+                .collect(),
+            Tree::Expression(first, operation, second) => {
+                let mut result = vec![(**first).clone(), (**second).clone()];
+                result.extend(
+                    first
+                        .shrink()
+                        .into_iter()
+                        .map(|v| Tree::Expression(Box::new(v), *operation, second.clone())),
+                );
+                result.extend(
+                    second
+                        .shrink()
+                        .into_iter()
+                        .map(|v| Tree::Expression(first.clone(), *operation, Box::new(v))),
+                );
+                result
+            }
+        }
+    }
+
+    fn count_humn(&self) -> usize {
+        match self {
+            Tree::Humn => 1,
+            Tree::Value(_) => 0,
+            Tree::Expression(first, operation, second) => {
+                let count = second.count_humn();
+                if *operation == '/' && count > 0 {
+                    return usize::MAX;
+                }
+                first.count_humn().saturating_add(count)
+            }
+        }
+    }
+
+    fn evaluate(&self, humn: i64) -> Option<i64> {
+        match self {
+            Tree::Humn => Some(humn),
+            Tree::Value(value) => Some(*value),
+            Tree::Expression(first, operation, second) => {
+                let (first, second) = (first.evaluate(humn)?, second.evaluate(humn)?);
+                match operation {
+                    '+' => first.checked_add(second),
+                    '-' => first.checked_sub(second),
+                    '*' => first.checked_mul(second),
+                    _ => first.checked_div(second),
+                }
+            }
+        }
+    }
+
+    fn write(&self, lines: &mut Vec<String>) -> String {
+        let definition = match self {
+            Tree::Humn => return String::from("humn"),
+            Tree::Value(value) => value.to_string(),
+            Tree::Expression(first, operation, second) => {
+                let first = first.write(lines);
+                let second = second.write(lines);
+                format!("{} {} {}", first, operation, second)
+            }
+        };
+        let name = format!("m{:03}", lines.len());
+        lines.push(format!("{}: {}", name, definition));
+        name
+    }
+}
+
+#[test]
+fn proper_humn_property_test() {
+    use crate::property::{check, expect_eq};
+
+    check(
+        "find_proper_humn",
+        300,
+        |rng| {
+            let humn_first = rng.chance(1, 2);
+            (
+                Tree::generate(rng, 4, humn_first),
+                Tree::generate(rng, 4, !humn_first),
+            )
+        },
+        |(first, second)| {
+            let mut result: Vec<(Tree, Tree)> = first
+                .shrink()
+                .into_iter()
+                .map(|v| (v, second.clone()))
+                .collect();
+            result.extend(second.shrink().into_iter().map(|v| (first.clone(), v)));
+            result
+        },
+        |(first, second)| {
+            if first.count_humn() + second.count_humn() != 1 {
+                return Ok(());
+            }
+            let mut sides = Vec::new();
+            for humn in -1..=2048 {
+                match (first.evaluate(humn), second.evaluate(humn)) {
+                    (Some(a), Some(b)) => sides.push(a == b),
+                    _ => return Ok(()),
+                }
+            }
+            if sides[0] {
+                return Ok(());
+            }
+            let Some(expected) = sides[1..1002].iter().position(|v| *v) else {
+                return Ok(());
+            };
+            let mut lines = Vec::new();
+            let first = first.write(&mut lines);
+            let second = second.write(&mut lines);
+            lines.push(format!("root: {} + {}", first, second));
+            lines.push(String::from("humn: 0"));
+            let (values, expressions) =
+                parse_values_and_expressions(lines.join("\n").as_bytes()).unwrap();
+            expect_eq(find_proper_humn(values, &expressions), expected as i64)
+        },
+    );
 }
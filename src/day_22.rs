@@ -1,35 +1,50 @@
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!(
-        "{:?}",
-        compute_result(std::io::stdin().lock(), &CubeLayout0)
-    );
+use crate::grid::Grid;
+use crate::parse::{lines, ParseError};
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+
+    type Puzzle = (Grid<Tile>, Path, KnownLayout);
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_one(&self, (map, path, _): &Self::Puzzle) -> Self::PartOne {
+        compute_plane_password_with_wrapping(map, path)
+    }
+
+    fn part_two(&self, (map, path, layout): &Self::Puzzle) -> Self::PartTwo {
+        compute_cube_password(map, path, layout)
+    }
 }
 
-fn compute_result<L>(input: impl BufRead, layout: &L) -> (usize, usize)
-where
-    L: CubeLayout,
-{
-    let (rows, path) = parse_input(input);
-    (
-        compute_plane_password_with_wrapping(&rows, &path),
-        compute_cube_password(&rows, &path, layout),
-    )
+pub fn compute_result(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let (map, path, layout) = parse_input(input)?;
+    Ok((
+        compute_plane_password_with_wrapping(&map, &path),
+        compute_cube_password(&map, &path, &layout),
+    ))
+}
+
+fn get_face_size(map: &Grid<Tile>) -> usize {
+    map.width().max(map.height()) / 4
 }
 
-fn compute_cube_password<L>(rows: &[Row], path: &Path, layout: &L) -> usize
+pub fn compute_cube_password<L>(map: &Grid<Tile>, path: &Path, layout: &L) -> usize
 where
     L: CubeLayout,
 {
-    let max_column = rows.iter().map(|v| v.shift + v.values.len()).max().unwrap();
-    let face_size = if max_column > rows.len() {
-        max_column / 4
-    } else {
-        rows.len() / 4
-    };
-    let mut x = rows[0].shift;
+    let face_size = get_face_size(map);
+    let mut x = find_tiles_bounds(map.row(0).iter()).0;
     let mut y = 0;
     let mut direction = Direction::Right;
     for point in path.iter() {
@@ -38,7 +53,7 @@ where
                 for _ in 0..*steps {
                     let (new_x, new_y, new_direction) =
                         move_by_cube_surface(x, y, direction, face_size, layout);
-                    if matches!(rows[new_y].values[new_x - rows[new_y].shift], Tile::Wall) {
+                    if matches!(map[(new_x, new_y)], Tile::Wall) {
                         break;
                     }
                     x = new_x;
@@ -67,7 +82,7 @@ where
     1000 * (y + 1) + 4 * (x + 1) + direction as usize
 }
 
-fn move_by_cube_surface<L>(
+pub fn move_by_cube_surface<L>(
     x: usize,
     y: usize,
     direction: Direction,
@@ -102,23 +117,26 @@ where
 }
 
 #[derive(Copy, Clone, Debug)]
-enum TransformX {
+pub enum TransformX {
     Id,
+    Inv,
     Left,
     Right,
     FromY,
+    FromYInv,
 }
 
 #[derive(Copy, Clone, Debug)]
-enum TransformY {
+pub enum TransformY {
     Id,
     Inv,
     Top,
     Bottom,
     FromX,
+    FromXInv,
 }
 
-trait CubeLayout {
+pub trait CubeLayout {
     fn faces(&self) -> &[(usize, usize); 6];
 
     fn get_next_face(
@@ -128,7 +146,7 @@ trait CubeLayout {
     ) -> (usize, TransformX, TransformY, Direction);
 }
 
-struct CubeLayout0;
+pub struct CubeLayout0;
 
 impl CubeLayout for CubeLayout0 {
     fn faces(&self) -> &[(usize, usize); 6] {
@@ -140,46 +158,159 @@ impl CubeLayout for CubeLayout0 {
         face: usize,
         direction: Direction,
     ) -> (usize, TransformX, TransformY, Direction) {
-        match face {
-            0 => match direction {
-                Direction::Right => (1, TransformX::Left, TransformY::Id, direction),
-                Direction::Down => (2, TransformX::Id, TransformY::Top, direction),
-                Direction::Left => (3, TransformX::Left, TransformY::Inv, Direction::Right),
-                Direction::Up => (5, TransformX::Left, TransformY::FromX, Direction::Right),
-            },
-            1 => match direction {
-                Direction::Right => (4, TransformX::Right, TransformY::Inv, Direction::Left),
-                Direction::Down => (2, TransformX::Right, TransformY::FromX, Direction::Left),
-                Direction::Left => (0, TransformX::Right, TransformY::Id, direction),
-                Direction::Up => (5, TransformX::Id, TransformY::Bottom, Direction::Up),
-            },
-            2 => match direction {
-                Direction::Right => (1, TransformX::FromY, TransformY::Bottom, Direction::Up),
-                Direction::Down => (4, TransformX::Id, TransformY::Top, direction),
-                Direction::Left => (3, TransformX::FromY, TransformY::Top, Direction::Down),
-                Direction::Up => (0, TransformX::Id, TransformY::Bottom, direction),
-            },
-            3 => match direction {
-                Direction::Right => (4, TransformX::Left, TransformY::Id, direction),
-                Direction::Down => (5, TransformX::Id, TransformY::Top, direction),
-                Direction::Left => (0, TransformX::Left, TransformY::Inv, Direction::Right),
-                Direction::Up => (2, TransformX::Left, TransformY::FromX, Direction::Right),
-            },
-            4 => match direction {
-                Direction::Right => (1, TransformX::Right, TransformY::Inv, Direction::Left),
-                Direction::Down => (5, TransformX::Right, TransformY::FromX, Direction::Left),
-                Direction::Left => (3, TransformX::Right, TransformY::Id, direction),
-                Direction::Up => (2, TransformX::Id, TransformY::Bottom, direction),
-            },
-            5 => match direction {
-                Direction::Right => (4, TransformX::FromY, TransformY::Bottom, Direction::Up),
-                Direction::Down => (1, TransformX::Id, TransformY::Top, Direction::Down),
-                Direction::Left => (0, TransformX::FromY, TransformY::Top, Direction::Down),
-                Direction::Up => (3, TransformX::Id, TransformY::Bottom, direction),
-            },
-            _ => unreachable!(),
+        use Direction::{Down, Left, Right, Up};
+        use TransformX as X;
+        use TransformY as Y;
+        const NEXT_FACES: [[(usize, TransformX, TransformY, Direction); 4]; 6] = [
+            [
+                (1, X::Left, Y::Id, Right),
+                (2, X::Id, Y::Top, Down),
+                (3, X::Left, Y::Inv, Right),
+                (5, X::Left, Y::FromX, Right),
+            ],
+            [
+                (4, X::Right, Y::Inv, Left),
+                (2, X::Right, Y::FromX, Left),
+                (0, X::Right, Y::Id, Left),
+                (5, X::Id, Y::Bottom, Up),
+            ],
+            [
+                (1, X::FromY, Y::Bottom, Up),
+                (4, X::Id, Y::Top, Down),
+                (3, X::FromY, Y::Top, Down),
+                (0, X::Id, Y::Bottom, Up),
+            ],
+            [
+                (4, X::Left, Y::Id, Right),
+                (5, X::Id, Y::Top, Down),
+                (0, X::Left, Y::Inv, Right),
+                (2, X::Left, Y::FromX, Right),
+            ],
+            [
+                (1, X::Right, Y::Inv, Left),
+                (5, X::Right, Y::FromX, Left),
+                (3, X::Right, Y::Id, Left),
+                (2, X::Id, Y::Bottom, Up),
+            ],
+            [
+                (4, X::FromY, Y::Bottom, Up),
+                (1, X::Id, Y::Top, Down),
+                (0, X::FromY, Y::Top, Down),
+                (3, X::Id, Y::Bottom, Up),
+            ],
+        ];
+        NEXT_FACES[face][direction as usize]
+    }
+}
+
+pub struct CubeLayoutExample;
+
+impl CubeLayout for CubeLayoutExample {
+    fn faces(&self) -> &[(usize, usize); 6] {
+        &[(2, 0), (0, 1), (1, 1), (2, 1), (2, 2), (3, 2)]
+    }
+
+    fn get_next_face(
+        &self,
+        face: usize,
+        direction: Direction,
+    ) -> (usize, TransformX, TransformY, Direction) {
+        use Direction::{Down, Left, Right, Up};
+        use TransformX as X;
+        use TransformY as Y;
+        const NEXT_FACES: [[(usize, TransformX, TransformY, Direction); 4]; 6] = [
+            [
+                (5, X::Right, Y::Inv, Left),
+                (3, X::Id, Y::Top, Down),
+                (2, X::FromY, Y::Top, Down),
+                (1, X::Inv, Y::Top, Down),
+            ],
+            [
+                (2, X::Left, Y::Id, Right),
+                (4, X::Inv, Y::Bottom, Up),
+                (5, X::FromYInv, Y::Bottom, Up),
+                (0, X::Inv, Y::Top, Down),
+            ],
+            [
+                (3, X::Left, Y::Id, Right),
+                (4, X::Left, Y::FromXInv, Right),
+                (1, X::Right, Y::Id, Left),
+                (0, X::Left, Y::FromX, Right),
+            ],
+            [
+                (5, X::FromYInv, Y::Top, Down),
+                (4, X::Id, Y::Top, Down),
+                (2, X::Right, Y::Id, Left),
+                (0, X::Id, Y::Bottom, Up),
+            ],
+            [
+                (5, X::Left, Y::Id, Right),
+                (1, X::Inv, Y::Bottom, Up),
+                (2, X::FromYInv, Y::Bottom, Up),
+                (3, X::Id, Y::Bottom, Up),
+            ],
+            [
+                (0, X::Right, Y::Inv, Left),
+                (1, X::Left, Y::FromXInv, Right),
+                (4, X::Right, Y::Id, Left),
+                (3, X::Right, Y::FromXInv, Left),
+            ],
+        ];
+        NEXT_FACES[face][direction as usize]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum KnownLayout {
+    Input,
+    Example,
+}
+
+impl KnownLayout {
+    pub const ALL: [KnownLayout; 2] = [KnownLayout::Input, KnownLayout::Example];
+}
+
+impl CubeLayout for KnownLayout {
+    fn faces(&self) -> &[(usize, usize); 6] {
+        match self {
+            KnownLayout::Input => CubeLayout0.faces(),
+            KnownLayout::Example => CubeLayoutExample.faces(),
         }
     }
+
+    fn get_next_face(
+        &self,
+        face: usize,
+        direction: Direction,
+    ) -> (usize, TransformX, TransformY, Direction) {
+        match self {
+            KnownLayout::Input => CubeLayout0.get_next_face(face, direction),
+            KnownLayout::Example => CubeLayoutExample.get_next_face(face, direction),
+        }
+    }
+}
+
+pub fn find_known_layout(map: &Grid<Tile>) -> Option<KnownLayout> {
+    let face_size = get_face_size(map);
+    if face_size == 0
+        || !map.width().is_multiple_of(face_size)
+        || !map.height().is_multiple_of(face_size)
+    {
+        return None;
+    }
+    let faces: Vec<(usize, usize)> = (0..map.height() / face_size)
+        .flat_map(|y| (0..map.width() / face_size).map(move |x| (x, y)))
+        .filter(|(x, y)| !matches!(map[(x * face_size, y * face_size)], Tile::Void))
+        .collect();
+    let layout = KnownLayout::ALL
+        .into_iter()
+        .find(|v| v.faces()[..] == faces[..])?;
+    let is_folded = (0..map.height()).all(|y| {
+        (0..map.width()).all(|x| {
+            matches!(map[(x, y)], Tile::Void) != faces.contains(&(x / face_size, y / face_size))
+        })
+    });
+    is_folded.then_some(layout)
 }
 
 struct Faces<'a> {
@@ -208,9 +339,13 @@ impl Faces<'_> {
         (
             match transform_x {
                 TransformX::Id => self.values[next_face].0 * face_size + x % face_size,
+                TransformX::Inv => (self.values[next_face].0 + 1) * face_size - 1 - x % face_size,
                 TransformX::Left => self.values[next_face].0 * face_size,
                 TransformX::Right => (self.values[next_face].0 + 1) * face_size - 1,
                 TransformX::FromY => self.values[next_face].0 * face_size + y % face_size,
+                TransformX::FromYInv => {
+                    (self.values[next_face].0 + 1) * face_size - 1 - y % face_size
+                }
             },
             match transform_y {
                 TransformY::Id => self.values[next_face].1 * face_size + y % face_size,
@@ -218,32 +353,23 @@ impl Faces<'_> {
                 TransformY::Top => self.values[next_face].1 * face_size,
                 TransformY::Bottom => (self.values[next_face].1 + 1) * face_size - 1,
                 TransformY::FromX => self.values[next_face].1 * face_size + x % face_size,
+                TransformY::FromXInv => {
+                    (self.values[next_face].1 + 1) * face_size - 1 - x % face_size
+                }
             },
         )
     }
 }
 
-fn compute_plane_password_with_wrapping(rows: &[Row], path: &Path) -> usize {
-    let max_column = rows.iter().map(|v| v.shift + v.values.len()).max().unwrap();
-    let columns = (0..max_column)
-        .map(|column| {
-            (
-                rows.iter()
-                    .enumerate()
-                    .find(|(_, v)| (v.shift..v.shift + v.values.len()).contains(&column))
-                    .map(|(v, _)| v)
-                    .unwrap(),
-                rows.iter()
-                    .enumerate()
-                    .rev()
-                    .find(|(_, v)| (v.shift..v.shift + v.values.len()).contains(&column))
-                    .map(|(v, _)| v)
-                    .unwrap(),
-            )
-        })
+pub fn compute_plane_password_with_wrapping(map: &Grid<Tile>, path: &Path) -> usize {
+    let rows = (0..map.height())
+        .map(|y| find_tiles_bounds(map.row(y).iter()))
+        .collect::<Vec<_>>();
+    let columns = (0..map.width())
+        .map(|x| find_tiles_bounds(map.column(x)))
         .collect::<Vec<_>>();
     let mut direction = Direction::Right;
-    let mut column = rows[0].shift;
+    let mut column = rows[0].0;
     let mut row = 0;
     for point in path.iter() {
         match point {
@@ -251,8 +377,8 @@ fn compute_plane_password_with_wrapping(rows: &[Row], path: &Path) -> usize {
                 for _ in 0..*steps {
                     let (new_column, new_row) = match direction {
                         Direction::Right => {
-                            if column >= rows[row].shift + rows[row].values.len() - 1 {
-                                (rows[row].shift, row)
+                            if column >= rows[row].1 {
+                                (rows[row].0, row)
                             } else {
                                 (column + 1, row)
                             }
@@ -265,8 +391,8 @@ fn compute_plane_password_with_wrapping(rows: &[Row], path: &Path) -> usize {
                             }
                         }
                         Direction::Left => {
-                            if column <= rows[row].shift {
-                                (rows[row].shift + rows[row].values.len() - 1, row)
+                            if column <= rows[row].0 {
+                                (rows[row].1, row)
                             } else {
                                 (column - 1, row)
                             }
@@ -279,10 +405,7 @@ fn compute_plane_password_with_wrapping(rows: &[Row], path: &Path) -> usize {
                             }
                         }
                     };
-                    if matches!(
-                        rows[new_row].values[new_column - rows[new_row].shift],
-                        Tile::Wall
-                    ) {
+                    if matches!(map[(new_column, new_row)], Tile::Wall) {
                         break;
                     }
                     column = new_column;
@@ -310,103 +433,109 @@ fn compute_plane_password_with_wrapping(rows: &[Row], path: &Path) -> usize {
     1000 * (row + 1) + 4 * (column + 1) + direction as usize
 }
 
-fn parse_input(input: impl BufRead) -> (Vec<Row>, Path) {
-    let mut lines = Vec::new();
+fn find_tiles_bounds<'a>(tiles: impl Iterator<Item = &'a Tile>) -> (usize, usize) {
+    let mut positions = tiles
+        .enumerate()
+        .filter(|(_, v)| !matches!(v, Tile::Void))
+        .map(|(i, _)| i);
+    let first = positions.next().unwrap();
+    (first, positions.last().unwrap_or(first))
+}
+
+pub fn parse_input(input: impl BufRead) -> Result<(Grid<Tile>, Path, KnownLayout), ParseError> {
+    let mut first_row = String::new();
+    let mut rows = Vec::new();
     let mut path = Vec::new();
     let mut parse_map = true;
-    for line in input.lines().map(|v| v.unwrap()) {
-        if line.is_empty() {
+    let mut lines_count = 0;
+    for line in lines(input) {
+        let line = line?;
+        lines_count = line.number;
+        if line.text.is_empty() {
             parse_map = false;
             continue;
         }
         if parse_map {
-            let mut shift = 0;
+            if rows.is_empty() {
+                first_row = line.text.clone();
+            }
             let mut values = Vec::new();
-            for value in line.bytes() {
-                if value == b' ' {
-                    shift += 1;
+            for (i, value) in line.text.char_indices() {
+                if value == ' ' && values.iter().all(|v| matches!(v, Tile::Void)) {
+                    values.push(Tile::Void);
                 } else {
                     values.push(match value {
-                        b'.' => Tile::Empty,
-                        b'#' => Tile::Wall,
-                        _ => unreachable!(),
+                        '.' => Tile::Empty,
+                        '#' => Tile::Wall,
+                        _ => return Err(line.error(&line.text[i..i + value.len_utf8()], ". or #")),
                     });
                 }
             }
-            lines.push(Row { values, shift });
+            rows.push(values);
         } else {
             let mut start = 0;
-            for (i, value) in line.chars().enumerate() {
+            for (i, value) in line.text.char_indices() {
                 match value {
                     'R' => {
-                        path.push(Point::Steps(usize::from_str(&line[start..i]).unwrap()));
+                        path.push(Point::Steps(line.parse(&line.text[start..i], "steps")?));
                         path.push(Point::Right);
                         start = i + 1;
                     }
                     'L' => {
-                        path.push(Point::Steps(usize::from_str(&line[start..i]).unwrap()));
+                        path.push(Point::Steps(line.parse(&line.text[start..i], "steps")?));
                         path.push(Point::Left);
                         start = i + 1;
                     }
                     _ => (),
                 }
             }
-            path.push(Point::Steps(
-                usize::from_str(&line[start..line.len()]).unwrap(),
-            ));
+            path.push(Point::Steps(line.parse(&line.text[start..], "steps")?));
         }
     }
-    (lines, path)
+    if rows.is_empty() || path.is_empty() {
+        return Err(ParseError::new(
+            lines_count + 1,
+            1,
+            "",
+            "map followed by path",
+        ));
+    }
+    let width = rows.iter().map(|v| v.len()).max().unwrap();
+    let mut map = Grid::new(width, rows.len(), Tile::Void);
+    for (y, values) in rows.into_iter().enumerate() {
+        map.row_mut(y)[..values.len()].copy_from_slice(&values);
+    }
+    let layout = find_known_layout(&map).ok_or_else(|| {
+        ParseError::new(1, 1, &first_row, "map folding into a supported cube layout")
+    })?;
+    Ok((map, path, layout))
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Direction {
+pub enum Direction {
     Right,
     Down,
     Left,
     Up,
 }
 
-type Path = Vec<Point>;
+pub type Path = Vec<Point>;
 
 #[derive(Debug)]
-enum Point {
+pub enum Point {
     Steps(usize),
     Right,
     Left,
 }
 
-#[derive(Default)]
-struct Row {
-    values: Vec<Tile>,
-    shift: usize,
-}
-
 #[derive(Clone, Copy)]
 #[repr(u8)]
-enum Tile {
+pub enum Tile {
+    Void,
     Empty,
     Wall,
 }
 
-#[cfg(test)]
-struct CubeLayoutExample;
-
-#[cfg(test)]
-impl CubeLayout for CubeLayoutExample {
-    fn faces(&self) -> &[(usize, usize); 6] {
-        &[(2, 0), (0, 1), (1, 1), (2, 1), (2, 2), (3, 2)]
-    }
-
-    fn get_next_face(
-        &self,
-        face: usize,
-        direction: Direction,
-    ) -> (usize, TransformX, TransformY, Direction) {
-        (face, TransformX::Id, TransformY::Id, direction)
-    }
-}
-
 #[test]
 fn example_test() {
     let buffer = r#"        ...#
@@ -425,19 +554,21 @@ fn example_test() {
 10R5L5R10L4R5L5
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer, &CubeLayoutExample).0, 6032);
+    assert_eq!(compute_result(buffer), Ok((6032, 5031)));
 }
 
 #[test]
-fn move_by_cube_surface_with_layout0() {
+fn move_by_cube_surface_with_known_layouts() {
     let face_size = 4;
-    for initial_direction in [
+    for (initial_direction, layout) in [
         Direction::Down,
         Direction::Right,
         Direction::Up,
         Direction::Left,
-    ] {
-        let layout = CubeLayout0;
+    ]
+    .into_iter()
+    .flat_map(|v| KnownLayout::ALL.map(|layout| (v, layout)))
+    {
         for (face_x, face_y) in layout.faces().iter() {
             for shift in 0..face_size {
                 let initial_x = face_x * face_size + shift;
@@ -451,7 +582,8 @@ fn move_by_cube_surface_with_layout0() {
                 assert_eq!(
                     (x, y, direction),
                     (initial_x, initial_y, initial_direction),
-                    "face_x={} face_y={} shift={}",
+                    "layout={:?} face_x={} face_y={} shift={}",
+                    layout,
                     face_x,
                     face_y,
                     shift
@@ -460,3 +592,24 @@ fn move_by_cube_surface_with_layout0() {
         }
     }
 }
+
+#[test]
+fn unknown_layout_test() {
+    let expected = "map folding into a supported cube layout";
+    assert_eq!(
+        compute_result("....\n....\n\n1R1\n".as_bytes()),
+        Err(ParseError::new(1, 1, "....", expected))
+    );
+    assert_eq!(
+        compute_result(" .\n..\n\n1\n".as_bytes()),
+        Err(ParseError::new(1, 1, " .", expected))
+    );
+    assert_eq!(
+        compute_result("  .\n...\n  ..\n  .\n\n1\n".as_bytes()).map(|_| ()),
+        Err(ParseError::new(1, 1, "  .", expected))
+    );
+    assert_eq!(
+        compute_result("  .\n...\n  ..\n\n1\n".as_bytes()),
+        Ok((1012, 3018))
+    );
+}
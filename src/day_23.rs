@@ -1,69 +1,239 @@
-use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
-use std::ops::RangeInclusive;
-
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
-}
-
-fn compute_result(input: impl BufRead) -> (usize, usize) {
-    let mut map = parse_map(input);
-    (
-        find_number_of_empty_tiles(map.clone()),
-        move_elves(&mut map, usize::MAX),
-    )
-}
-
-fn find_number_of_empty_tiles(mut map: HashSet<Position>) -> usize {
-    move_elves(&mut map, 10);
-    let min_x = map.iter().map(|v| v.0).min().unwrap();
-    let max_x = map.iter().map(|v| v.0).max().unwrap();
-    let min_y = map.iter().map(|v| v.1).min().unwrap();
-    let max_y = map.iter().map(|v| v.1).max().unwrap();
-    (max_x - min_x + 1) as usize * (max_y - min_y + 1) as usize - map.len()
-}
-
-fn move_elves(map: &mut HashSet<Position>, max_rounds: usize) -> usize {
-    let mut elves: Vec<Position> = map.iter().copied().collect();
-    let mut next_positions: Vec<Option<Position>> =
-        std::iter::repeat(None).take(elves.len()).collect();
-    let mut rounds = 0;
-    for _ in 0..max_rounds {
-        for (index, elf) in elves.iter_mut().enumerate() {
-            if !is_another_elf_at(*elf, -1..=1, -1..=1, map) {
-                next_positions[index] = None;
-                continue;
-            }
-            next_positions[index] = find_first_valid_suggestion(rounds, *elf, map);
+use std::ops::{Range, RangeInclusive};
+
+use serde_json::json;
+
+use crate::grid::{parse_grid, Grid};
+use crate::parse::ParseError;
+use crate::solution::{unknown_parameter, Solution};
+use crate::trace::{Trace, Tracer};
+use crate::viewer::{Bounds, Scene};
+
+#[derive(Default)]
+pub struct Day23 {
+    pub trace: Trace,
+}
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    const PARAMETERS: &'static [&'static str] = &["trace"];
+
+    type Puzzle = Grid<bool>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_map(input)
+    }
+
+    fn part_one(&self, map: &Self::Puzzle) -> Self::PartOne {
+        find_number_of_empty_tiles(map.clone(), self.trace.part(1))
+    }
+
+    fn part_two(&self, map: &Self::Puzzle) -> Self::PartTwo {
+        move_elves(map.clone(), usize::MAX, self.trace.part(2))
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "trace" => self.trace = Trace::create(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+}
+
+const MARGIN: usize = 16;
+
+pub fn compute_result(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let map = parse_map(input)?;
+    Ok((
+        find_number_of_empty_tiles(map.clone(), Tracer::disabled()),
+        move_elves(map, usize::MAX, Tracer::disabled()),
+    ))
+}
+
+pub fn find_number_of_empty_tiles(map: Grid<bool>, tracer: Tracer) -> usize {
+    let mut elves = Elves::new(map);
+    for _ in 0..10 {
+        if !elves.round(tracer) {
+            break;
+        }
+    }
+    let (x, y) = elves.bounds();
+    (x.end - x.start) as usize * (y.end - y.start) as usize - elves.positions.len()
+}
+
+pub fn move_elves(map: Grid<bool>, max_rounds: usize, tracer: Tracer) -> usize {
+    let mut elves = Elves::new(map);
+    while elves.rounds < max_rounds && elves.round(tracer) {}
+    elves.rounds
+}
+
+pub struct Elves {
+    map: Grid<bool>,
+    positions: Vec<Position>,
+    next_positions: Vec<Option<Position>>,
+    next_positions_counter: Grid<u8>,
+    rounds: usize,
+    moved: usize,
+    offset: i64,
+}
+
+impl Elves {
+    pub fn new(map: Grid<bool>) -> Self {
+        let positions = find_elves(&map);
+        Self {
+            next_positions: std::iter::repeat_n(None, positions.len()).collect(),
+            next_positions_counter: Grid::new(map.width(), map.height(), 0u8),
+            map,
+            positions,
+            rounds: 0,
+            moved: 0,
+            offset: 0,
+        }
+    }
+
+    pub fn round(&mut self, tracer: Tracer) -> bool {
+        let map = &mut self.map;
+        if self
+            .positions
+            .iter()
+            .any(|v| v.0 == 0 || v.1 == 0 || v.0 == map.width() - 1 || v.1 == map.height() - 1)
+        {
+            expand(map, &mut self.positions);
+            self.offset += MARGIN as i64;
+            self.next_positions_counter = Grid::new(map.width(), map.height(), 0u8);
+        }
+        for (index, elf) in self.positions.iter().enumerate() {
+            self.next_positions[index] = if is_another_elf_at(*elf, -1..=1, -1..=1, map) {
+                find_first_valid_suggestion(self.rounds, *elf, map)
+            } else {
+                None
+            };
         }
-        let mut next_positions_counter = HashMap::<Position, usize>::new();
-        for next_position in next_positions.iter().flatten() {
-            next_positions_counter
-                .entry(*next_position)
-                .and_modify(|v| *v += 1)
-                .or_insert(1);
+        for next_position in self.next_positions.iter().flatten() {
+            let counter = &mut self.next_positions_counter[*next_position];
+            *counter = counter.saturating_add(1);
         }
-        for (elf, next_position) in elves.iter_mut().zip(next_positions.iter_mut()) {
+        let offset = self.offset;
+        let mut moves = Vec::new();
+        self.moved = 0;
+        for (index, (elf, next_position)) in self
+            .positions
+            .iter_mut()
+            .zip(self.next_positions.iter())
+            .enumerate()
+        {
             if let Some(position) = next_position {
-                if next_positions_counter[position] == 1 {
-                    map.remove(elf);
+                if self.next_positions_counter[*position] == 1 {
+                    if tracer.is_enabled() {
+                        let (x, y) = (elf.0 as i64 - offset, elf.1 as i64 - offset);
+                        let (dx, dy) = (position.0 as i64 - offset, position.1 as i64 - offset);
+                        moves.push([index as i64, x, y, dx, dy]);
+                    }
+                    map[*elf] = false;
                     *elf = *position;
-                    map.insert(*elf);
+                    map[*elf] = true;
+                    self.moved += 1;
                 }
             }
         }
-        rounds += 1;
-        if next_positions.iter().all(|v| v.is_none()) {
-            break;
+        for next_position in self.next_positions.iter().flatten() {
+            self.next_positions_counter[*next_position] = 0;
+        }
+        tracer.event(self.rounds, || json!({"event": "round", "moves": moves}));
+        self.rounds += 1;
+        self.next_positions.iter().any(|v| v.is_some())
+    }
+
+    fn bounds(&self) -> (Range<i64>, Range<i64>) {
+        let xs = self.positions.iter().map(|v| v.0 as i64 - self.offset);
+        let ys = self.positions.iter().map(|v| v.1 as i64 - self.offset);
+        (
+            xs.clone().min().unwrap_or(0)..xs.max().unwrap_or(-1) + 1,
+            ys.clone().min().unwrap_or(0)..ys.max().unwrap_or(-1) + 1,
+        )
+    }
+}
+
+pub struct ElvesScene {
+    elves: Elves,
+    max_rounds: usize,
+    finished: bool,
+}
+
+impl ElvesScene {
+    pub fn new(map: Grid<bool>, max_rounds: usize) -> Self {
+        Self {
+            elves: Elves::new(map),
+            max_rounds,
+            finished: false,
+        }
+    }
+}
+
+impl Scene for ElvesScene {
+    fn bounds(&self) -> Bounds {
+        let (x, y) = self.elves.bounds();
+        Bounds {
+            x: x.start - 1..x.end + 1,
+            y: y.start - 1..y.end + 1,
+        }
+    }
+
+    fn focus(&self) -> (i64, i64) {
+        let (x, y) = self.elves.bounds();
+        ((x.start + x.end) / 2, (y.start + y.end) / 2)
+    }
+
+    fn tile(&self, x: i64, y: i64) -> char {
+        let (x, y) = (x + self.elves.offset, y + self.elves.offset);
+        if x >= 0 && y >= 0 && self.elves.map.get(x as usize, y as usize) == Some(&true) {
+            '#'
+        } else {
+            '.'
+        }
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "round: {}, elves moved: {}",
+            self.elves.rounds, self.elves.moved
+        )
+    }
+
+    fn step(&mut self) -> bool {
+        if !self.finished {
+            self.finished =
+                !self.elves.round(Tracer::disabled()) || self.elves.rounds >= self.max_rounds;
         }
+        !self.finished
+    }
+}
+
+fn find_elves(map: &Grid<bool>) -> Vec<Position> {
+    map.values()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v)
+        .map(|(i, _)| map.position(i))
+        .collect()
+}
+
+fn expand(map: &mut Grid<bool>, elves: &mut [Position]) {
+    let mut expanded = Grid::new(map.width() + 2 * MARGIN, map.height() + 2 * MARGIN, false);
+    for elf in elves.iter_mut() {
+        *elf = (elf.0 + MARGIN, elf.1 + MARGIN);
+        expanded[*elf] = true;
     }
-    rounds
+    *map = expanded;
 }
 
 fn find_first_valid_suggestion(
     first_direction: usize,
     position: Position,
-    map: &HashSet<Position>,
+    map: &Grid<bool>,
 ) -> Option<Position> {
     for direction_index in 0..DIRECTIONS.len() {
         match DIRECTIONS[(direction_index + first_direction) % DIRECTIONS.len()] {
@@ -94,13 +264,17 @@ fn find_first_valid_suggestion(
 
 fn is_another_elf_at(
     position: Position,
-    dx_range: RangeInclusive<i16>,
-    dy_range: RangeInclusive<i16>,
-    map: &HashSet<Position>,
+    dx_range: RangeInclusive<isize>,
+    dy_range: RangeInclusive<isize>,
+    map: &Grid<bool>,
 ) -> bool {
     for dx in dx_range {
         for dy in dy_range.clone() {
-            if (dx, dy) != (0, 0) && map.contains(&(position.0 + dx, position.1 + dy)) {
+            if (dx, dy) != (0, 0)
+                && map
+                    .offset(position.0, position.1, (dx, dy))
+                    .is_some_and(|v| map[v])
+            {
                 return true;
             }
         }
@@ -123,18 +297,18 @@ enum Direction {
     East,
 }
 
-type Position = (i16, i16);
+pub type Position = (usize, usize);
 
-fn parse_map(input: impl BufRead) -> HashSet<Position> {
-    let mut map = HashSet::new();
-    for (y, line) in input.lines().map(|v| v.unwrap()).enumerate() {
-        for (x, value) in line.bytes().enumerate() {
-            if value == b'#' {
-                map.insert((x as i16, y as i16));
-            }
-        }
+pub fn parse_map(input: impl BufRead) -> Result<Grid<bool>, ParseError> {
+    let map = parse_grid(input, ". or #", |v, _| match v {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    if !map.values().iter().any(|v| *v) {
+        return Err(ParseError::new(map.height() + 1, 1, "", "at least one elf"));
     }
-    map
+    Ok(map)
 }
 
 #[test]
@@ -153,7 +327,7 @@ fn example_test() {
 ..............
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (110, 20));
+    assert_eq!(compute_result(buffer), Ok((110, 20)));
 }
 
 #[test]
@@ -166,5 +340,5 @@ fn example_test_0() {
 .....
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (25, 4));
+    assert_eq!(compute_result(buffer), Ok((25, 4)));
 }
@@ -1,102 +1,197 @@
-use std::cmp::Reverse;
-use std::collections::hash_map::Entry;
-use std::collections::{BinaryHeap, HashMap};
 use std::io::BufRead;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
+use serde_json::json;
+
+use crate::grid::{parse_grid, Grid};
+use crate::parse::ParseError;
+use crate::search::{search, Budget, Found, Limits, Problem, SearchError};
+use crate::solution::Solution;
+use crate::trace::{Trace, Tracer};
+use crate::viewer::{Bounds, Scene};
+
+#[derive(Default)]
+pub struct Day24 {
+    pub trace: Trace,
+    pub limits: Limits,
 }
 
-fn compute_result(input: impl BufRead) -> (u16, u16) {
-    let map = parse_map(input);
-    let src_x = (0..map.width)
-        .find(|v| map.get_tile(*v as u8, 0) == b'.')
-        .unwrap() as u8;
-    let dst_x = (0..map.width)
-        .find(|v| map.get_tile(*v as u8, (map.height - 1) as u8) == b'.')
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    const PARAMETERS: &'static [&'static str] = &[
+        "trace",
+        "max-iterations",
+        "max-states",
+        "timeout",
+        "max-memory",
+    ];
+
+    type Puzzle = Map;
+    type PartOne = Result<u16, SearchError<u16>>;
+    type PartTwo = Result<u16, SearchError<u16>>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_map(input)
+    }
+
+    fn part_one(&self, map: &Self::Puzzle) -> Self::PartOne {
+        find_shortest_paths(map, 1, self.trace.part(1), self.budget())
+    }
+
+    fn part_two(&self, map: &Self::Puzzle) -> Self::PartTwo {
+        find_shortest_paths(map, 3, self.trace.part(2), self.budget())
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "trace" => self.trace = Trace::create(value)?,
+            _ => self.limits.set_parameter(name, value)?,
+        }
+        Ok(())
+    }
+}
+
+impl Day24 {
+    fn budget(&self) -> Budget {
+        self.limits.start(Budget::iterations(MAX_ITERATIONS))
+    }
+}
+
+pub fn compute_result(input: impl BufRead) -> Result<(u16, u16), ParseError> {
+    let solution = Day24::default();
+    let map = solution.parse(input)?;
+    Ok((
+        solution.part_one(&map).expect("shortest paths"),
+        solution.part_two(&map).expect("shortest paths"),
+    ))
+}
+
+pub fn find_shortest_paths(
+    map: &Map,
+    trips: usize,
+    tracer: Tracer,
+    budget: Budget,
+) -> Result<u16, SearchError<u16>> {
+    let found_trips = find_trips(map, trips, budget).map_err(SearchError::without_best)?;
+    let mut steps = 0;
+    for (trip, found) in found_trips.into_iter().enumerate() {
+        for step in found.path.iter().skip(1) {
+            tracer.event(step.steps as usize - 1, || {
+                json!({
+                    "event": "move",
+                    "trip": trip,
+                    "x": step.position.0,
+                    "y": step.position.1,
+                })
+            });
+        }
+        steps = found.state.steps;
+    }
+    Ok(steps)
+}
+
+fn find_trips(map: &Map, trips: usize, budget: Budget) -> Result<Vec<Found<State>>, SearchError> {
+    let height = map.tiles.height();
+    let src_x = map.tiles.row(0).iter().position(|v| *v == b'.').unwrap() as u8;
+    let dst_x = map
+        .tiles
+        .row(height - 1)
+        .iter()
+        .position(|v| *v == b'.')
         .unwrap() as u8;
     let src = (src_x, 0);
-    let dst = (dst_x, (map.height - 1) as u8);
-    let initial_state = State {
+    let dst = (dst_x, (height - 1) as u8);
+    let mut state = State {
         position: src,
         steps: 0,
     };
     let mut blizzards_history = vec![map.blizzards.clone()];
-    let mut busy_tiles_history = vec![map.tiles.iter().map(|v| *v == b'#').collect::<Vec<_>>()];
-    let mut find_shortest_path = |state: State, dst: (u8, u8)| {
-        find_shortest_path(
+    let mut busy_tiles_history = vec![map.tiles.map(|v| *v == b'#')];
+    let mut result = Vec::with_capacity(trips);
+    for trip in 0..trips {
+        let found = find_shortest_path(
             state,
-            dst,
-            &map,
+            if trip % 2 == 0 { dst } else { src },
+            map,
             &mut blizzards_history,
             &mut busy_tiles_history,
-        )
-    };
-    (find_shortest_path(initial_state, dst).steps, {
-        let state1 = find_shortest_path(initial_state, dst);
-        let state2 = find_shortest_path(state1, src);
-        find_shortest_path(state2, dst).steps
-    })
+            budget,
+        )?;
+        state = found.state;
+        result.push(found);
+    }
+    Ok(result)
 }
 
 const MAX_ITERATIONS: usize = 1_000_000;
 
-fn find_shortest_path(
+pub fn find_shortest_path(
     initial_state: State,
     dst: (u8, u8),
     map: &Map,
     blizzards_history: &mut Vec<Vec<Blizzard>>,
-    busy_tiles_history: &mut Vec<Vec<bool>>,
-) -> State {
-    let mut visited = HashMap::<State, usize>::new();
-    visited.insert(initial_state, 0);
-    let mut states = vec![initial_state];
-    let mut incoming = BinaryHeap::new();
-    incoming.push((Reverse(0), 0));
-    let mut actions = Vec::new();
-    let mut iterations = 0;
-    while let Some((_, state_index)) = incoming.pop() {
-        if states[state_index].position == dst {
-            return states[state_index];
-        }
-        iterations += 1;
-        if iterations >= MAX_ITERATIONS {
-            break;
-        }
-        actions.clear();
-        let depth = states[state_index].steps as usize;
-        if depth + 1 >= blizzards_history.len() {
-            let mut next_blizzards = blizzards_history[depth].clone();
-            let mut next_busy_tiles = busy_tiles_history[depth].clone();
-            move_blizzards(map, &mut next_busy_tiles, &mut next_blizzards);
-            blizzards_history.push(next_blizzards);
-            busy_tiles_history.push(next_busy_tiles);
+    busy_tiles_history: &mut Vec<Grid<bool>>,
+    budget: Budget,
+) -> Result<Found<State>, SearchError> {
+    let mut problem = Valley {
+        map,
+        dst,
+        blizzards_history,
+        busy_tiles_history,
+        actions: Vec::new(),
+    };
+    search(&mut problem, initial_state, budget).map_err(SearchError::without_best)
+}
+
+struct Valley<'a> {
+    map: &'a Map,
+    dst: (u8, u8),
+    blizzards_history: &'a mut Vec<Vec<Blizzard>>,
+    busy_tiles_history: &'a mut Vec<Grid<bool>>,
+    actions: Vec<Action>,
+}
+
+impl Problem for Valley<'_> {
+    type State = State;
+    type Key = State;
+    type Cost = u64;
+
+    fn key(&self, state: &State) -> State {
+        *state
+    }
+
+    fn cost(&self, state: &State) -> u64 {
+        get_cost(state)
+    }
+
+    fn heuristic(&self, state: &State) -> u64 {
+        get_heuristic(self.dst, state)
+    }
+
+    fn is_goal(&self, state: &State) -> bool {
+        state.position == self.dst
+    }
+
+    fn successors(&mut self, state: &State, successors: &mut Vec<State>) {
+        let depth = state.steps as usize;
+        if depth + 1 >= self.blizzards_history.len() {
+            let mut next_blizzards = self.blizzards_history[depth].clone();
+            let mut next_busy_tiles = self.busy_tiles_history[depth].clone();
+            move_blizzards(self.map, &mut next_busy_tiles, &mut next_blizzards);
+            self.blizzards_history.push(next_blizzards);
+            self.busy_tiles_history.push(next_busy_tiles);
         }
-        let busy_tiles = &busy_tiles_history[depth + 1];
-        generate_actions(&states[state_index], map, busy_tiles, &mut actions);
-        for action in actions.iter() {
-            let mut new_state = State {
-                position: states[state_index].position,
-                steps: states[state_index].steps,
-            };
+        self.actions.clear();
+        generate_actions(
+            state,
+            &self.busy_tiles_history[depth + 1],
+            &mut self.actions,
+        );
+        for action in self.actions.iter() {
+            let mut new_state = *state;
             apply_action(action, &mut new_state);
-            let cost = get_cost(&new_state);
-            match visited.entry(new_state) {
-                Entry::Occupied(v) => {
-                    if get_cost(&states[*v.get()]) > cost {
-                        incoming.push((Reverse(cost + get_heuristic(dst, &new_state)), *v.get()));
-                        states[*v.get()] = new_state;
-                    }
-                }
-                Entry::Vacant(v) => {
-                    v.insert(states.len());
-                    incoming.push((Reverse(cost + get_heuristic(dst, &new_state)), states.len()));
-                    states.push(new_state);
-                }
-            }
+            successors.push(new_state);
         }
     }
-    unreachable!();
 }
 
 fn get_cost(state: &State) -> u64 {
@@ -116,58 +211,37 @@ fn apply_action(action: &Action, state: &mut State) {
     state.steps += 1;
 }
 
-fn move_blizzards(map: &Map, busy_tiles: &mut [bool], blizzards: &mut [Blizzard]) {
+pub fn move_blizzards(map: &Map, busy_tiles: &mut Grid<bool>, blizzards: &mut [Blizzard]) {
     for blizzard in blizzards.iter_mut() {
-        busy_tiles[map.index(blizzard.position.0, blizzard.position.1)] = false;
+        busy_tiles[blizzard.tile()] = false;
         match blizzard.direction {
             Direction::Right => blizzard.position.0 += 1,
             Direction::Left => blizzard.position.0 -= 1,
             Direction::Down => blizzard.position.1 += 1,
             Direction::Up => blizzard.position.1 -= 1,
         }
-        if map.get_tile(blizzard.position.0, blizzard.position.1) == b'#' {
+        if map.tiles[blizzard.tile()] == b'#' {
             match blizzard.direction {
                 Direction::Right => blizzard.position.0 = 1,
-                Direction::Left => blizzard.position.0 = (map.width - 2) as u8,
+                Direction::Left => blizzard.position.0 = (map.tiles.width() - 2) as u8,
                 Direction::Down => blizzard.position.1 = 1,
-                Direction::Up => blizzard.position.1 = (map.height - 2) as u8,
+                Direction::Up => blizzard.position.1 = (map.tiles.height() - 2) as u8,
             }
         }
     }
     for blizzard in blizzards.iter() {
-        busy_tiles[map.index(blizzard.position.0, blizzard.position.1)] = true;
+        busy_tiles[blizzard.tile()] = true;
     }
 }
 
-fn generate_actions(state: &State, map: &Map, busy_tiles: &[bool], actions: &mut Vec<Action>) {
-    if state.position.0 > 0 && !busy_tiles[map.index(state.position.0 - 1, state.position.1)] {
-        actions.push(Action {
-            next_position: (state.position.0 - 1, state.position.1),
-        });
-    }
-    if state.position.0 < (map.width - 1) as u8
-        && !busy_tiles[map.index(state.position.0 + 1, state.position.1)]
-    {
-        actions.push(Action {
-            next_position: (state.position.0 + 1, state.position.1),
-        });
-    }
-    if state.position.1 > 0 && !busy_tiles[map.index(state.position.0, state.position.1 - 1)] {
-        actions.push(Action {
-            next_position: (state.position.0, state.position.1 - 1),
-        });
-    }
-    if state.position.1 < (map.height - 1) as u8
-        && !busy_tiles[map.index(state.position.0, state.position.1 + 1)]
-    {
-        actions.push(Action {
-            next_position: (state.position.0, state.position.1 + 1),
-        });
-    }
-    if !busy_tiles[map.index(state.position.0, state.position.1)] {
-        actions.push(Action {
-            next_position: state.position,
-        });
+fn generate_actions(state: &State, busy_tiles: &Grid<bool>, actions: &mut Vec<Action>) {
+    let (x, y) = (state.position.0 as usize, state.position.1 as usize);
+    for (next_x, next_y) in busy_tiles.neighbors4(x, y).chain([(x, y)]) {
+        if !busy_tiles[(next_x, next_y)] {
+            actions.push(Action {
+                next_position: (next_x as u8, next_y as u8),
+            });
+        }
     }
 }
 
@@ -177,85 +251,181 @@ struct Action {
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-struct State {
+pub struct State {
     position: (u8, u8),
     steps: u16,
 }
 
-fn parse_map(input: impl BufRead) -> Map {
-    let mut width = 0;
-    let mut height = 0;
-    let mut blizzards = Vec::new();
-    let mut tiles = Vec::new();
-    for (y, line) in input.lines().map(|v| v.unwrap()).enumerate() {
-        width = line.len();
-        for (x, value) in line.bytes().enumerate() {
-            let position = (x as u8, y as u8);
-            match value {
-                b'<' => {
-                    blizzards.push(Blizzard {
-                        position,
-                        direction: Direction::Left,
-                    });
-                }
-                b'>' => {
-                    blizzards.push(Blizzard {
-                        position,
-                        direction: Direction::Right,
-                    });
-                }
-                b'^' => {
-                    blizzards.push(Blizzard {
-                        position,
-                        direction: Direction::Up,
-                    });
-                }
-                b'v' => {
-                    blizzards.push(Blizzard {
-                        position,
-                        direction: Direction::Down,
-                    });
+pub struct ValleyScene {
+    map: Map,
+    blizzards: Vec<Blizzard>,
+    busy_tiles: Grid<bool>,
+    tiles: Grid<char>,
+    path: Vec<State>,
+    minute: usize,
+    error: Option<SearchError>,
+}
+
+impl ValleyScene {
+    pub fn new(map: Map, trips: usize) -> Self {
+        let (path, error) = match find_trips(&map, trips, Budget::iterations(MAX_ITERATIONS)) {
+            Ok(trips) => {
+                let mut path = vec![trips[0].path[0]];
+                for found in trips {
+                    path.extend(found.path.into_iter().skip(1));
                 }
-                _ => (),
+                (path, None)
             }
-            tiles.push(value);
-        }
-        height += 1;
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        let mut scene = Self {
+            blizzards: map.blizzards.clone(),
+            busy_tiles: map.tiles.map(|v| *v == b'#'),
+            tiles: map.tiles.map(|_| '.'),
+            map,
+            path,
+            minute: 0,
+            error,
+        };
+        scene.render_tiles();
+        scene
     }
-    Map {
-        width,
-        height,
-        blizzards,
-        tiles,
+
+    fn render_tiles(&mut self) {
+        for (tile, value) in self
+            .tiles
+            .values_mut()
+            .iter_mut()
+            .zip(self.map.tiles.values())
+        {
+            *tile = if *value == b'#' { '#' } else { '.' };
+        }
+        for blizzard in self.blizzards.iter() {
+            let tile = &mut self.tiles[blizzard.tile()];
+            *tile = match *tile {
+                '.' => match blizzard.direction {
+                    Direction::Right => '>',
+                    Direction::Left => '<',
+                    Direction::Down => 'v',
+                    Direction::Up => '^',
+                },
+                '<' | '>' | '^' | 'v' => '2',
+                v => char::from_digit(v.to_digit(10).unwrap_or(9) + 1, 10).unwrap_or('9'),
+            };
+        }
+        if let Some(state) = self.path.get(self.minute) {
+            self.tiles[(state.position.0 as usize, state.position.1 as usize)] = 'E';
+        }
     }
 }
 
-struct Map {
-    width: usize,
-    height: usize,
-    blizzards: Vec<Blizzard>,
-    tiles: Vec<u8>,
-}
+impl Scene for ValleyScene {
+    fn bounds(&self) -> Bounds {
+        Bounds {
+            x: 0..self.tiles.width() as i64,
+            y: 0..self.tiles.height() as i64,
+        }
+    }
+
+    fn focus(&self) -> (i64, i64) {
+        match self.path.get(self.minute) {
+            Some(state) => (state.position.0 as i64, state.position.1 as i64),
+            None => (0, 0),
+        }
+    }
+
+    fn tile(&self, x: i64, y: i64) -> char {
+        if x < 0 || y < 0 {
+            return ' ';
+        }
+        self.tiles
+            .get(x as usize, y as usize)
+            .copied()
+            .unwrap_or(' ')
+    }
+
+    fn status(&self) -> String {
+        match self.error {
+            Some(error) => format!("minute: {}, {}", self.minute, error),
+            None => format!("minute: {}", self.minute),
+        }
+    }
 
-impl Map {
-    fn get_tile(&self, x: u8, y: u8) -> u8 {
-        self.tiles[self.index(x, y)]
+    fn step(&mut self) -> bool {
+        if self.minute + 1 >= self.path.len() {
+            return false;
+        }
+        move_blizzards(&self.map, &mut self.busy_tiles, &mut self.blizzards);
+        self.minute += 1;
+        self.render_tiles();
+        self.minute + 1 < self.path.len()
     }
+}
 
-    fn index(&self, x: u8, y: u8) -> usize {
-        x as usize + y as usize * self.width
+pub fn parse_map(input: impl BufRead) -> Result<Map, ParseError> {
+    let mut blizzards = Vec::new();
+    let tiles = parse_grid(input, "#, ., <, >, ^ or v", |v, (x, y)| {
+        let direction = match v {
+            '<' => Direction::Left,
+            '>' => Direction::Right,
+            '^' => Direction::Up,
+            'v' => Direction::Down,
+            '#' | '.' => return Some(v as u8),
+            _ => return None,
+        };
+        blizzards.push(Blizzard {
+            position: (x as u8, y as u8),
+            direction,
+        });
+        Some(v as u8)
+    })?;
+    if tiles.width() > u8::MAX as usize || tiles.height() > u8::MAX as usize {
+        return Err(ParseError::new(1, 1, "", "valley of at most 255x255 tiles"));
+    }
+    if !tiles.row(0).contains(&b'.') {
+        return Err(ParseError::new(
+            1,
+            1,
+            &render_row(&tiles, 0),
+            "row with an entrance",
+        ));
     }
+    let last = tiles.height() - 1;
+    if last == 0 || !tiles.row(last).contains(&b'.') {
+        return Err(ParseError::new(
+            last + 1,
+            1,
+            &render_row(&tiles, last),
+            "row with an exit",
+        ));
+    }
+    Ok(Map { tiles, blizzards })
+}
+
+fn render_row(tiles: &Grid<u8>, y: usize) -> String {
+    tiles.row(y).iter().map(|v| *v as char).collect()
+}
+
+pub struct Map {
+    tiles: Grid<u8>,
+    blizzards: Vec<Blizzard>,
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Blizzard {
+pub struct Blizzard {
     position: (u8, u8),
     direction: Direction,
 }
 
+impl Blizzard {
+    fn tile(&self) -> (usize, usize) {
+        (self.position.0 as usize, self.position.1 as usize)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[repr(u8)]
-enum Direction {
+pub enum Direction {
     Right,
     Left,
     Down,
@@ -272,5 +442,5 @@ fn example_test() {
 ######.#
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (18, 54));
+    assert_eq!(compute_result(buffer), Ok((18, 54)));
 }
@@ -1,39 +1,110 @@
+use std::fmt::{Display, Formatter};
 use std::io::BufRead;
 
-fn main() {
-    println!("{}", sum_snafu_numbers(std::io::stdin().lock()));
+use crate::parse::{lines, ParseError};
+use crate::solution::{NoAnswer, Solution};
+
+#[derive(Default)]
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+
+    type Puzzle = Vec<String>;
+    type PartOne = Option<String>;
+    type PartTwo = NoAnswer;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        lines(input)
+            .map(|v| {
+                let line = v?;
+                if line.text.is_empty() {
+                    return Err(line.error(&line.text, "SNAFU number"));
+                }
+                if let Some((i, v)) = line
+                    .text
+                    .char_indices()
+                    .find(|(_, v)| !matches!(v, '=' | '-' | '0' | '1' | '2'))
+                {
+                    return Err(line.error(&line.text[i..i + v.len_utf8()], "SNAFU digit"));
+                }
+                Ok(line.text)
+            })
+            .collect()
+    }
+
+    fn part_one(&self, snafu_numbers: &Self::Puzzle) -> Self::PartOne {
+        sum_snafu(snafu_numbers).ok()
+    }
+
+    fn part_two(&self, _: &Self::Puzzle) -> Self::PartTwo {
+        NoAnswer
+    }
 }
 
-fn sum_snafu_numbers(input: impl BufRead) -> String {
-    let snafu_numbers: Vec<String> = input.lines().map(|v| v.unwrap()).collect();
-    let decimal_sum = snafu_numbers.iter().map(|v| snafu_to_decimal(v)).sum();
-    decimal_to_snafu(decimal_sum).unwrap()
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SnafuError {
+    InvalidDigit(char),
+    InvalidValue(i128),
+    NoFactor { decimal: i128, base: i128 },
+    Overflow,
 }
 
-fn snafu_to_decimal(snafu: &str) -> i64 {
-    let mut result = 0;
-    let mut power = 1;
-    for digit in snafu.chars().rev() {
+impl Display for SnafuError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnafuError::InvalidDigit(digit) => write!(f, "invalid SNAFU digit: {:?}", digit),
+            SnafuError::InvalidValue(value) => write!(f, "invalid SNAFU digit value: {}", value),
+            SnafuError::NoFactor { decimal, base } => {
+                write!(f, "no SNAFU digit for {} at base {}", decimal, base)
+            }
+            SnafuError::Overflow => write!(f, "SNAFU number does not fit into 64 bits"),
+        }
+    }
+}
+
+impl std::error::Error for SnafuError {}
+
+pub fn sum_snafu_numbers(input: impl BufRead) -> Result<Option<String>, ParseError> {
+    Ok(Day25.part_one(&Day25.parse(input)?))
+}
+
+pub fn sum_snafu(snafu_numbers: &[String]) -> Result<String, SnafuError> {
+    let mut decimal_sum: i64 = 0;
+    for snafu in snafu_numbers.iter() {
+        decimal_sum = decimal_sum
+            .checked_add(snafu_to_decimal(snafu)?)
+            .ok_or(SnafuError::Overflow)?;
+    }
+    decimal_to_snafu(decimal_sum)
+}
+
+pub fn snafu_to_decimal(snafu: &str) -> Result<i64, SnafuError> {
+    let mut result: i128 = 0;
+    for digit in snafu.chars() {
         let decimal = match digit {
             '=' => -2,
             '-' => -1,
             '0' => 0,
             '1' => 1,
             '2' => 2,
-            _ => unreachable!(),
+            v => return Err(SnafuError::InvalidDigit(v)),
         };
-        result += decimal * power;
-        power *= 5;
+        result = result * 5 + decimal;
+        if result.unsigned_abs() > i64::MAX as u128 + 1 {
+            return Err(SnafuError::Overflow);
+        }
     }
-    result
+    i64::try_from(result).map_err(|_| SnafuError::Overflow)
 }
 
-fn decimal_to_snafu(mut decimal: i64) -> Result<String, String> {
+pub fn decimal_to_snafu(decimal: i64) -> Result<String, SnafuError> {
+    let mut decimal = decimal as i128;
     let mut base = find_snafu_base(decimal);
     let mut limit = get_limit(base / 5);
     let mut result = String::new();
     while base > 1 {
-        let factor = find_factor(decimal, base, limit);
+        let factor = find_factor(decimal, base, limit)?;
         decimal -= base * factor;
         base /= 5;
         limit -= 2 * base;
@@ -43,7 +114,7 @@ fn decimal_to_snafu(mut decimal: i64) -> Result<String, String> {
     Ok(result)
 }
 
-fn get_limit(mut base: i64) -> i64 {
+fn get_limit(mut base: i128) -> i128 {
     let mut result = 0;
     while base > 0 {
         result += 2 * base;
@@ -52,42 +123,36 @@ fn get_limit(mut base: i64) -> i64 {
     result
 }
 
-fn find_snafu_base(decimal: i64) -> i64 {
-    if (-2..=2).contains(&decimal) {
-        return 1;
-    }
-    let mut base = 5;
-    loop {
-        if decimal > 0 && decimal - 2 * base <= 2 || decimal < 0 && decimal + 2 * base >= -2 {
-            break;
-        }
+fn find_snafu_base(decimal: i128) -> i128 {
+    let mut base = 1;
+    while get_limit(base) < decimal.abs() {
         base *= 5;
     }
     base
 }
 
-fn find_factor(decimal: i64, base: i64, limit: i64) -> i64 {
+fn find_factor(decimal: i128, base: i128, limit: i128) -> Result<i128, SnafuError> {
     if decimal == 0 {
-        return 0;
+        return Ok(0);
     }
     for factor in 0..=2 {
         if decimal > 0 && decimal - base * factor <= limit {
-            return factor;
+            return Ok(factor);
         } else if decimal < 0 && decimal + base * factor >= -limit {
-            return -factor;
+            return Ok(-factor);
         }
     }
-    unreachable!();
+    Err(SnafuError::NoFactor { decimal, base })
 }
 
-fn decimal_to_snafu_digit(decimal: i64) -> Result<char, String> {
+fn decimal_to_snafu_digit(decimal: i128) -> Result<char, SnafuError> {
     match decimal {
         -2 => Ok('='),
         -1 => Ok('-'),
         0 => Ok('0'),
         1 => Ok('1'),
         2 => Ok('2'),
-        v => Err(format!("invalid SNAFU digit: {}", v)),
+        v => Err(SnafuError::InvalidValue(v)),
     }
 }
 
@@ -108,7 +173,7 @@ fn example_test() {
 122
 "#
     .as_bytes();
-    assert_eq!(sum_snafu_numbers(buffer), "2=-1=0");
+    assert_eq!(sum_snafu_numbers(buffer), Ok(Some(String::from("2=-1=0"))));
 }
 
 #[test]
@@ -202,7 +267,7 @@ fn conversion_test() {
     for (decimal, snafu) in SAMPLES.iter() {
         assert_eq!(
             snafu_to_decimal(snafu),
-            *decimal,
+            Ok(*decimal),
             "snafu to decimal: {} -> {}",
             snafu,
             decimal
@@ -224,6 +289,61 @@ fn conversion_test() {
 fn conversion_test_1() {
     for value in -100000..100000 {
         let snafu = decimal_to_snafu(value).unwrap();
-        assert_eq!(value, snafu_to_decimal(&snafu));
+        assert_eq!(Ok(value), snafu_to_decimal(&snafu));
+    }
+}
+
+#[cfg(test)]
+fn decimal_to_snafu_naively(mut decimal: i64) -> String {
+    if decimal == 0 {
+        return String::from("0");
+    }
+    let mut digits = Vec::new();
+    while decimal != 0 {
+        let digit = (decimal + 2).rem_euclid(5) - 2;
+        digits.push(['=', '-', '0', '1', '2'][(digit + 2) as usize]);
+        decimal = (decimal - digit) / 5;
+    }
+    digits.iter().rev().collect()
+}
+
+#[test]
+fn conversion_property_test() {
+    use crate::property::{check, expect_eq, shrink_integer};
+
+    check(
+        "decimal_to_snafu",
+        1000,
+        |rng| {
+            let magnitude = 5i64.pow(rng.range(0, 25) as u32);
+            rng.range(-magnitude, magnitude)
+        },
+        |decimal| shrink_integer(*decimal),
+        |decimal| {
+            let snafu = decimal_to_snafu(*decimal).map_err(|e| e.to_string())?;
+            expect_eq(snafu.as_str(), decimal_to_snafu_naively(*decimal).as_str())?;
+            expect_eq(snafu_to_decimal(&snafu), Ok(*decimal))
+        },
+    );
+}
+
+#[test]
+fn invalid_snafu_test() {
+    assert_eq!(snafu_to_decimal("1x"), Err(SnafuError::InvalidDigit('x')));
+    assert_eq!(snafu_to_decimal(&"2".repeat(28)), Err(SnafuError::Overflow));
+    assert_eq!(
+        sum_snafu(&[decimal_to_snafu(i64::MAX).unwrap(), String::from("1")]),
+        Err(SnafuError::Overflow)
+    );
+    for value in [i64::MAX, i64::MIN] {
+        let snafu = decimal_to_snafu(value).unwrap();
+        assert_eq!(snafu_to_decimal(&snafu), Ok(value));
     }
+    assert_eq!(
+        find_factor(100, 1, 2),
+        Err(SnafuError::NoFactor {
+            decimal: 100,
+            base: 1
+        })
+    );
 }
@@ -1,75 +1,284 @@
-use std::collections::{HashMap, HashSet};
 use std::io::BufRead;
 
-fn main() {
-    println!("{:?}", get_all_sums(std::io::stdin().lock()));
+use crate::parse::ParseError;
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+
+pub struct Day3 {
+    pub rules: Rules,
 }
 
-fn get_all_sums(mut buffer: impl BufRead) -> (u64, u64) {
-    let mut copied_buffer = Vec::new();
-    buffer.read_to_end(&mut copied_buffer).unwrap();
-    (
-        sum_priorities_for_items_in_both_compartments(copied_buffer.as_slice()),
-        sum_priorities_of_group_badges(copied_buffer.as_slice()),
-    )
+impl Default for Day3 {
+    fn default() -> Self {
+        Self {
+            rules: Rules {
+                compartments: 2,
+                group_size: 3,
+            },
+        }
+    }
 }
 
-fn sum_priorities_of_group_badges(buffer: impl BufRead) -> u64 {
-    let mut total_priority = 0;
-    let mut items_counter: HashMap<u8, usize> = HashMap::new();
-    for (i, line) in buffer.lines().map(|v| v.unwrap()).enumerate() {
-        if i % 3 == 0 {
-            for (item, count) in items_counter.iter() {
-                if *count == 3 {
-                    total_priority += get_item_priority(*item);
-                    break;
-                }
-            }
-            items_counter.clear();
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    const PARAMETERS: &'static [&'static str] = &["compartments", "group-size"];
+
+    type Puzzle = Vec<Rucksack>;
+    type PartOne = Option<u64>;
+    type PartTwo = Option<u64>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_rucksacks(input, &self.rules)
+    }
+
+    fn part_one(&self, rucksacks: &Self::Puzzle) -> Self::PartOne {
+        sum_priorities(&get_shared_compartment_items(rucksacks))
+    }
+
+    fn part_two(&self, rucksacks: &Self::Puzzle) -> Self::PartTwo {
+        sum_priorities(&get_badges(rucksacks, self.rules.group_size))
+    }
+
+    fn validate(&self, rucksacks: &Self::Puzzle) -> Vec<String> {
+        let mut problems: Vec<String> = get_shared_compartment_items(rucksacks)
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.len() != 1)
+            .map(|(i, v)| {
+                format!(
+                    "rucksack {}: compartments share {} items ({})",
+                    i + 1,
+                    v.len(),
+                    v
+                )
+            })
+            .collect();
+        problems.extend(
+            get_badges(rucksacks, self.rules.group_size)
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.len() != 1)
+                .map(|(i, v)| {
+                    format!("group {}: rucksacks share {} items ({})", i + 1, v.len(), v)
+                }),
+        );
+        problems
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value: usize = parse_parameter(name, value)?;
+        if value == 0 {
+            return Err(format!("invalid value for --{}: {}", name, value));
         }
-        let unique_items: HashSet<u8> = line.as_bytes().iter().copied().collect();
-        for item in unique_items.iter() {
-            *items_counter.entry(*item).or_insert(0usize) += 1;
+        match name {
+            "compartments" => self.rules.compartments = value,
+            "group-size" => self.rules.group_size = value,
+            _ => return Err(unknown_parameter(name)),
         }
+        Ok(())
     }
-    for (item, count) in items_counter.iter() {
-        if *count == 3 {
-            total_priority += get_item_priority(*item);
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Rules {
+    pub compartments: usize,
+    pub group_size: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rucksack {
+    pub compartments: Vec<ItemSet>,
+}
+
+impl Rucksack {
+    pub fn items(&self) -> ItemSet {
+        ItemSet::union(&self.compartments)
+    }
+}
+
+pub fn get_all_sums(buffer: impl BufRead) -> Result<(Option<u64>, Option<u64>), ParseError> {
+    let rules = Day3::default().rules;
+    let rucksacks = parse_rucksacks(buffer, &rules)?;
+    Ok((
+        sum_priorities(&get_shared_compartment_items(&rucksacks)),
+        sum_priorities(&get_badges(&rucksacks, rules.group_size)),
+    ))
+}
+
+pub fn sum_priorities(items: &[ItemSet]) -> Option<u64> {
+    items
+        .iter()
+        .map(|v| v.single().and_then(get_item_priority))
+        .sum()
+}
+
+pub fn get_shared_compartment_items(rucksacks: &[Rucksack]) -> Vec<ItemSet> {
+    rucksacks
+        .iter()
+        .map(|v| ItemSet::intersection(&v.compartments))
+        .collect()
+}
+
+pub fn get_badges(rucksacks: &[Rucksack], group_size: usize) -> Vec<ItemSet> {
+    rucksacks
+        .chunks(group_size)
+        .map(|group| ItemSet::intersection(&group.iter().map(Rucksack::items).collect::<Vec<_>>()))
+        .collect()
+}
+
+pub fn parse_rucksacks(
+    mut input: impl BufRead,
+    rules: &Rules,
+) -> Result<Vec<Rucksack>, ParseError> {
+    let mut line = Vec::new();
+    let mut rucksacks = Vec::new();
+    let mut number = 0;
+    loop {
+        line.clear();
+        let size = input
+            .read_until(b'\n', &mut line)
+            .map_err(|e| ParseError::new(number + 1, 1, "", format!("readable line ({})", e)))?;
+        if size == 0 {
             break;
         }
+        number += 1;
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        let error = |expected: String| {
+            ParseError::new(number, 1, &String::from_utf8_lossy(&line), expected)
+        };
+        if line.len() < rules.compartments {
+            return Err(error(format!("at least {} items", rules.compartments)));
+        }
+        if line.len() % rules.compartments != 0 {
+            return Err(error(format!(
+                "number of items divisible by {}",
+                rules.compartments
+            )));
+        }
+        let compartment_size = line.len() / rules.compartments;
+        let compartments = line
+            .chunks(compartment_size)
+            .enumerate()
+            .map(|(i, items)| {
+                ItemSet::from_items(items).map_err(|e| {
+                    let index = i * compartment_size + e.index;
+                    let token = String::from_utf8_lossy(&line[index..index + 1]);
+                    ParseError::new(number, index + 1, &token, "item letter")
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        rucksacks.push(Rucksack { compartments });
     }
-    total_priority
+    let remainder = rucksacks.len() % rules.group_size;
+    if remainder != 0 {
+        return Err(ParseError::new(
+            number + 1,
+            1,
+            "",
+            format!(
+                "{} more rucksacks to complete group of {}",
+                rules.group_size - remainder,
+                rules.group_size
+            ),
+        ));
+    }
+    Ok(rucksacks)
 }
 
-fn sum_priorities_for_items_in_both_compartments(buffer: impl BufRead) -> u64 {
-    let mut total_priority = 0;
-    for line in buffer.lines().map(|v| v.unwrap()) {
-        let rucksack_size = line.as_bytes().len();
-        let first_compartment: HashSet<u8> = line.as_bytes()[0..rucksack_size / 2]
-            .iter()
-            .copied()
-            .collect();
-        let second_compartment: HashSet<u8> = line.as_bytes()[rucksack_size / 2..rucksack_size]
-            .iter()
-            .copied()
-            .collect();
-        for item in first_compartment.iter() {
-            if second_compartment.contains(item) {
-                total_priority += get_item_priority(*item);
-            }
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub fn from_items(items: &[u8]) -> Result<Self, InvalidItem> {
+        let mut result = 0;
+        for (index, item) in items.iter().enumerate() {
+            let priority = get_item_priority(*item).ok_or(InvalidItem { index, item: *item })?;
+            result |= 1 << priority;
+        }
+        Ok(Self(result))
+    }
+
+    pub fn union(sets: &[ItemSet]) -> Self {
+        Self(sets.iter().fold(0, |result, set| result | set.0))
+    }
+
+    pub fn intersection(sets: &[ItemSet]) -> Self {
+        match sets.split_first() {
+            Some((first, rest)) => Self(rest.iter().fold(first.0, |result, set| result & set.0)),
+            None => Self::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn single(&self) -> Option<u8> {
+        if self.len() == 1 {
+            self.items().next()
+        } else {
+            None
+        }
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = u8> + '_ {
+        (1..=52)
+            .filter(|v| self.0 & (1 << v) != 0)
+            .map(get_item_by_priority)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InvalidItem {
+    pub index: usize,
+    pub item: u8,
+}
+
+impl std::fmt::Display for InvalidItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid item {:?} at index {}",
+            self.item as char, self.index
+        )
+    }
+}
+
+impl std::error::Error for InvalidItem {}
+
+impl std::fmt::Display for ItemSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
         }
+        for item in self.items() {
+            write!(f, "{}", item as char)?;
+        }
+        Ok(())
     }
-    total_priority
 }
 
-fn get_item_priority(item: u8) -> u64 {
-    if (b'a'..=b'z').contains(&item) {
-        return (item - b'a') as u64 + 1;
+pub fn get_item_by_priority(priority: u64) -> u8 {
+    if priority <= 26 {
+        b'a' + (priority - 1) as u8
+    } else {
+        b'A' + (priority - 27) as u8
     }
-    if (b'A'..=b'Z').contains(&item) {
-        return (item - b'A') as u64 + 27;
+}
+
+pub fn get_item_priority(item: u8) -> Option<u64> {
+    if item.is_ascii_lowercase() {
+        return Some((item - b'a') as u64 + 1);
     }
-    unreachable!();
+    if item.is_ascii_uppercase() {
+        return Some((item - b'A') as u64 + 27);
+    }
+    None
 }
 
 #[test]
@@ -82,5 +291,102 @@ ttgJtRGJQctTZtZT
 CrZsJsPPZsGzwwsLwLmpwMDw
 "#
     .as_bytes();
-    assert_eq!(get_all_sums(buffer), (157, 70));
+    assert_eq!(get_all_sums(buffer), Ok((Some(157), Some(70))));
+}
+
+#[test]
+fn chunked_input_test() {
+    use crate::parse::ChunkedReader;
+
+    let buffer = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\n";
+    let rucksacks = parse_rucksacks(
+        ChunkedReader::new(buffer.as_bytes(), 3),
+        &Day3::default().rules,
+    )
+    .unwrap();
+    assert_eq!(
+        sum_priorities(&get_shared_compartment_items(&rucksacks)),
+        Some(16 + 38 + 42)
+    );
+    assert_eq!(sum_priorities(&get_badges(&rucksacks, 3)), Some(18));
+    assert_eq!(
+        parse_rucksacks(ChunkedReader::new(b"aa\nab1c\n", 2), &Day3::default().rules),
+        Err(ParseError::new(2, 3, "1", "item letter"))
+    );
+}
+
+#[test]
+fn shared_items_test() {
+    let buffer = "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\nwMqvLMZHhHMvwLHjbvcjnnSBnvTQFn\n";
+    let solution = Day3 {
+        rules: Rules {
+            compartments: 2,
+            group_size: 2,
+        },
+    };
+    let rucksacks = solution.parse(buffer.as_bytes()).unwrap();
+    assert_eq!(solution.part_one(&rucksacks), Some(16 + 38 + 42 + 22));
+    assert_eq!(solution.part_two(&rucksacks), None);
+    assert_eq!(
+        solution.validate(&rucksacks),
+        vec![
+            String::from("group 1: rucksacks share 5 items (frsFM)"),
+            String::from("group 2: rucksacks share 5 items (qvwBT)"),
+        ]
+    );
+    let solution = Day3 {
+        rules: Rules {
+            compartments: 3,
+            group_size: 1,
+        },
+    };
+    let rucksacks = solution
+        .parse("aaa\nabcabd\naBxaBuaBz\n".as_bytes())
+        .unwrap();
+    assert_eq!(solution.part_one(&rucksacks), None);
+    assert_eq!(solution.part_two(&rucksacks), None);
+    assert_eq!(
+        solution.validate(&rucksacks),
+        vec![
+            String::from("rucksack 2: compartments share 0 items (none)"),
+            String::from("rucksack 3: compartments share 2 items (aB)"),
+            String::from("group 2: rucksacks share 4 items (abcd)"),
+            String::from("group 3: rucksacks share 5 items (auxzB)"),
+        ]
+    );
+    let parse = |buffer: &str| parse_rucksacks(buffer.as_bytes(), &solution.rules);
+    assert_eq!(
+        parse("aBxaBya\n").unwrap_err().expected,
+        "number of items divisible by 3"
+    );
+    assert_eq!(
+        parse("aaa\n\naaa\n"),
+        Err(ParseError::new(2, 1, "", "at least 3 items"))
+    );
+    assert_eq!(
+        parse("aaa\nab\n"),
+        Err(ParseError::new(2, 1, "ab", "at least 3 items"))
+    );
+    assert_eq!(
+        parse_rucksacks("aa\n".as_bytes(), &Day3::default().rules),
+        Err(ParseError::new(
+            2,
+            1,
+            "",
+            "2 more rucksacks to complete group of 3"
+        ))
+    );
+}
+
+#[test]
+fn invalid_item_test() {
+    assert_eq!(
+        ItemSet::from_items(b"ab1"),
+        Err(InvalidItem {
+            index: 2,
+            item: b'1'
+        })
+    );
+    assert_eq!(get_item_priority(b'Z'), Some(52));
+    assert_eq!(get_item_priority(b' '), None);
 }
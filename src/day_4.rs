@@ -1,57 +1,114 @@
 use std::io::BufRead;
 use std::ops::RangeInclusive;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", count_assignment_pairs(std::io::stdin().lock()));
+use crate::interval::{find_max_depth, IntervalSet};
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day4;
+
+pub type Group = Vec<RangeInclusive<i64>>;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+
+    type Puzzle = Vec<Group>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_groups(input)
+    }
+
+    fn part_one(&self, groups: &Self::Puzzle) -> Self::PartOne {
+        count_fully_overlapping_assignment_pairs(groups)
+    }
+
+    fn part_two(&self, groups: &Self::Puzzle) -> Self::PartTwo {
+        count_partially_overlapping_assignment_pairs(groups)
+    }
 }
 
-fn count_assignment_pairs(buffer: impl BufRead) -> (usize, usize) {
-    let pairs = parse_pairs(buffer);
-    (
-        count_fully_overlapping_assignment_pairs(&pairs),
-        count_partially_overlapping_assignment_pairs(&pairs),
-    )
+pub fn count_assignment_pairs(buffer: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let groups = parse_groups(buffer)?;
+    Ok((
+        count_fully_overlapping_assignment_pairs(&groups),
+        count_partially_overlapping_assignment_pairs(&groups),
+    ))
 }
 
-fn count_partially_overlapping_assignment_pairs(
-    pairs: &[(RangeInclusive<u64>, RangeInclusive<u64>)],
-) -> usize {
-    pairs
+pub fn count_partially_overlapping_assignment_pairs(groups: &[Group]) -> usize {
+    groups
         .iter()
-        .filter(|(first, second)| overlaps(first, second))
+        .filter(|group| {
+            group
+                .iter()
+                .enumerate()
+                .any(|(i, first)| group[i + 1..].iter().any(|second| overlaps(first, second)))
+        })
         .count()
 }
 
-fn overlaps(a: &RangeInclusive<u64>, b: &RangeInclusive<u64>) -> bool {
+pub fn overlaps(a: &RangeInclusive<i64>, b: &RangeInclusive<i64>) -> bool {
     a.contains(b.start()) || a.contains(b.end()) || b.contains(a.start()) || b.contains(a.end())
 }
 
-fn count_fully_overlapping_assignment_pairs(
-    pairs: &[(RangeInclusive<u64>, RangeInclusive<u64>)],
-) -> usize {
-    pairs
+pub fn count_fully_overlapping_assignment_pairs(groups: &[Group]) -> usize {
+    groups
         .iter()
-        .filter(|(first, second)| contains(first, second) || contains(second, first))
+        .filter(|group| {
+            group
+                .iter()
+                .any(|first| group.iter().all(|second| contains(first, second)))
+        })
         .count()
 }
 
-fn contains(containing: &RangeInclusive<u64>, contained: &RangeInclusive<u64>) -> bool {
+pub fn contains(containing: &RangeInclusive<i64>, contained: &RangeInclusive<i64>) -> bool {
     containing.start() <= contained.start() && contained.end() <= containing.end()
 }
 
-fn parse_pairs(buffer: impl BufRead) -> Vec<(RangeInclusive<u64>, RangeInclusive<u64>)> {
-    buffer.lines().map(|v| parse_pair(&v.unwrap())).collect()
+pub fn get_covered_sections(groups: &[Group]) -> IntervalSet {
+    groups.iter().flatten().cloned().collect()
+}
+
+pub fn find_uncovered_sections(groups: &[Group]) -> IntervalSet {
+    let covered = get_covered_sections(groups);
+    match covered.bounds() {
+        Some(bounds) => covered.gaps(bounds),
+        None => IntervalSet::new(),
+    }
+}
+
+pub fn find_max_overlap_depth(groups: &[Group]) -> usize {
+    let sections: Vec<RangeInclusive<i64>> = groups.iter().flatten().cloned().collect();
+    find_max_depth(&sections)
 }
 
-fn parse_pair(value: &str) -> (RangeInclusive<u64>, RangeInclusive<u64>) {
-    let (first, second) = value.split_once(',').unwrap();
-    (parse_range(first), parse_range(second))
+pub fn parse_groups(buffer: impl BufRead) -> Result<Vec<Group>, ParseError> {
+    lines(buffer).map(|v| parse_group(&v?)).collect()
 }
 
-fn parse_range(value: &str) -> RangeInclusive<u64> {
-    let (start, end) = value.split_once('-').unwrap();
-    u64::from_str(start).unwrap()..=u64::from_str(end).unwrap()
+fn parse_group(line: &Line) -> Result<Group, ParseError> {
+    let (first, rest) = line.split_once(&line.text, ",")?;
+    let mut group = vec![parse_range(line, first)?];
+    for value in rest.split(',') {
+        group.push(parse_range(line, value)?);
+    }
+    Ok(group)
+}
+
+fn parse_range(line: &Line, value: &str) -> Result<RangeInclusive<i64>, ParseError> {
+    let (start, end) = line.split_once(value, "-")?;
+    Ok(parse_section(line, start)?..=parse_section(line, end)?)
+}
+
+fn parse_section(line: &Line, value: &str) -> Result<i64, ParseError> {
+    match line.parse(value, "section number")? {
+        section if section >= 0 => Ok(section),
+        _ => Err(line.error(value, "section number")),
+    }
 }
 
 #[test]
@@ -64,5 +121,42 @@ fn example_test() {
 2-6,4-8
 "#
     .as_bytes();
-    assert_eq!(count_assignment_pairs(buffer), (2, 4));
+    assert_eq!(count_assignment_pairs(buffer), Ok((2, 4)));
+}
+
+#[test]
+fn section_queries_test() {
+    let buffer = "2-4,6-8\n12-13,4-5\n5-7,7-9,6-6\n2-8,3-7,8-8\n".as_bytes();
+    let groups = parse_groups(buffer).unwrap();
+    assert_eq!(count_fully_overlapping_assignment_pairs(&groups), 1);
+    assert_eq!(count_partially_overlapping_assignment_pairs(&groups), 2);
+    assert_eq!(get_covered_sections(&groups).len(), 10);
+    assert_eq!(
+        find_uncovered_sections(&groups)
+            .ranges()
+            .collect::<Vec<_>>(),
+        vec![10..=11]
+    );
+    assert_eq!(find_max_overlap_depth(&groups), 5);
+}
+
+#[test]
+fn invalid_section_test() {
+    assert_eq!(
+        parse_groups("1-9223372036854775808,2-3\n".as_bytes()),
+        Err(ParseError::new(
+            1,
+            3,
+            "9223372036854775808",
+            "section number"
+        ))
+    );
+    assert_eq!(
+        parse_groups("1--3,4-5\n".as_bytes()),
+        Err(ParseError::new(1, 3, "-3", "section number"))
+    );
+    assert_eq!(
+        parse_groups("1-9223372036854775807,2-3\n".as_bytes()).map(|v| v[0][0].clone()),
+        Ok(1..=i64::MAX)
+    );
 }
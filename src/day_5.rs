@@ -1,92 +1,483 @@
+use std::fmt::{Display, Formatter};
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!("{:?}", rearrange_crates(std::io::stdin().lock()));
+use serde_json::json;
+
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+use crate::trace::{Trace, Tracer};
+
+#[derive(Default)]
+pub struct Day5 {
+    pub capacity: Option<usize>,
+    pub trace: Trace,
 }
 
-fn rearrange_crates(buffer: impl BufRead) -> (String, String) {
-    let (mut stacks, commands) = parse_input(buffer);
-    let mut stacks_one_by_one = stacks.clone();
-    move_crates_by_one(&commands, &mut stacks_one_by_one);
-    move_crates_at_once(&commands, &mut stacks);
-    (get_top_crates(&stacks_one_by_one), get_top_crates(&stacks))
+pub type Crate = String;
+
+pub type Stacks = Vec<Vec<Crate>>;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    const PARAMETERS: &'static [&'static str] = &["capacity", "trace"];
+
+    type Puzzle = (Stacks, Vec<Command>);
+    type PartOne = Option<String>;
+    type PartTwo = Option<String>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_one(&self, (stacks, commands): &Self::Puzzle) -> Self::PartOne {
+        let mut stacks = stacks.clone();
+        run_crane(&CrateMover9000, commands, &mut stacks, self.trace.part(1)).ok()?;
+        Some(get_top_crates(&stacks))
+    }
+
+    fn part_two(&self, (stacks, commands): &Self::Puzzle) -> Self::PartTwo {
+        let mut stacks = stacks.clone();
+        let crane: &dyn Crane = match self.capacity {
+            Some(capacity) => &LimitedCrateMover { capacity },
+            None => &CrateMover9001,
+        };
+        run_crane(crane, commands, &mut stacks, self.trace.part(2)).ok()?;
+        Some(get_top_crates(&stacks))
+    }
+
+    fn validate(&self, (stacks, commands): &Self::Puzzle) -> Vec<String> {
+        find_problems(stacks, commands)
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "capacity" => match parse_parameter(name, value)? {
+                0 => return Err(format!("invalid value for --{}: {}", name, value)),
+                capacity => self.capacity = Some(capacity),
+            },
+            "trace" => self.trace = Trace::create(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
 }
 
-fn get_top_crates(stacks: &[Vec<u8>]) -> String {
-    String::from_utf8(stacks.iter().filter_map(|v| v.last()).copied().collect()).unwrap()
+pub fn rearrange_crates(buffer: impl BufRead) -> Result<(String, String), RearrangeError> {
+    let (mut stacks, commands) = parse_input(buffer)?;
+    let stacks_one_by_one = get_stacks_after(&CrateMover9000, &stacks, &commands, commands.len())?;
+    run_crane(&CrateMover9001, &commands, &mut stacks, Tracer::disabled())?;
+    Ok((get_top_crates(&stacks_one_by_one), get_top_crates(&stacks)))
 }
 
-fn move_crates_by_one(commands: &[Command], stacks: &mut [Vec<u8>]) {
-    for command in commands {
-        for _ in 0..command.amount {
-            let value = stacks[command.src - 1].pop().unwrap();
-            stacks[command.dst - 1].push(value);
+pub fn find_problems(stacks: &[Vec<Crate>], commands: &[Command]) -> Vec<String> {
+    let mut heights: Vec<usize> = stacks.iter().map(|v| v.len()).collect();
+    let mut problems = Vec::new();
+    for (index, command) in commands.iter().enumerate() {
+        if let Err(error) = check_command(index, command, &heights) {
+            problems.push(error.to_string());
+            if let Problem::NoStack(_) = error.problem {
+                continue;
+            }
         }
+        let moved = heights[command.src - 1].min(command.amount);
+        heights[command.src - 1] -= moved;
+        heights[command.dst - 1] += moved;
     }
+    problems
 }
 
-fn move_crates_at_once(commands: &[Command], stacks: &mut [Vec<u8>]) {
-    for command in commands {
-        let mut buffer = Vec::new();
-        for _ in 0..command.amount {
-            buffer.push(stacks[command.src - 1].pop().unwrap());
+pub fn check_command(
+    index: usize,
+    command: &Command,
+    heights: &[usize],
+) -> Result<(), CommandError> {
+    let error = |problem| CommandError {
+        index,
+        command: *command,
+        problem,
+    };
+    if let Some(stack) = [command.src, command.dst]
+        .into_iter()
+        .find(|v| *v == 0 || *v > heights.len())
+    {
+        return Err(error(Problem::NoStack(stack)));
+    }
+    let available = heights[command.src - 1];
+    if available < command.amount {
+        return Err(error(Problem::NotEnoughCrates {
+            stack: command.src,
+            available,
+        }));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CommandError {
+    pub index: usize,
+    pub command: Command,
+    pub problem: Problem,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Problem {
+    NoStack(usize),
+    NotEnoughCrates { stack: usize, available: usize },
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "command {}: move {} from {} to {}, ",
+            self.index + 1,
+            self.command.amount,
+            self.command.src,
+            self.command.dst
+        )?;
+        match self.problem {
+            Problem::NoStack(stack) => write!(f, "but there is no stack {}", stack),
+            Problem::NotEnoughCrates { stack, available } => write!(
+                f,
+                "but stack {} has {} crate{}",
+                stack,
+                available,
+                if available == 1 { "" } else { "s" }
+            ),
         }
-        while let Some(value) = buffer.pop() {
-            stacks[command.dst - 1].push(value);
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RearrangeError {
+    Parse(ParseError),
+    Command(CommandError),
+}
+
+impl Display for RearrangeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RearrangeError::Parse(error) => write!(f, "invalid input at {}", error),
+            RearrangeError::Command(error) => write!(f, "{}", error),
         }
     }
 }
 
-fn parse_input(buffer: impl BufRead) -> (Vec<Vec<u8>>, Vec<Command>) {
-    let mut stack_lines: Vec<String> = Vec::new();
-    let mut stacks = Vec::new();
-    let mut commands = Vec::new();
-    for line in buffer.lines().map(|v| v.unwrap()) {
-        if !stacks.is_empty() {
-            commands.push(parse_command(&line));
-        } else if line.is_empty() {
-            stacks = (0..stack_lines[stack_lines.len() - 1]
-                .split(' ')
-                .filter(|v| !v.is_empty())
-                .count())
-                .map(|_| Vec::new())
-                .collect();
-            for stack_line in stack_lines
-                .iter()
-                .rev()
-                .map(|v| v.bytes().collect::<Vec<u8>>())
-            {
-                for (i, stack) in stacks.iter_mut().enumerate() {
-                    let crate_index = (i + 1) * 4 - 3;
-                    if crate_index < stack_line.len() && stack_line[crate_index] != b' ' {
-                        stack.push(stack_line[crate_index]);
-                    }
-                }
-            }
-        } else {
-            stack_lines.push(line);
+impl std::error::Error for RearrangeError {}
+
+impl From<ParseError> for RearrangeError {
+    fn from(error: ParseError) -> Self {
+        RearrangeError::Parse(error)
+    }
+}
+
+impl From<CommandError> for RearrangeError {
+    fn from(error: CommandError) -> Self {
+        RearrangeError::Command(error)
+    }
+}
+
+pub fn get_top_crates(stacks: &[Vec<Crate>]) -> String {
+    stacks
+        .iter()
+        .filter_map(|v| v.last())
+        .map(|v| v.as_str())
+        .collect()
+}
+
+pub trait Crane {
+    fn move_crates(&self, amount: usize, src: &mut Vec<Crate>, dst: &mut Vec<Crate>);
+}
+
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn move_crates(&self, amount: usize, src: &mut Vec<Crate>, dst: &mut Vec<Crate>) {
+        dst.extend(src.drain(src.len() - amount..).rev());
+    }
+}
+
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn move_crates(&self, amount: usize, src: &mut Vec<Crate>, dst: &mut Vec<Crate>) {
+        dst.extend(src.drain(src.len() - amount..));
+    }
+}
+
+pub struct LimitedCrateMover {
+    pub capacity: usize,
+}
+
+impl Crane for LimitedCrateMover {
+    fn move_crates(&self, mut amount: usize, src: &mut Vec<Crate>, dst: &mut Vec<Crate>) {
+        while amount > 0 {
+            let batch = amount.min(self.capacity);
+            dst.extend(src.drain(src.len() - batch..));
+            amount -= batch;
         }
     }
-    (stacks, commands)
 }
 
-fn parse_command(value: &str) -> Command {
-    let after_move = value.split_once("move ").unwrap().1;
-    let (amount, after_from) = after_move.split_once(" from ").unwrap();
-    let (src, dst) = after_from.split_once(" to ").unwrap();
-    Command {
-        amount: usize::from_str(amount).unwrap(),
-        src: usize::from_str(src).unwrap(),
-        dst: usize::from_str(dst).unwrap(),
+pub fn run_crane(
+    crane: &dyn Crane,
+    commands: &[Command],
+    stacks: &mut [Vec<Crate>],
+    tracer: Tracer,
+) -> Result<(), CommandError> {
+    for (step, command) in commands.iter().enumerate() {
+        apply_command(crane, step, command, stacks)?;
+        trace_command(tracer, step, command, stacks);
     }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct Command {
-    amount: usize,
+pub fn get_stacks_after(
+    crane: &dyn Crane,
+    stacks: &[Vec<Crate>],
+    commands: &[Command],
+    count: usize,
+) -> Result<Stacks, CommandError> {
+    let mut rearrangement = Rearrangement::new(crane, stacks.to_vec(), commands);
+    rearrangement.seek(count)?;
+    Ok(rearrangement.stacks)
+}
+
+pub fn apply_command(
+    crane: &dyn Crane,
+    index: usize,
+    command: &Command,
+    stacks: &mut [Vec<Crate>],
+) -> Result<Change, CommandError> {
+    let heights: Vec<usize> = stacks.iter().map(|v| v.len()).collect();
+    check_command(index, command, &heights)?;
+    let mut src = std::mem::take(&mut stacks[command.src - 1]);
+    let removed = src[src.len() - command.amount..].to_vec();
+    let dst_len;
+    if command.src == command.dst {
+        let mut moved = Vec::new();
+        crane.move_crates(command.amount, &mut src, &mut moved);
+        dst_len = src.len();
+        src.extend(moved);
+    } else {
+        dst_len = stacks[command.dst - 1].len();
+        crane.move_crates(command.amount, &mut src, &mut stacks[command.dst - 1]);
+    }
+    stacks[command.src - 1] = src;
+    Ok(Change {
+        src: command.src - 1,
+        dst: command.dst - 1,
+        dst_len,
+        removed,
+    })
+}
+
+pub struct Change {
     src: usize,
     dst: usize,
+    dst_len: usize,
+    removed: Vec<Crate>,
+}
+
+impl Change {
+    pub fn revert(self, stacks: &mut [Vec<Crate>]) {
+        stacks[self.dst].truncate(self.dst_len);
+        stacks[self.src].extend(self.removed);
+    }
+}
+
+pub struct Rearrangement<'a> {
+    crane: &'a dyn Crane,
+    commands: &'a [Command],
+    stacks: Stacks,
+    history: Vec<Change>,
+}
+
+impl<'a> Rearrangement<'a> {
+    pub fn new(crane: &'a dyn Crane, stacks: Stacks, commands: &'a [Command]) -> Self {
+        Self {
+            crane,
+            commands,
+            stacks,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn stacks(&self) -> &[Vec<Crate>] {
+        &self.stacks
+    }
+
+    pub fn position(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn redo(&mut self) -> Result<bool, CommandError> {
+        let index = self.history.len();
+        let Some(command) = self.commands.get(index) else {
+            return Ok(false);
+        };
+        let change = apply_command(self.crane, index, command, &mut self.stacks)?;
+        self.history.push(change);
+        Ok(true)
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(change) => {
+                change.revert(&mut self.stacks);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn seek(&mut self, position: usize) -> Result<(), CommandError> {
+        while self.position() > position {
+            self.undo();
+        }
+        while self.position() < position.min(self.commands.len()) {
+            self.redo()?;
+        }
+        Ok(())
+    }
+}
+
+pub fn render_stacks(stacks: &[Vec<Crate>]) -> String {
+    let width = stacks
+        .iter()
+        .flatten()
+        .map(|v| v.len() + 2)
+        .chain([stacks.len().to_string().len(), 3])
+        .max()
+        .unwrap();
+    let height = stacks.iter().map(|v| v.len()).max().unwrap_or(0);
+    let mut result = String::new();
+    for level in (0..height).rev() {
+        let cells: Vec<String> = stacks
+            .iter()
+            .map(|v| match v.get(level) {
+                Some(value) => format!("{:<width$}", format!("[{}]", value)),
+                None => " ".repeat(width),
+            })
+            .collect();
+        result += cells.join(" ").trim_end();
+        result.push('\n');
+    }
+    let numbers: Vec<String> = (1..=stacks.len())
+        .map(|v| {
+            let number = v.to_string();
+            let padding = (width - number.len()) / 2;
+            format!("{:<width$}", format!("{}{}", " ".repeat(padding), number))
+        })
+        .collect();
+    result += numbers.join(" ").trim_end();
+    result.push('\n');
+    result
+}
+
+fn trace_command(tracer: Tracer, step: usize, command: &Command, stacks: &[Vec<Crate>]) {
+    tracer.event(step, || {
+        let dst = &stacks[command.dst - 1];
+        json!({
+            "event": "move",
+            "from": command.src,
+            "to": command.dst,
+            "crates": dst[dst.len() - command.amount..].concat(),
+            "src": stacks[command.src - 1].concat(),
+            "dst": dst.concat(),
+        })
+    });
+}
+
+pub fn parse_input(buffer: impl BufRead) -> Result<(Stacks, Vec<Command>), ParseError> {
+    let mut stack_lines: Vec<Line> = Vec::new();
+    let mut stacks = None;
+    let mut commands = Vec::new();
+    let mut last_line = Line::new(0, String::new());
+    for line in lines(buffer) {
+        let line = line?;
+        if stacks.is_some() {
+            commands.push(parse_command(&line)?);
+        } else if line.text.is_empty() {
+            let numbers_line = match stack_lines.pop() {
+                Some(v) => v,
+                None => return Err(line.error_at_end("stack numbers line")),
+            };
+            stacks = Some(parse_stacks(&stack_lines, &numbers_line)?);
+        } else {
+            stack_lines.push(line.clone());
+        }
+        last_line = line;
+    }
+    match stacks {
+        Some(stacks) => Ok((stacks, commands)),
+        None => Err(ParseError::new(
+            last_line.number + 1,
+            1,
+            "",
+            "empty line after stack numbers",
+        )),
+    }
+}
+
+fn parse_stacks(stack_lines: &[Line], numbers_line: &Line) -> Result<Stacks, ParseError> {
+    let mut numbers = Vec::new();
+    for (start, number) in find_tokens(&numbers_line.text) {
+        if numbers_line.parse::<usize>(number, "stack number")? != numbers.len() + 1 {
+            return Err(numbers_line.error(number, format!("stack number {}", numbers.len() + 1)));
+        }
+        numbers.push(start..start + number.len());
+    }
+    let mut stacks = vec![Vec::new(); numbers.len()];
+    for line in stack_lines.iter().rev() {
+        for (start, token) in find_tokens(&line.text) {
+            let label = token
+                .strip_prefix('[')
+                .and_then(|v| v.strip_suffix(']'))
+                .filter(|v| !v.is_empty() && v.bytes().all(|v| v.is_ascii_alphanumeric()))
+                .ok_or_else(|| line.error(token, "crate like [A] or empty space"))?;
+            let end = start + token.len();
+            let mut matching = numbers
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.start < end && start < v.end);
+            match (matching.next(), matching.next()) {
+                (Some((i, _)), None) => stacks[i].push(label.to_string()),
+                _ => return Err(line.error(token, "crate above a single stack number")),
+            }
+        }
+    }
+    Ok(stacks)
+}
+
+fn find_tokens(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(' ')
+        .scan(0, |start, token| {
+            let result = (*start, token);
+            *start += token.len() + 1;
+            Some(result)
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
+
+fn parse_command(line: &Line) -> Result<Command, ParseError> {
+    let after_move = line.strip_prefix(&line.text, "move ")?;
+    let (amount, after_from) = line.split_once(after_move, " from ")?;
+    let (src, dst) = line.split_once(after_from, " to ")?;
+    Ok(Command {
+        amount: line.parse(amount, "amount")?,
+        src: line.parse(src, "stack number")?,
+        dst: line.parse(dst, "stack number")?,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Command {
+    pub amount: usize,
+    pub src: usize,
+    pub dst: usize,
 }
 
 #[test]
@@ -104,6 +495,110 @@ move 1 from 1 to 2
     .as_bytes();
     assert_eq!(
         rearrange_crates(buffer),
-        ("CMZ".to_string(), "MCD".to_string())
+        Ok(("CMZ".to_string(), "MCD".to_string()))
+    );
+}
+
+#[test]
+fn invalid_command_test() {
+    let buffer = r#"[A]
+ 1
+
+move 1 from 1 to 1
+move one from 1 to 1
+"#
+    .as_bytes();
+    assert_eq!(
+        rearrange_crates(buffer),
+        Err(RearrangeError::Parse(ParseError::new(
+            5, 6, "one", "amount"
+        )))
+    );
+    assert_eq!(
+        rearrange_crates("[A]\n 1 \n\nmove 2 from 1 to 1\n".as_bytes())
+            .unwrap_err()
+            .to_string(),
+        "command 1: move 2 from 1 to 1, but stack 1 has 1 crate"
+    );
+    let solution = Day5::default();
+    let puzzle = solution
+        .parse("[A]\n 1 \n\nmove 2 from 1 to 1\nmove 1 from 2 to 1\n".as_bytes())
+        .unwrap();
+    assert_eq!(solution.part_one(&puzzle), None);
+    assert_eq!(
+        solution.validate(&puzzle),
+        vec![
+            "command 1: move 2 from 1 to 1, but stack 1 has 1 crate",
+            "command 2: move 1 from 2 to 1, but there is no stack 2",
+        ]
+    );
+}
+
+#[test]
+fn crane_test() {
+    let buffer = r#"[AB]      [CD]
+[E]  [F]  [G]  [H]  [I]  [J]  [K]  [L]  [M]  [N]
+ 1    2    3    4    5    6    7    8    9    10
+
+move 1 from 10 to 1
+move 3 from 1 to 3
+move 1 from 9 to 10
+"#;
+    let (mut stacks, commands) = parse_input(buffer.as_bytes()).unwrap();
+    assert_eq!(stacks.len(), 10);
+    assert_eq!(stacks[0], vec!["E", "AB"]);
+    assert_eq!(
+        render_stacks(&stacks),
+        buffer[..buffer.find("\n\n").unwrap() + 1]
+    );
+    run_crane(
+        &LimitedCrateMover { capacity: 2 },
+        &commands,
+        &mut stacks,
+        Tracer::disabled(),
+    )
+    .unwrap();
+    assert_eq!(get_top_crates(&stacks), "FEHIJKLM");
+    assert_eq!(
+        render_stacks(&stacks),
+        "          [E]\n          [N]\n          [AB]\n          [CD]\n     [F]  [G]  [H]  [I]  [J]  [K]  [L]       [M]\n 1    2    3    4    5    6    7    8    9    10\n"
+    );
+}
+
+#[test]
+fn rearrangement_test() {
+    let buffer = r#"    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 1
+move 3 from 2 to 1
+"#;
+    let (stacks, commands) = parse_input(buffer.as_bytes()).unwrap();
+    let mut rearrangement = Rearrangement::new(&CrateMover9000, stacks.clone(), &commands);
+    assert_eq!(rearrangement.seek(2), Ok(()));
+    assert_eq!(get_top_crates(rearrangement.stacks()), "CZ");
+    assert_eq!(
+        Ok(rearrangement.stacks().to_vec()),
+        get_stacks_after(&CrateMover9000, &stacks, &commands, 2)
+    );
+    assert_eq!(rearrangement.redo(), Ok(true));
+    assert_eq!(rearrangement.redo(), Ok(true));
+    let error = rearrangement.redo().unwrap_err();
+    assert_eq!(error.index, 4);
+    assert_eq!(
+        error.to_string(),
+        "command 5: move 3 from 2 to 1, but stack 2 has 0 crates"
     );
+    assert_eq!(rearrangement.position(), 4);
+    assert_eq!(get_top_crates(rearrangement.stacks()), "MZ");
+    assert!(rearrangement.undo());
+    assert_eq!(get_top_crates(rearrangement.stacks()), "MZ");
+    assert_eq!(rearrangement.seek(0), Ok(()));
+    assert_eq!(rearrangement.stacks(), &stacks[..]);
+    assert!(!rearrangement.undo());
 }
@@ -1,38 +1,175 @@
-use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::io::BufRead;
 
-fn main() {
-    println!("{:?}", find_starts(std::io::stdin().lock()));
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+
+    type Puzzle = Vec<u8>;
+    type PartOne = Option<usize>;
+    type PartTwo = Option<usize>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_datastream(input)
+    }
+
+    fn part_one(&self, datastream: &Self::Puzzle) -> Self::PartOne {
+        find_start_of_the_packet(datastream)
+    }
+
+    fn part_two(&self, datastream: &Self::Puzzle) -> Self::PartTwo {
+        find_start_of_the_message(datastream)
+    }
+
+    fn validate(&self, datastream: &Self::Puzzle) -> Vec<String> {
+        let mut problems = Vec::new();
+        if find_start_of_the_packet(datastream).is_none() {
+            problems.push(String::from("start-of-packet marker is missing"));
+        }
+        if find_start_of_the_message(datastream).is_none() {
+            problems.push(String::from("start-of-message marker is missing"));
+        }
+        problems
+    }
+}
+
+pub fn find_starts(input: impl BufRead) -> Result<(Option<usize>, Option<usize>), ParseError> {
+    let datastream = parse_datastream(input)?;
+    Ok((
+        find_start_of_the_packet(&datastream),
+        find_start_of_the_message(&datastream),
+    ))
 }
 
-fn find_starts(mut input: impl BufRead) -> (usize, usize) {
-    let mut buffer = Vec::new();
-    input.read_to_end(&mut buffer).unwrap();
-    (
-        find_start_of_the_packet(&buffer),
-        find_start_of_the_message(&buffer),
-    )
+pub fn parse_datastream(input: impl BufRead) -> Result<Vec<u8>, ParseError> {
+    let mut datastream = Vec::new();
+    scan_datastream(input, |value| datastream.push(value))?;
+    Ok(datastream)
 }
 
-fn find_start_of_the_packet(buffer: &[u8]) -> usize {
-    find_first_unique_sequence(buffer, 4)
+pub fn find_all_markers(input: impl BufRead, size: usize) -> Result<Vec<usize>, ParseError> {
+    let mut detector = MarkerDetector::new(size);
+    let mut markers = Vec::new();
+    scan_datastream(input, |value| {
+        if detector.push(value) {
+            markers.push(detector.position());
+        }
+    })?;
+    Ok(markers)
 }
 
-fn find_start_of_the_message(buffer: &[u8]) -> usize {
-    find_first_unique_sequence(buffer, 14)
+fn scan_datastream(mut input: impl BufRead, mut f: impl FnMut(u8)) -> Result<(), ParseError> {
+    let mut column = 1;
+    loop {
+        let buffer = input
+            .fill_buf()
+            .map_err(|e| ParseError::new(1, column, "", format!("readable input ({})", e)))?;
+        if buffer.is_empty() {
+            break;
+        }
+        let size = buffer.len();
+        let mut end = false;
+        for value in buffer.iter().copied() {
+            if value == b'\n' {
+                end = true;
+                break;
+            }
+            if !value.is_ascii_lowercase() {
+                let token = String::from_utf8_lossy(&[value]).to_string();
+                return Err(ParseError::new(1, column, &token, "lowercase letter"));
+            }
+            column += 1;
+            f(value);
+        }
+        input.consume(size);
+        if end {
+            break;
+        }
+    }
+    Ok(())
+}
+
+const PACKET_MARKER_SIZE: usize = 4;
+const MESSAGE_MARKER_SIZE: usize = 14;
+
+pub struct MarkerDetector {
+    size: usize,
+    window: VecDeque<u8>,
+    counts: [usize; 256],
+    duplicates: usize,
+    position: usize,
+    found: Option<usize>,
 }
 
-fn find_first_unique_sequence(buffer: &[u8], size: usize) -> usize {
-    for i in 0..buffer.len() - size {
-        if is_unique_sequence(&buffer[i..i + size]) {
-            return i + size;
+impl MarkerDetector {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            window: VecDeque::with_capacity(size + 1),
+            counts: [0; 256],
+            duplicates: 0,
+            position: 0,
+            found: None,
         }
     }
-    unreachable!()
+
+    pub fn push(&mut self, value: u8) -> bool {
+        self.position += 1;
+        self.window.push_back(value);
+        self.counts[value as usize] += 1;
+        if self.counts[value as usize] > 1 {
+            self.duplicates += 1;
+        }
+        if self.window.len() > self.size {
+            let removed = self.window.pop_front().unwrap() as usize;
+            self.counts[removed] -= 1;
+            if self.counts[removed] > 0 {
+                self.duplicates -= 1;
+            }
+        }
+        let is_marker = self.size > 0 && self.window.len() == self.size && self.duplicates == 0;
+        if is_marker && self.found.is_none() {
+            self.found = Some(self.position);
+        }
+        is_marker
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn found(&self) -> Option<usize> {
+        self.found
+    }
 }
 
-fn is_unique_sequence(values: &[u8]) -> bool {
-    values.iter().copied().collect::<HashSet<u8>>().len() == values.len()
+pub fn find_start_of_the_packet(buffer: &[u8]) -> Option<usize> {
+    find_first_unique_sequence(buffer, PACKET_MARKER_SIZE)
+}
+
+pub fn find_start_of_the_message(buffer: &[u8]) -> Option<usize> {
+    find_first_unique_sequence(buffer, MESSAGE_MARKER_SIZE)
+}
+
+pub fn find_first_unique_sequence(buffer: &[u8], size: usize) -> Option<usize> {
+    let mut detector = MarkerDetector::new(size);
+    buffer.iter().position(|v| detector.push(*v))?;
+    detector.found()
+}
+
+pub fn find_unique_sequences(buffer: &[u8], size: usize) -> Vec<usize> {
+    let mut detector = MarkerDetector::new(size);
+    buffer
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| detector.push(**v))
+        .map(|(i, _)| i + 1)
+        .collect()
 }
 
 #[test]
@@ -40,7 +177,7 @@ fn example_test() {
     let buffer = r#"mjqjpqmgbljsphdztnvjfqwrcgsmlb
 "#
     .as_bytes();
-    assert_eq!(find_starts(buffer), (7, 19));
+    assert_eq!(find_starts(buffer), Ok((Some(7), Some(19))));
 }
 
 #[test]
@@ -48,7 +185,7 @@ fn example_test_1() {
     let buffer = r#"bvwbjplbgvbhsrlpgdmjqwftvncz
 "#
     .as_bytes();
-    assert_eq!(find_starts(buffer), (5, 23));
+    assert_eq!(find_starts(buffer), Ok((Some(5), Some(23))));
 }
 
 #[test]
@@ -56,7 +193,7 @@ fn example_test_2() {
     let buffer = r#"nppdvjthqldpwncqszvftbrmjlhg
 "#
     .as_bytes();
-    assert_eq!(find_starts(buffer), (6, 23));
+    assert_eq!(find_starts(buffer), Ok((Some(6), Some(23))));
 }
 
 #[test]
@@ -64,7 +201,7 @@ fn example_test_3() {
     let buffer = r#"nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg
 "#
     .as_bytes();
-    assert_eq!(find_starts(buffer), (10, 29));
+    assert_eq!(find_starts(buffer), Ok((Some(10), Some(29))));
 }
 
 #[test]
@@ -72,5 +209,47 @@ fn example_test_4() {
     let buffer = r#"zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw
 "#
     .as_bytes();
-    assert_eq!(find_starts(buffer), (11, 26));
+    assert_eq!(find_starts(buffer), Ok((Some(11), Some(26))));
+}
+
+#[test]
+fn chunked_input_test() {
+    use crate::parse::ChunkedReader;
+
+    let buffer = b"mjqjpqmgbljsphdztnvjfqwrcgsmlb\n";
+    assert_eq!(
+        find_starts(ChunkedReader::new(buffer, 1)),
+        Ok((Some(7), Some(19)))
+    );
+    assert_eq!(
+        find_starts(ChunkedReader::new(b"abcd\nabc", 2)),
+        Ok((Some(4), None))
+    );
+    assert_eq!(
+        parse_datastream(ChunkedReader::new(b"ab1", 2)),
+        Err(ParseError::new(1, 3, "1", "lowercase letter"))
+    );
+    assert_eq!(
+        Day6.validate(&parse_datastream(&b"aabb\n"[..]).unwrap()),
+        vec![
+            String::from("start-of-packet marker is missing"),
+            String::from("start-of-message marker is missing"),
+        ]
+    );
+}
+
+#[test]
+fn all_markers_test() {
+    use crate::parse::ChunkedReader;
+
+    let buffer = b"abcabdd\n";
+    assert_eq!(find_unique_sequences(buffer, 3), vec![3, 4, 5, 6]);
+    assert_eq!(
+        find_all_markers(ChunkedReader::new(buffer, 2), 3),
+        Ok(vec![3, 4, 5, 6])
+    );
+    assert_eq!(find_unique_sequences(b"abc", 3), vec![3]);
+    assert_eq!(find_first_unique_sequence(b"ab", 3), None);
+    assert_eq!(find_first_unique_sequence(b"aab", 0), None);
+    assert_eq!(find_all_markers(&b"zz\n"[..], 1), Ok(vec![1, 2]));
 }
@@ -1,27 +1,105 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!(
-        "{:?}",
-        get_total_size_of_some_directories(std::io::stdin().lock())
-    );
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{parse_parameter, unknown_parameter, Solution};
+
+pub struct Day7 {
+    pub disk_size: u64,
+    pub required_space: u64,
 }
 
-fn get_total_size_of_some_directories(input: impl BufRead) -> (u64, u64) {
-    let mut file_system = parse_file_system(input);
-    update_directory_size(0, &mut file_system);
-    (
-        got_total_size_of_directories_with_at_most(100000, &file_system.directories),
+impl Default for Day7 {
+    fn default() -> Self {
+        Self {
+            disk_size: 70000000,
+            required_space: 30000000,
+        }
+    }
+}
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    const PARAMETERS: &'static [&'static str] = &["disk-size", "required-space"];
+
+    type Puzzle = FileSystem;
+    type PartOne = Option<u64>;
+    type PartTwo = Option<u64>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        let mut file_system = parse_file_system(input)?;
+        update_directory_size(0, &mut file_system);
+        Ok(file_system)
+    }
+
+    fn part_one(&self, file_system: &Self::Puzzle) -> Self::PartOne {
+        if !file_system.invalid_moves.is_empty() {
+            return None;
+        }
+        Some(got_total_size_of_directories_with_at_most(
+            100000,
+            &file_system.directories,
+        ))
+    }
+
+    fn part_two(&self, file_system: &Self::Puzzle) -> Self::PartTwo {
+        if !file_system.invalid_moves.is_empty() {
+            return None;
+        }
         get_smallest_directory_size_with_at_least(
-            30000000 - (70000000 - file_system.directories[0].size),
+            self.required_space.saturating_sub(
+                self.disk_size
+                    .saturating_sub(file_system.directories[0].size),
+            ),
             &file_system.directories,
-        ),
-    )
+        )
+    }
+
+    fn validate(&self, file_system: &Self::Puzzle) -> Vec<String> {
+        let mut problems: Vec<String> = file_system
+            .invalid_moves
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        let used_space = file_system.directories[0].size;
+        if used_space > self.disk_size {
+            problems.push(format!(
+                "used space {} exceeds disk size {}",
+                used_space, self.disk_size
+            ));
+        }
+        if self.required_space > self.disk_size {
+            problems.push(format!(
+                "required space {} exceeds disk size {}",
+                self.required_space, self.disk_size
+            ));
+        }
+        problems
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "disk-size" => self.disk_size = parse_parameter(name, value)?,
+            "required-space" => self.required_space = parse_parameter(name, value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+}
+
+pub fn get_total_size_of_some_directories(
+    input: impl BufRead,
+) -> Result<(Option<u64>, Option<u64>), ParseError> {
+    let solution = Day7::default();
+    let file_system = solution.parse(input)?;
+    Ok((
+        solution.part_one(&file_system),
+        solution.part_two(&file_system),
+    ))
 }
 
-fn got_total_size_of_directories_with_at_most(size: u64, directories: &[Directory]) -> u64 {
+pub fn got_total_size_of_directories_with_at_most(size: u64, directories: &[Directory]) -> u64 {
     directories
         .iter()
         .filter(|v| v.size <= size)
@@ -29,41 +107,46 @@ fn got_total_size_of_directories_with_at_most(size: u64, directories: &[Director
         .sum()
 }
 
-fn get_smallest_directory_size_with_at_least(size: u64, directories: &[Directory]) -> u64 {
+pub fn get_smallest_directory_size_with_at_least(
+    size: u64,
+    directories: &[Directory],
+) -> Option<u64> {
     directories
         .iter()
         .filter(|v| v.size >= size)
-        .min_by_key(|v| v.size)
-        .unwrap()
-        .size
+        .map(|v| v.size)
+        .min()
 }
 
-fn parse_file_system(input: impl BufRead) -> FileSystem {
+pub fn parse_file_system(input: impl BufRead) -> Result<FileSystem, ParseError> {
     let mut file_system = FileSystem {
         directories: vec![Directory::default()],
         ..Default::default()
     };
-    let mut current_directory = usize::MAX;
+    let mut current_directory = 0;
     let mut last_command = None;
-    for line in input.lines().map(|v| v.unwrap()) {
-        if let Some(value) = line.strip_prefix("$ ") {
-            let command = parse_command(value);
+    for line in lines(input) {
+        let line = line?;
+        if let Some(value) = line.text.strip_prefix("$ ") {
+            let command = parse_command(&line, value)?;
             if let Command::ChangeDirectory(v) = &command {
-                match v.as_str() {
-                    "/" => current_directory = 0,
-                    ".." => {
-                        current_directory =
-                            file_system.directories[current_directory].parent.unwrap()
-                    }
-                    name => {
-                        current_directory =
-                            file_system.directories[current_directory].directories[name]
-                    }
+                let directory = &file_system.directories[current_directory];
+                let target = match v.as_str() {
+                    "/" => Some(0),
+                    ".." => directory.parent,
+                    _ => directory.directories.get(v).copied(),
+                };
+                match target {
+                    Some(index) => current_directory = index,
+                    None => file_system.invalid_moves.push(InvalidMove {
+                        line: line.number,
+                        directory: v.clone(),
+                    }),
                 }
             }
             last_command = Some(command);
         } else if matches!(last_command, Some(Command::ListDirectory)) {
-            match parse_ls_output(&line) {
+            match parse_ls_output(&line)? {
                 Output::Directory(mut v) => {
                     let directory_index = file_system.directories.len();
                     file_system.directories[current_directory]
@@ -79,12 +162,14 @@ fn parse_file_system(input: impl BufRead) -> FileSystem {
                     file_system.files.push(v);
                 }
             }
+        } else {
+            return Err(line.error(&line.text, "command"));
         }
     }
-    file_system
+    Ok(file_system)
 }
 
-fn update_directory_size(directory_index: usize, file_system: &mut FileSystem) {
+pub fn update_directory_size(directory_index: usize, file_system: &mut FileSystem) {
     let mut directories = vec![directory_index];
     let mut to_visit = Vec::new();
     while let Some(directory_index) = directories.pop() {
@@ -112,28 +197,28 @@ fn update_directory_size(directory_index: usize, file_system: &mut FileSystem) {
     }
 }
 
-fn parse_command(value: &str) -> Command {
+fn parse_command(line: &Line, value: &str) -> Result<Command, ParseError> {
     if let Some(suffix) = value.strip_prefix("cd ") {
-        return Command::ChangeDirectory(suffix.to_string());
+        return Ok(Command::ChangeDirectory(suffix.to_string()));
     }
     if value == "ls" {
-        return Command::ListDirectory;
+        return Ok(Command::ListDirectory);
     }
-    unreachable!();
+    Err(line.error(value, "cd or ls"))
 }
 
-fn parse_ls_output(value: &str) -> Output {
-    if let Some(suffix) = value.strip_prefix("dir ") {
-        return Output::Directory(Directory {
+fn parse_ls_output(line: &Line) -> Result<Output, ParseError> {
+    if let Some(suffix) = line.text.strip_prefix("dir ") {
+        return Ok(Output::Directory(Directory {
             name: suffix.to_string(),
             ..Default::default()
-        });
+        }));
     }
-    let (size, name) = value.split_once(' ').unwrap();
-    Output::File(File {
+    let (size, name) = line.split_once(&line.text, " ")?;
+    Ok(Output::File(File {
         name: name.to_string(),
-        size: u64::from_str(size).unwrap(),
-    })
+        size: line.parse(size, "file size")?,
+    }))
 }
 
 #[derive(Debug)]
@@ -149,19 +234,37 @@ enum Output {
 }
 
 #[derive(Default, Debug)]
-struct FileSystem {
+pub struct FileSystem {
     directories: Vec<Directory>,
     files: Vec<File>,
+    invalid_moves: Vec<InvalidMove>,
 }
 
 #[derive(Debug)]
-struct File {
+pub struct InvalidMove {
+    line: usize,
+    directory: String,
+}
+
+impl Display for InvalidMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: cd {}, but ", self.line, self.directory)?;
+        if self.directory == ".." {
+            write!(f, "the current directory has no parent")
+        } else {
+            write!(f, "the current directory has no such subdirectory")
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct File {
     name: String,
     size: u64,
 }
 
 #[derive(Default, Debug)]
-struct Directory {
+pub struct Directory {
     name: String,
     size: u64,
     parent: Option<DirectoryIndex>,
@@ -201,6 +304,38 @@ $ ls
     .as_bytes();
     assert_eq!(
         get_total_size_of_some_directories(buffer),
-        (95437, 24933642)
+        Ok((Some(95437), Some(24933642)))
+    );
+    let solution = Day7 {
+        required_space: 80000000,
+        ..Default::default()
+    };
+    let file_system = solution.parse("$ cd /\n$ ls\n100 a\n".as_bytes()).unwrap();
+    assert_eq!(solution.part_two(&file_system), None);
+}
+
+#[test]
+fn unknown_directory_test() {
+    let buffer = r#"$ cd /
+$ ls
+dir a
+$ cd b
+$ cd a
+$ cd ..
+$ cd ..
+$ ls
+100 c
+"#
+    .as_bytes();
+    let solution = Day7::default();
+    let file_system = solution.parse(buffer).unwrap();
+    assert_eq!(
+        solution.validate(&file_system),
+        vec![
+            String::from("line 4: cd b, but the current directory has no such subdirectory"),
+            String::from("line 7: cd .., but the current directory has no parent"),
+        ]
     );
+    assert_eq!(solution.part_one(&file_system), None);
+    assert_eq!(solution.part_two(&file_system), None);
 }
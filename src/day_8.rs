@@ -1,129 +1,136 @@
 use std::io::BufRead;
 
-fn main() {
-    println!("{:?}", compute_result(std::io::stdin().lock()));
+use crate::grid::{parse_grid, Grid};
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
+#[derive(Default)]
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+
+    type Puzzle = Grid<u8>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_tree_map(input)
+    }
+
+    fn part_one(&self, tree_map: &Self::Puzzle) -> Self::PartOne {
+        count_visible_trees(tree_map)
+    }
+
+    fn part_two(&self, tree_map: &Self::Puzzle) -> Self::PartTwo {
+        find_highest_scenic_score(tree_map)
+    }
 }
 
-fn compute_result(input: impl BufRead) -> (usize, usize) {
-    let (tree_map, width) = parse_tree_map(input);
-    (
-        count_visible_trees(&tree_map, width),
-        find_highest_scenic_score(&tree_map, width),
-    )
+pub fn compute_result(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let tree_map = parse_tree_map(input)?;
+    Ok((
+        count_visible_trees(&tree_map),
+        find_highest_scenic_score(&tree_map),
+    ))
 }
 
-fn find_highest_scenic_score(tree_map: &[u8], width: usize) -> usize {
-    let height = tree_map.len() / width;
+pub fn find_highest_scenic_score(tree_map: &Grid<u8>) -> usize {
     let mut max_scenic_score = 0;
-    for i in 0..width {
-        for j in 0..height {
-            max_scenic_score = compute_scenic_score(i, j, tree_map, width).max(max_scenic_score);
+    for i in 0..tree_map.width() {
+        for j in 0..tree_map.height() {
+            max_scenic_score = compute_scenic_score(i, j, tree_map).max(max_scenic_score);
         }
     }
     max_scenic_score
 }
 
-fn compute_scenic_score(x: usize, y: usize, tree_map: &[u8], width: usize) -> usize {
-    let height = tree_map.len() / width;
-    let max = tree_map[get_index(x, y, width)];
+pub fn compute_scenic_score(x: usize, y: usize, tree_map: &Grid<u8>) -> usize {
+    let max = tree_map[(x, y)];
     let mut up = 0;
     for j in (0..y).rev() {
         up += 1;
-        if tree_map[get_index(x, j, width)] >= max {
+        if tree_map[(x, j)] >= max {
             break;
         }
     }
     let mut down = 0;
-    for j in y + 1..height {
+    for j in y + 1..tree_map.height() {
         down += 1;
-        if tree_map[get_index(x, j, width)] >= max {
+        if tree_map[(x, j)] >= max {
             break;
         }
     }
     let mut left = 0;
     for i in (0..x).rev() {
         left += 1;
-        if tree_map[get_index(i, y, width)] >= max {
+        if tree_map[(i, y)] >= max {
             break;
         }
     }
     let mut right = 0;
-    for i in x + 1..width {
+    for i in x + 1..tree_map.width() {
         right += 1;
-        if tree_map[get_index(i, y, width)] >= max {
+        if tree_map[(i, y)] >= max {
             break;
         }
     }
     up * down * right * left
 }
 
-fn count_visible_trees(tree_map: &[u8], width: usize) -> usize {
-    let height = tree_map.len() / width;
-    let mut visibility: Vec<bool> = std::iter::repeat(false).take(tree_map.len()).collect();
+pub fn count_visible_trees(tree_map: &Grid<u8>) -> usize {
+    let width = tree_map.width();
+    let height = tree_map.height();
+    let mut visibility = Grid::new(width, height, false);
     for i in 0..width {
-        visibility[get_index(i, 0, width)] = true;
-        visibility[get_index(i, height - 1, width)] = true;
+        visibility[(i, 0)] = true;
+        visibility[(i, height - 1)] = true;
     }
     for j in 0..height {
-        visibility[get_index(0, j, width)] = true;
-        visibility[get_index(width - 1, j, width)] = true;
+        visibility[(0, j)] = true;
+        visibility[(width - 1, j)] = true;
     }
     let columns = 1..width - 1;
     let rows = 1..height - 1;
     for i in columns.clone() {
-        let mut max = tree_map[get_index(i, 0, width)];
+        let mut max = tree_map[(i, 0)];
         for j in rows.clone() {
-            let index = get_index(i, j, width);
-            if max < tree_map[index] {
-                visibility[index] = true;
-                max = tree_map[index];
+            if max < tree_map[(i, j)] {
+                visibility[(i, j)] = true;
+                max = tree_map[(i, j)];
             }
         }
-        max = tree_map[get_index(i, height - 1, width)];
+        max = tree_map[(i, height - 1)];
         for j in rows.clone().rev() {
-            let index = get_index(i, j, width);
-            if max < tree_map[index] {
-                visibility[index] = true;
-                max = tree_map[index];
+            if max < tree_map[(i, j)] {
+                visibility[(i, j)] = true;
+                max = tree_map[(i, j)];
             }
         }
     }
     for j in rows {
-        let mut max = tree_map[get_index(0, j, width)];
+        let mut max = tree_map[(0, j)];
         for i in columns.clone() {
-            let index = get_index(i, j, width);
-            if max < tree_map[index] {
-                visibility[index] = true;
-                max = tree_map[index];
+            if max < tree_map[(i, j)] {
+                visibility[(i, j)] = true;
+                max = tree_map[(i, j)];
             }
         }
-        max = tree_map[get_index(width - 1, j, width)];
+        max = tree_map[(width - 1, j)];
         for i in columns.clone().rev() {
-            let index = get_index(i, j, width);
-            if max < tree_map[index] {
-                visibility[index] = true;
-                max = tree_map[index];
+            if max < tree_map[(i, j)] {
+                visibility[(i, j)] = true;
+                max = tree_map[(i, j)];
             }
         }
     }
-    visibility.iter().filter(|v| **v).count()
-}
-
-fn parse_tree_map(input: impl BufRead) -> (Vec<u8>, usize) {
-    let mut width = 0;
-    let mut values = Vec::new();
-    for (i, symbol) in input.bytes().map(|v| v.unwrap()).enumerate() {
-        if symbol != b'\n' {
-            values.push(symbol);
-        } else if width == 0 {
-            width = i;
-        }
-    }
-    (values, width)
+    visibility.values().iter().filter(|v| **v).count()
 }
 
-fn get_index(i: usize, j: usize, width: usize) -> usize {
-    i + j * width
+pub fn parse_tree_map(input: impl BufRead) -> Result<Grid<u8>, ParseError> {
+    parse_grid(input, "tree height digit", |v, _| {
+        v.to_digit(10).map(|v| v as u8)
+    })
 }
 
 #[test]
@@ -135,5 +142,5 @@ fn example_test() {
 35390
 "#
     .as_bytes();
-    assert_eq!(compute_result(buffer), (21, 8));
+    assert_eq!(compute_result(buffer), Ok((21, 8)));
 }
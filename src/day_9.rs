@@ -1,27 +1,62 @@
 use std::collections::HashSet;
 use std::io::BufRead;
-use std::str::FromStr;
 
-fn main() {
-    println!(
-        "{:?}",
-        count_visited_positions_by_tails(std::io::stdin().lock())
-    );
+use serde_json::json;
+
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{unknown_parameter, Solution};
+use crate::trace::{Trace, Tracer};
+
+#[derive(Default)]
+pub struct Day9 {
+    pub trace: Trace,
+}
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    const PARAMETERS: &'static [&'static str] = &["trace"];
+
+    type Puzzle = Vec<Movement>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError> {
+        parse_movements(input)
+    }
+
+    fn part_one(&self, movements: &Self::Puzzle) -> Self::PartOne {
+        count_visited_positions_by_tail(movements, 2, self.trace.part(1))
+    }
+
+    fn part_two(&self, movements: &Self::Puzzle) -> Self::PartTwo {
+        count_visited_positions_by_tail(movements, 10, self.trace.part(2))
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "trace" => self.trace = Trace::create(value)?,
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
 }
 
-fn count_visited_positions_by_tails(input: impl BufRead) -> (usize, usize) {
-    let movements = parse_movements(input);
-    (
-        count_visited_positions_by_tail(&movements, 2),
-        count_visited_positions_by_tail(&movements, 10),
-    )
+pub fn count_visited_positions_by_tails(input: impl BufRead) -> Result<(usize, usize), ParseError> {
+    let movements = parse_movements(input)?;
+    Ok((
+        count_visited_positions_by_tail(&movements, 2, Tracer::disabled()),
+        count_visited_positions_by_tail(&movements, 10, Tracer::disabled()),
+    ))
 }
 
-fn count_visited_positions_by_tail(movements: &[Movement], size: usize) -> usize {
-    let mut knots = std::iter::repeat((0i64, 0i64))
-        .take(size)
-        .collect::<Vec<_>>();
+pub fn count_visited_positions_by_tail(
+    movements: &[Movement],
+    size: usize,
+    tracer: Tracer,
+) -> usize {
+    let mut knots = std::iter::repeat_n((0i64, 0i64), size).collect::<Vec<_>>();
     let mut visited = HashSet::new();
+    let mut step = 0;
     for movement in movements.iter() {
         for _ in 0..movement.length {
             match movement.direction {
@@ -30,16 +65,33 @@ fn count_visited_positions_by_tail(movements: &[Movement], size: usize) -> usize
                 Direction::Right => knots[0].0 += 1,
                 Direction::Left => knots[0].0 -= 1,
             }
-            for i in 1..knots.len() {
-                knots[i] = adjust_position(&knots[i - 1], &knots[i]);
+            let mut moved = Vec::new();
+            for i in 0..knots.len() {
+                let position = if i == 0 {
+                    knots[0]
+                } else {
+                    adjust_position(&knots[i - 1], &knots[i])
+                };
+                if tracer.is_enabled() && (i == 0 || position != knots[i]) {
+                    moved.push((i, position));
+                }
+                knots[i] = position;
             }
             visited.insert(knots[knots.len() - 1]);
+            tracer.event(step, || {
+                json!({
+                    "event": "move",
+                    "moved": moved.iter().map(|(i, (x, y))| [*i as i64, *x, *y]).collect::<Vec<_>>(),
+                    "visited": visited.len(),
+                })
+            });
+            step += 1;
         }
     }
     visited.len()
 }
 
-fn adjust_position(head: &(i64, i64), tail: &(i64, i64)) -> (i64, i64) {
+pub fn adjust_position(head: &(i64, i64), tail: &(i64, i64)) -> (i64, i64) {
     let dx = head.0 - tail.0;
     let dy = head.1 - tail.1;
     if dx.abs() > 1 || dy.abs() > 1 {
@@ -49,30 +101,30 @@ fn adjust_position(head: &(i64, i64), tail: &(i64, i64)) -> (i64, i64) {
     }
 }
 
-fn parse_movements(input: impl BufRead) -> Vec<Movement> {
-    input.lines().map(|v| parse_movement(&v.unwrap())).collect()
+pub fn parse_movements(input: impl BufRead) -> Result<Vec<Movement>, ParseError> {
+    lines(input).map(|v| parse_movement(&v?)).collect()
 }
 
-fn parse_movement(value: &str) -> Movement {
-    let (direction, length) = value.split_once(' ').unwrap();
-    Movement {
+fn parse_movement(line: &Line) -> Result<Movement, ParseError> {
+    let (direction, length) = line.split_once(&line.text, " ")?;
+    Ok(Movement {
         direction: match direction {
             "U" => Direction::Up,
             "D" => Direction::Down,
             "R" => Direction::Right,
             "L" => Direction::Left,
-            _ => unreachable!(),
+            _ => return Err(line.error(direction, "U, D, R or L")),
         },
-        length: u64::from_str(length).unwrap(),
-    }
+        length: line.parse(length, "movement length")?,
+    })
 }
 
-struct Movement {
+pub struct Movement {
     direction: Direction,
     length: u64,
 }
 
-enum Direction {
+pub enum Direction {
     Up,
     Down,
     Right,
@@ -91,7 +143,7 @@ L 5
 R 2
 "#
     .as_bytes();
-    assert_eq!(count_visited_positions_by_tails(buffer), (13, 1));
+    assert_eq!(count_visited_positions_by_tails(buffer), Ok((13, 1)));
 }
 
 #[test]
@@ -106,5 +158,14 @@ L 25
 U 20
 "#
     .as_bytes();
-    assert_eq!(count_visited_positions_by_tails(buffer), (88, 36));
+    assert_eq!(count_visited_positions_by_tails(buffer), Ok((88, 36)));
+}
+
+#[test]
+fn invalid_direction_test() {
+    let buffer = "R 4\nX 2\n".as_bytes();
+    assert_eq!(
+        count_visited_positions_by_tails(buffer),
+        Err(ParseError::new(2, 1, "X", "U, D, R or L"))
+    );
 }
@@ -0,0 +1,878 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::day_15::Day15;
+use crate::day_22::{CubeLayout, CubeLayout0};
+use crate::day_5::render_stacks;
+
+const PRIMES: [u64; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+const ITEMS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const MAX_MONKEY_VALUE: i64 = 1_000_000_000_000;
+const MAX_HUMN_SCALE: i64 = 10_000;
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+
+    pub fn below(&mut self, count: usize) -> usize {
+        assert!(count > 0, "empty range");
+        (self.next_u64() % count as u64) as usize
+    }
+
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low <= high, "empty range {}..={}", low, high);
+        low + (self.next_u64() % (high.abs_diff(low) + 1)) as i64
+    }
+
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, values: &'a [T]) -> &'a T {
+        &values[self.below(values.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+pub fn default_size(day: u8) -> usize {
+    match day {
+        1 => 250,
+        2 => 2500,
+        3 => 300,
+        4 => 1000,
+        5 => 500,
+        6 => 4096,
+        7 => 180,
+        8 => 99,
+        9 => 2000,
+        10 => 240,
+        11 => 8,
+        12 => 41,
+        13 => 150,
+        14 => 150,
+        15 => 30,
+        16 => 60,
+        17 => 10091,
+        18 => 2800,
+        19 => 30,
+        20 => 5000,
+        21 => 2000,
+        22 => 50,
+        23 => 73,
+        24 => 120,
+        25 => 120,
+        _ => 0,
+    }
+}
+
+pub fn generate(day: u8, size: usize, rng: &mut Rng) -> Option<String> {
+    Some(match day {
+        1 => generate_calories(size, rng),
+        2 => generate_strategy_guide(size, rng),
+        3 => generate_rucksacks(size, rng),
+        4 => generate_section_pairs(size, rng),
+        5 => generate_rearrangement(size, rng),
+        6 => generate_datastream(size, rng),
+        7 => generate_terminal_output(size, rng),
+        8 => generate_digits(size, size, rng),
+        9 => generate_motions(size, rng),
+        10 => generate_program(size, rng),
+        11 => generate_monkeys(size, rng),
+        12 => generate_heightmap(size, rng),
+        13 => generate_packet_pairs(size, rng),
+        14 => generate_rock_paths(size, rng),
+        15 => generate_sensors(size, rng),
+        16 => generate_valves(size, rng),
+        17 => generate_jets(size, rng),
+        18 => generate_cubes(size, rng),
+        19 => generate_blueprints(size, rng),
+        20 => generate_numbers(size, rng),
+        21 => generate_monkey_math(size, rng),
+        22 => generate_monkey_map(size, rng),
+        23 => generate_elves(size, rng),
+        24 => generate_valley(size, rng),
+        25 => generate_snafu_numbers(size, rng),
+        _ => return None,
+    })
+}
+
+fn generate_calories(elves: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for i in 0..elves.max(1) {
+        if i > 0 {
+            result.push('\n');
+        }
+        for _ in 0..rng.range(1, 15) {
+            writeln!(result, "{}", rng.range(1000, 60000)).unwrap();
+        }
+    }
+    result
+}
+
+fn generate_strategy_guide(rounds: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..rounds.max(1) {
+        let opponent = rng.choose(&['A', 'B', 'C']);
+        let response = rng.choose(&['X', 'Y', 'Z']);
+        writeln!(result, "{} {}", opponent, response).unwrap();
+    }
+    result
+}
+
+fn generate_rucksacks(rucksacks: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..rucksacks.max(1).div_ceil(3) {
+        let mut items = ITEMS.to_vec();
+        rng.shuffle(&mut items);
+        let badge = items.pop().unwrap();
+        for pool in items.chunks(17) {
+            let size = rng.range(2, 16) as usize;
+            let mut first = vec![pool[0], badge];
+            let mut second = vec![pool[0]];
+            while first.len() < size {
+                first.push(*rng.choose(&pool[1..9]));
+            }
+            while second.len() < size {
+                second.push(*rng.choose(&pool[9..]));
+            }
+            rng.shuffle(&mut first);
+            rng.shuffle(&mut second);
+            if rng.chance(1, 2) {
+                std::mem::swap(&mut first, &mut second);
+            }
+            result.extend(first.iter().chain(second.iter()).map(|v| *v as char));
+            result.push('\n');
+        }
+    }
+    result
+}
+
+fn generate_section_pairs(pairs: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..pairs.max(1) {
+        let first = rng.range(1, 99);
+        let second = rng.range(1, 99);
+        let first_end = rng.range(first, 99);
+        let second_end = rng.range(second, 99);
+        writeln!(result, "{}-{},{}-{}", first, first_end, second, second_end).unwrap();
+    }
+    result
+}
+
+fn generate_rearrangement(moves: usize, rng: &mut Rng) -> String {
+    let mut stacks: Vec<Vec<u8>> = Vec::new();
+    for _ in 0..9 {
+        let height = rng.range(0, 8);
+        stacks.push((0..height).map(|_| *rng.choose(&ITEMS[26..])).collect());
+    }
+    if stacks.iter().all(|v| v.is_empty()) {
+        stacks[0].push(b'A');
+    }
+    let labels: Vec<Vec<String>> = stacks
+        .iter()
+        .map(|v| v.iter().map(|v| (*v as char).to_string()).collect())
+        .collect();
+    let mut result = render_stacks(&labels);
+    result.push('\n');
+    for _ in 0..moves {
+        let filled: Vec<usize> = (0..stacks.len())
+            .filter(|v| !stacks[*v].is_empty())
+            .collect();
+        let src = *rng.choose(&filled);
+        let dst = (src + 1 + rng.below(stacks.len() - 1)) % stacks.len();
+        let count = rng.range(1, stacks[src].len() as i64) as usize;
+        for _ in 0..count {
+            let value = stacks[src].pop().unwrap();
+            stacks[dst].push(value);
+        }
+        writeln!(result, "move {} from {} to {}", count, src + 1, dst + 1).unwrap();
+    }
+    result
+}
+
+fn generate_datastream(length: usize, rng: &mut Rng) -> String {
+    let length = length.max(14);
+    let mut buffer: Vec<u8> = (0..length).map(|_| b'a' + rng.below(13) as u8).collect();
+    let mut marker: Vec<u8> = (b'a'..=b'z').collect();
+    rng.shuffle(&mut marker);
+    let start = rng.below(length - 13);
+    buffer[start..start + 14].copy_from_slice(&marker[..14]);
+    buffer.push(b'\n');
+    String::from_utf8(buffer).unwrap()
+}
+
+fn generate_terminal_output(directories: usize, rng: &mut Rng) -> String {
+    let mut children = vec![Vec::new(); directories.max(1)];
+    for i in 1..children.len() {
+        children[rng.below(i)].push(i);
+    }
+    let mut result = String::from("$ cd /\n");
+    list_directory(0, &children, rng, &mut result);
+    result
+}
+
+fn list_directory(index: usize, children: &[Vec<usize>], rng: &mut Rng, result: &mut String) {
+    let mut names = HashSet::new();
+    let mut entries = Vec::new();
+    let mut directories = Vec::new();
+    for &child in children[index].iter() {
+        let name = generate_unique_name(rng, 1, 8, &mut names);
+        entries.push(format!("dir {}", name));
+        directories.push((child, name));
+    }
+    for _ in 0..rng.range(0, 4) {
+        let mut name = generate_unique_name(rng, 1, 8, &mut names);
+        if rng.chance(1, 2) {
+            name = format!("{}.{}", name, generate_name(rng, 3, 3));
+        }
+        entries.push(format!("{} {}", rng.range(1000, 300000), name));
+    }
+    rng.shuffle(&mut entries);
+    result.push_str("$ ls\n");
+    for entry in entries.iter() {
+        writeln!(result, "{}", entry).unwrap();
+    }
+    for (child, name) in directories.iter() {
+        writeln!(result, "$ cd {}", name).unwrap();
+        list_directory(*child, children, rng, result);
+        result.push_str("$ cd ..\n");
+    }
+}
+
+fn generate_name(rng: &mut Rng, min: usize, max: usize) -> String {
+    let length = rng.range(min as i64, max as i64);
+    (0..length)
+        .map(|_| *rng.choose(&ITEMS[..26]) as char)
+        .collect()
+}
+
+fn generate_unique_name(
+    rng: &mut Rng,
+    min: usize,
+    max: usize,
+    names: &mut HashSet<String>,
+) -> String {
+    loop {
+        let name = generate_name(rng, min, max);
+        if names.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+fn generate_digits(width: usize, height: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..height.max(1) {
+        result.extend((0..width.max(1)).map(|_| (b'0' + rng.below(10) as u8) as char));
+        result.push('\n');
+    }
+    result
+}
+
+fn generate_motions(motions: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..motions.max(1) {
+        let direction = rng.choose(&['U', 'D', 'L', 'R']);
+        writeln!(result, "{} {}", direction, rng.range(1, 20)).unwrap();
+    }
+    result
+}
+
+fn generate_program(cycles: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    let mut cycle = 0;
+    let mut x: i64 = 1;
+    while cycle < cycles.max(1) {
+        if cycle + 2 <= cycles && rng.chance(2, 3) {
+            let value = rng.range((-x).max(-10), (39 - x).min(10));
+            writeln!(result, "addx {}", value).unwrap();
+            x += value;
+            cycle += 2;
+        } else {
+            result.push_str("noop\n");
+            cycle += 1;
+        }
+    }
+    result
+}
+
+#[derive(Clone)]
+enum MonkeyOperation {
+    Add(u64),
+    Multiply(u64),
+    Square,
+}
+
+struct MonkeyDefinition {
+    items: Vec<u64>,
+    operation: MonkeyOperation,
+    divisor: u64,
+    targets: (usize, usize),
+}
+
+fn generate_monkeys(monkeys: usize, rng: &mut Rng) -> String {
+    let count = monkeys.clamp(2, PRIMES.len());
+    for attempt in 0.. {
+        let mut divisors = PRIMES.to_vec();
+        rng.shuffle(&mut divisors);
+        divisors.truncate(count);
+        let square = if attempt < 100 {
+            Some(rng.below(count))
+        } else {
+            None
+        };
+        let mut definitions = Vec::new();
+        for (i, &divisor) in divisors.iter().enumerate() {
+            let operation = if square == Some(i) {
+                MonkeyOperation::Square
+            } else if rng.chance(1, 4) {
+                MonkeyOperation::Multiply(rng.range(2, 19) as u64)
+            } else {
+                MonkeyOperation::Add(rng.range(1, 8) as u64)
+            };
+            let if_true = (i + 1 + rng.below(count - 1)) % count;
+            let mut if_false = (i + 1 + rng.below(count - 1)) % count;
+            while count > 2 && if_false == if_true {
+                if_false = (i + 1 + rng.below(count - 1)) % count;
+            }
+            definitions.push(MonkeyDefinition {
+                items: (0..rng.range(1, 8))
+                    .map(|_| rng.range(50, 99) as u64)
+                    .collect(),
+                operation,
+                divisor,
+                targets: (if_true, if_false),
+            });
+        }
+        if fits_worry_levels(&definitions, 20) {
+            return format_monkeys(&definitions);
+        }
+    }
+    unreachable!()
+}
+
+fn fits_worry_levels(definitions: &[MonkeyDefinition], rounds: usize) -> bool {
+    let mut items: Vec<Vec<u64>> = definitions.iter().map(|v| v.items.clone()).collect();
+    for _ in 0..rounds {
+        for (i, definition) in definitions.iter().enumerate() {
+            for item in std::mem::take(&mut items[i]) {
+                let value = match definition.operation {
+                    MonkeyOperation::Add(v) => item.checked_add(v),
+                    MonkeyOperation::Multiply(v) => item.checked_mul(v),
+                    MonkeyOperation::Square => item.checked_mul(item),
+                };
+                let Some(value) = value else {
+                    return false;
+                };
+                let value = value / 3;
+                if value.is_multiple_of(definition.divisor) {
+                    items[definition.targets.0].push(value);
+                } else {
+                    items[definition.targets.1].push(value);
+                }
+            }
+        }
+    }
+    true
+}
+
+fn format_monkeys(definitions: &[MonkeyDefinition]) -> String {
+    let mut result = String::new();
+    for (i, definition) in definitions.iter().enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        let items: Vec<String> = definition.items.iter().map(|v| v.to_string()).collect();
+        let operation = match definition.operation {
+            MonkeyOperation::Add(v) => format!("old + {}", v),
+            MonkeyOperation::Multiply(v) => format!("old * {}", v),
+            MonkeyOperation::Square => String::from("old * old"),
+        };
+        writeln!(result, "Monkey {}:", i).unwrap();
+        writeln!(result, "  Starting items: {}", items.join(", ")).unwrap();
+        writeln!(result, "  Operation: new = {}", operation).unwrap();
+        writeln!(result, "  Test: divisible by {}", definition.divisor).unwrap();
+        writeln!(
+            result,
+            "    If true: throw to monkey {}",
+            definition.targets.0
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "    If false: throw to monkey {}",
+            definition.targets.1
+        )
+        .unwrap();
+    }
+    result
+}
+
+fn generate_heightmap(rows: usize, rng: &mut Rng) -> String {
+    let height = rows.max(11);
+    let width = height * 4;
+    let distance =
+        |(x0, y0): (usize, usize), (x1, y1): (usize, usize)| x0.abs_diff(x1) + y0.abs_diff(y1);
+    let start = (rng.below(width), rng.below(height));
+    let end = [
+        (0, 0),
+        (width - 1, 0),
+        (0, height - 1),
+        (width - 1, height - 1),
+    ]
+    .into_iter()
+    .max_by_key(|v| distance(start, *v))
+    .unwrap();
+    let length = distance(start, end);
+    let level = |distance: usize| (distance * 25 / length).min(25);
+    let mut valleys = Vec::new();
+    for _ in 0..height / 2 {
+        let center = (rng.below(width), rng.below(height));
+        let offset = rng.below(26);
+        if offset + level(distance(center, end)) >= 25 {
+            valleys.push((center, offset));
+        }
+    }
+    let mut result = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            let position = (x, y);
+            let elevation = valleys
+                .iter()
+                .map(|(center, offset)| offset + level(distance(position, *center)))
+                .fold(level(distance(position, start)), usize::min);
+            result.push(if position == start {
+                'S'
+            } else if position == end {
+                'E'
+            } else {
+                (b'a' + elevation as u8) as char
+            });
+        }
+        result.push('\n');
+    }
+    result
+}
+
+fn generate_packet_pairs(pairs: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for i in 0..pairs.max(1) {
+        if i > 0 {
+            result.push('\n');
+        }
+        for _ in 0..2 {
+            generate_packet(0, rng, &mut result);
+            result.push('\n');
+        }
+    }
+    result
+}
+
+fn generate_packet(depth: usize, rng: &mut Rng, result: &mut String) {
+    result.push('[');
+    for i in 0..rng.range(0, 5) {
+        if i > 0 {
+            result.push(',');
+        }
+        if depth < 4 && rng.chance(1, 3) {
+            generate_packet(depth + 1, rng, result);
+        } else {
+            write!(result, "{}", rng.range(0, 10)).unwrap();
+        }
+    }
+    result.push(']');
+}
+
+fn generate_rock_paths(paths: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..paths.max(1) {
+        let mut x = rng.range(470, 530);
+        let mut y = rng.range(13, 170);
+        let mut points = vec![format!("{},{}", x, y)];
+        let horizontal = rng.chance(1, 2);
+        for i in 0..rng.range(1, 5) {
+            let length = rng.range(1, 8) * if rng.chance(1, 2) { 1 } else { -1 };
+            if (i % 2 == 0) == horizontal {
+                x += length;
+            } else if y + length < 1 {
+                y -= length;
+            } else {
+                y += length;
+            }
+            points.push(format!("{},{}", x, y));
+        }
+        writeln!(result, "{}", points.join(" -> ")).unwrap();
+    }
+    result
+}
+
+fn generate_sensors(sensors: usize, rng: &mut Rng) -> String {
+    let Day15 { min, max, .. } = Day15::default();
+    let distance = |(x0, y0): (i64, i64), (x1, y1): (i64, i64)| (x0 - x1).abs() + (y0 - y1).abs();
+    let hidden = (rng.range(min, max), rng.range(min, max));
+    let margin = (max - min) / 10;
+    let mut positions = Vec::new();
+    for (x, y) in [(min, min), (min, max), (max, min), (max, max)] {
+        let outward = |value: i64, rng: &mut Rng| {
+            if value == min {
+                min - rng.range(0, margin)
+            } else {
+                max + rng.range(0, margin)
+            }
+        };
+        let sensor = (outward(x, rng), outward(y, rng));
+        positions.push((sensor, distance(sensor, hidden) - 1));
+    }
+    while positions.len() < sensors + 4 {
+        let sensor = (
+            rng.range(min - margin, max + margin),
+            rng.range(min - margin, max + margin),
+        );
+        let limit = distance(sensor, hidden) - 1;
+        if limit > 0 {
+            positions.push((sensor, rng.range(limit / 2, limit)));
+        }
+    }
+    rng.shuffle(&mut positions);
+    let mut result = String::new();
+    for ((x, y), radius) in positions {
+        let dx = rng.range(-radius, radius);
+        let dy = (radius - dx.abs()) * if rng.chance(1, 2) { 1 } else { -1 };
+        writeln!(
+            result,
+            "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+            x,
+            y,
+            x + dx,
+            y + dy
+        )
+        .unwrap();
+    }
+    result
+}
+
+fn generate_valves(valves: usize, rng: &mut Rng) -> String {
+    let count = valves.clamp(2, 26 * 26);
+    let mut names: Vec<String> = ITEMS[26..]
+        .iter()
+        .flat_map(|a| {
+            ITEMS[26..]
+                .iter()
+                .map(move |b| format!("{}{}", *a as char, *b as char))
+        })
+        .filter(|v| v != "AA")
+        .collect();
+    rng.shuffle(&mut names);
+    names.truncate(count - 1);
+    names.insert(0, String::from("AA"));
+    let junctions = (count / 4).max(2);
+    let mut corridors = Vec::new();
+    for i in 1..junctions {
+        corridors.push((rng.below(i), i));
+    }
+    for _ in 0..junctions / 2 {
+        let (a, b) = (rng.below(junctions), rng.below(junctions));
+        if a != b && !corridors.contains(&(a, b)) && !corridors.contains(&(b, a)) {
+            corridors.push((a, b));
+        }
+    }
+    let mut lengths = vec![0; corridors.len()];
+    for _ in junctions..count {
+        lengths[rng.below(corridors.len())] += 1;
+    }
+    let mut tunnels = vec![Vec::new(); count];
+    let mut next = junctions;
+    for (&(a, b), &length) in corridors.iter().zip(lengths.iter()) {
+        let mut previous = a;
+        for valve in (next..next + length).chain([b]) {
+            tunnels[previous].push(valve);
+            tunnels[valve].push(previous);
+            previous = valve;
+        }
+        next += length;
+    }
+    let mut order: Vec<usize> = (0..count).collect();
+    rng.shuffle(&mut order);
+    let mut result = String::new();
+    for i in order {
+        let rate = if i > 0 && i < junctions {
+            rng.range(3, 25)
+        } else {
+            0
+        };
+        rng.shuffle(&mut tunnels[i]);
+        let destinations: Vec<&str> = tunnels[i].iter().map(|v| names[*v].as_str()).collect();
+        writeln!(
+            result,
+            "Valve {} has flow rate={}; {} {}",
+            names[i],
+            rate,
+            if destinations.len() == 1 {
+                "tunnel leads to valve"
+            } else {
+                "tunnels lead to valves"
+            },
+            destinations.join(", ")
+        )
+        .unwrap();
+    }
+    result
+}
+
+fn generate_jets(length: usize, rng: &mut Rng) -> String {
+    let mut result: String = (0..length.max(1))
+        .map(|_| *rng.choose(&['<', '>']))
+        .collect();
+    result.push('\n');
+    result
+}
+
+fn generate_cubes(cubes: usize, rng: &mut Rng) -> String {
+    let mut side = 20;
+    while side < 120 && side * side * side < cubes * 3 {
+        side += 1;
+    }
+    let count = cubes.clamp(1, side * side * side);
+    let mut positions = HashSet::new();
+    let mut result = String::new();
+    while positions.len() < count {
+        let cube = (
+            rng.range(1, side as i64),
+            rng.range(1, side as i64),
+            rng.range(1, side as i64),
+        );
+        if positions.insert(cube) {
+            writeln!(result, "{},{},{}", cube.0, cube.1, cube.2).unwrap();
+        }
+    }
+    result
+}
+
+fn generate_blueprints(blueprints: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for id in 1..=blueprints.max(1) {
+        writeln!(
+            result,
+            "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+             Each obsidian robot costs {} ore and {} clay. \
+             Each geode robot costs {} ore and {} obsidian.",
+            id,
+            rng.range(2, 4),
+            rng.range(2, 4),
+            rng.range(2, 4),
+            rng.range(5, 20),
+            rng.range(2, 4),
+            rng.range(5, 20)
+        )
+        .unwrap();
+    }
+    result
+}
+
+fn generate_numbers(count: usize, rng: &mut Rng) -> String {
+    let mut numbers: Vec<i64> = (1..count.max(1))
+        .map(|_| rng.range(1, 10000) * if rng.chance(1, 2) { 1 } else { -1 })
+        .collect();
+    numbers.insert(rng.below(numbers.len() + 1), 0);
+    let mut result = String::new();
+    for number in numbers {
+        writeln!(result, "{}", number).unwrap();
+    }
+    result
+}
+
+struct MonkeyMath {
+    names: HashSet<String>,
+    lines: Vec<String>,
+    humn: i64,
+    divisors: i64,
+    factors: i64,
+}
+
+impl MonkeyMath {
+    fn add(&mut self, rng: &mut Rng, value: i64, size: usize, humn: bool) -> String {
+        if size <= 2 {
+            if humn {
+                self.humn = value;
+                return String::from("humn");
+            }
+            let name = generate_unique_name(rng, 4, 4, &mut self.names);
+            self.lines.push(format!("{}: {}", name, value));
+            return name;
+        }
+        let name = generate_unique_name(rng, 4, 4, &mut self.names);
+        let humn_first = rng.chance(1, 2);
+        let factor = *rng.choose(&[2, 3, 4, 5, 6, 7, 8, 9]);
+        let (operation, first, second, humn_first) = match rng.below(4) {
+            0 if value >= 2 => {
+                let first = rng.range(1, value - 1);
+                ('+', first, value - first, humn_first)
+            }
+            1 if value % factor == 0 && (!humn || self.factors * factor <= MAX_HUMN_SCALE) => {
+                if humn {
+                    self.factors *= factor;
+                }
+                if humn_first {
+                    ('*', value / factor, factor, true)
+                } else {
+                    ('*', factor, value / factor, false)
+                }
+            }
+            2 if value * factor <= MAX_MONKEY_VALUE
+                && (!humn || self.divisors * factor <= MAX_HUMN_SCALE) =>
+            {
+                if humn {
+                    self.divisors *= factor;
+                }
+                ('/', value * factor, factor, true)
+            }
+            _ => {
+                let second = rng.range(1, 100);
+                ('-', value + second, second, humn_first)
+            }
+        };
+        let first_size = 1 + rng.below(size - 2);
+        let first = self.add(rng, first, first_size, humn && humn_first);
+        let second = self.add(rng, second, size - 1 - first_size, humn && !humn_first);
+        self.lines
+            .push(format!("{}: {} {} {}", name, first, operation, second));
+        name
+    }
+}
+
+fn generate_monkey_math(monkeys: usize, rng: &mut Rng) -> String {
+    let mut math = MonkeyMath {
+        names: HashSet::from([String::from("root"), String::from("humn")]),
+        lines: Vec::new(),
+        humn: 0,
+        divisors: 1,
+        factors: 1,
+    };
+    let size = monkeys.max(3) - 1;
+    let value = rng.range(1000, 1000000);
+    let with_humn = math.add(rng, value, size / 2, true);
+    let without_humn = math.add(rng, value, size - size / 2, false);
+    if rng.chance(1, 2) {
+        math.lines
+            .push(format!("root: {} + {}", with_humn, without_humn));
+    } else {
+        math.lines
+            .push(format!("root: {} + {}", without_humn, with_humn));
+    }
+    let humn = math.humn + rng.range(1, 5) * math.divisors;
+    math.lines.push(format!("humn: {}", humn));
+    rng.shuffle(&mut math.lines);
+    let mut result = math.lines.join("\n");
+    result.push('\n');
+    result
+}
+
+fn generate_monkey_map(face_size: usize, rng: &mut Rng) -> String {
+    let size = face_size.max(2);
+    let faces = CubeLayout0.faces();
+    let mut result = String::new();
+    for y in 0..size * 4 {
+        let mut line = String::new();
+        for x in 0..size * 3 {
+            line.push(if !faces.contains(&(x / size, y / size)) {
+                ' '
+            } else if (x, y) != (size, 0) && rng.chance(1, 10) {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        writeln!(result, "{}", line.trim_end()).unwrap();
+    }
+    result.push('\n');
+    for _ in 0..size * 40 {
+        write!(result, "{}{}", rng.range(1, 50), rng.choose(&['L', 'R'])).unwrap();
+    }
+    writeln!(result, "{}", rng.range(1, 50)).unwrap();
+    result
+}
+
+fn generate_elves(side: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..side.max(1) {
+        result.extend((0..side.max(1)).map(|_| if rng.chance(1, 2) { '#' } else { '.' }));
+        result.push('\n');
+    }
+    result
+}
+
+fn generate_valley(width: usize, rng: &mut Rng) -> String {
+    let width = width.clamp(2, u8::MAX as usize - 2);
+    let height = (width / 5).max(2);
+    let mut result = format!("#.{}\n", "#".repeat(width));
+    for _ in 0..height {
+        result.push('#');
+        for x in 0..width {
+            result.push(if rng.chance(1, 3) {
+                '.'
+            } else if x == 0 || x == width - 1 {
+                *rng.choose(&['<', '>'])
+            } else {
+                *rng.choose(&['<', '>', '^', 'v'])
+            });
+        }
+        result.push_str("#\n");
+    }
+    writeln!(result, "{}.#", "#".repeat(width)).unwrap();
+    result
+}
+
+fn generate_snafu_numbers(count: usize, rng: &mut Rng) -> String {
+    let mut result = String::new();
+    for _ in 0..count.max(1) {
+        result.push(*rng.choose(&['1', '2']));
+        for _ in 0..rng.range(0, 19) {
+            result.push(*rng.choose(&['=', '-', '0', '1', '2']));
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[test]
+fn generate_test() {
+    use crate::runner::{run_day, Part, DAYS};
+
+    for day in DAYS {
+        let size = match day {
+            10 | 17 => default_size(day),
+            12 | 16 | 19 | 22 => 3.max(default_size(day) / 10),
+            24 => default_size(day) / 3,
+            _ => default_size(day) / 10,
+        };
+        let input = generate(day, size, &mut Rng::new(u64::from(day))).unwrap();
+        let parts: &[Part] = match day {
+            15 | 19 => &[Part::One],
+            _ => &[Part::One, Part::Two],
+        };
+        if let Err(error) = run_day(day, &mut input.as_bytes(), parts) {
+            panic!("day {}: {}\n{}", day, error, input);
+        }
+    }
+    let mut rng = Rng::new(42);
+    assert_eq!(generate(4, 3, &mut rng), generate(4, 3, &mut Rng::new(42)));
+    assert_eq!(generate(26, 1, &mut rng), None);
+}
@@ -0,0 +1,235 @@
+use std::io::BufRead;
+use std::ops::{Index, IndexMut, Range};
+
+use crate::parse::{lines, ParseError};
+
+pub const NEIGHBORS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+pub const NEIGHBORS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    values: Vec<T>,
+    width: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            values: vec![value; width * height],
+            width,
+        }
+    }
+
+    pub fn from_values(width: usize, values: Vec<T>) -> Self {
+        assert!(
+            width > 0 && values.len().is_multiple_of(width),
+            "{} values do not make rows of {}",
+            values.len(),
+            width
+        );
+        Self { values, width }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.values.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        x + y * self.width
+    }
+
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        (0..self.width as isize).contains(&x) && (0..self.height() as isize).contains(&y)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width {
+            self.values.get(self.index(x, y))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width {
+            let index = self.index(x, y);
+            self.values.get_mut(index)
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.values[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.values[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self, range: Range<usize>) -> &[T] {
+        &self.values[range.start * self.width..range.end * self.width]
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(x < self.width, "column {} is out of {}", x, self.width);
+        self.values.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn push_row(&mut self, row: impl IntoIterator<Item = T>) {
+        let length = self.values.len();
+        self.values.extend(row);
+        assert_eq!(
+            self.values.len() - length,
+            self.width,
+            "row length does not match grid width"
+        );
+    }
+
+    pub fn offset(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let (x, y) = (x as isize + dx, y as isize + dy);
+        if self.contains(x, y) {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn neighbors4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBORS_4
+            .iter()
+            .filter_map(move |v| self.offset(x, y, *v))
+    }
+
+    pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBORS_8
+            .iter()
+            .filter_map(move |v| self.offset(x, y, *v))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            values: self.values.iter().map(f).collect(),
+            width: self.width,
+        }
+    }
+
+    pub fn render(&self, mut tile: impl FnMut(&T) -> char) -> String {
+        let mut result = String::with_capacity((self.width + 1) * self.height());
+        for y in 0..self.height() {
+            result.extend(self.row(y).iter().map(&mut tile));
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        assert!(x < self.width, "x {} is out of width {}", x, self.width);
+        &self.values[self.index(x, y)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        assert!(x < self.width, "x {} is out of width {}", x, self.width);
+        let index = self.index(x, y);
+        &mut self.values[index]
+    }
+}
+
+pub fn parse_grid<T, F>(
+    input: impl BufRead,
+    expected: &str,
+    mut tile: F,
+) -> Result<Grid<T>, ParseError>
+where
+    F: FnMut(char, (usize, usize)) -> Option<T>,
+{
+    let mut values = Vec::new();
+    let mut width = 0;
+    for (y, line) in lines(input).enumerate() {
+        let line = line?;
+        let length = line.text.chars().count();
+        if y == 0 {
+            width = length;
+        } else if length != width {
+            return Err(line.error(&line.text, format!("row of {} tiles", width)));
+        }
+        for (x, (i, v)) in line.text.char_indices().enumerate() {
+            match tile(v, (x, y)) {
+                Some(value) => values.push(value),
+                None => return Err(line.error(&line.text[i..i + v.len_utf8()], expected)),
+            }
+        }
+    }
+    if width == 0 {
+        return Err(ParseError::new(1, 1, "", "map"));
+    }
+    Ok(Grid::from_values(width, values))
+}
+
+#[test]
+fn parse_grid_test() {
+    let tile = |v, _| match v {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    };
+    let grid = parse_grid("#.\n.#\n".as_bytes(), ". or #", tile).unwrap();
+    assert_eq!((grid.width(), grid.height()), (2, 2));
+    assert_eq!(grid.render(|v| if *v { '#' } else { '.' }), "#.\n.#\n");
+    assert_eq!(
+        parse_grid("#.\n.x\n".as_bytes(), ". or #", tile),
+        Err(ParseError::new(2, 2, "x", ". or #"))
+    );
+    assert_eq!(
+        parse_grid("#.\n.\n".as_bytes(), ". or #", |v, _| Some(v)),
+        Err(ParseError::new(2, 1, ".", "row of 2 tiles"))
+    );
+}
+
+#[test]
+fn neighbors_test() {
+    let grid = Grid::from_values(3, (0..6).collect());
+    assert_eq!(
+        grid.neighbors4(0, 0).collect::<Vec<_>>(),
+        vec![(1, 0), (0, 1)]
+    );
+    assert_eq!(grid.neighbors8(1, 1).count(), 5);
+    assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![1, 4]);
+    assert_eq!(grid.row(1), &[3, 4, 5]);
+    assert_eq!(grid.get(3, 0), None);
+    assert_eq!(grid[(2, 1)], 5);
+}
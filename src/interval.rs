@@ -0,0 +1,173 @@
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct IntervalSet {
+    intervals: Vec<(i64, i64)>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_range(range: RangeInclusive<i64>) -> Self {
+        let mut result = Self::new();
+        result.insert(range);
+        result
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<i64>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        let first = self
+            .intervals
+            .partition_point(|v| v.1 < start.saturating_sub(1));
+        let last = self
+            .intervals
+            .partition_point(|v| v.0 <= end.saturating_add(1));
+        if first < last {
+            start = start.min(self.intervals[first].0);
+            end = end.max(self.intervals[last - 1].1);
+        }
+        self.intervals.splice(first..last, [(start, end)]);
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = self.clone();
+        for range in other.ranges() {
+            result.insert(range);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = IntervalSet::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+            if start <= end {
+                result.intervals.push((start, end));
+            }
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        result
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = IntervalSet::new();
+        let mut j = 0;
+        for &(start, end) in self.intervals.iter() {
+            while j < other.intervals.len() && other.intervals[j].1 < start {
+                j += 1;
+            }
+            let mut start = Some(start);
+            let mut k = j;
+            while let Some(value) = start.filter(|v| *v <= end) {
+                let Some(&(other_start, other_end)) = other.intervals.get(k).filter(|v| v.0 <= end)
+                else {
+                    break;
+                };
+                if other_start > value {
+                    result.intervals.push((value, other_start - 1));
+                }
+                start = other_end.checked_add(1).map(|v| v.max(value));
+                k += 1;
+            }
+            if let Some(value) = start.filter(|v| *v <= end) {
+                result.intervals.push((value, end));
+            }
+        }
+        result
+    }
+
+    pub fn gaps(&self, within: RangeInclusive<i64>) -> IntervalSet {
+        IntervalSet::from_range(within).difference(self)
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let index = self.intervals.partition_point(|v| v.1 < value);
+        index < self.intervals.len() && self.intervals[index].0 <= value
+    }
+
+    pub fn len(&self) -> u64 {
+        self.intervals
+            .iter()
+            .map(|(start, end)| end.abs_diff(*start) + 1)
+            .sum()
+    }
+
+    pub fn bounds(&self) -> Option<RangeInclusive<i64>> {
+        Some(self.intervals.first()?.0..=self.intervals.last()?.1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<i64>> + '_ {
+        self.intervals.iter().map(|(start, end)| *start..=*end)
+    }
+}
+
+impl FromIterator<RangeInclusive<i64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<i64>>>(iter: I) -> Self {
+        let mut result = IntervalSet::new();
+        for range in iter {
+            result.insert(range);
+        }
+        result
+    }
+}
+
+pub fn find_max_depth<'a>(ranges: impl IntoIterator<Item = &'a RangeInclusive<i64>>) -> usize {
+    let mut events: Vec<(i64, bool)> = Vec::new();
+    for range in ranges.into_iter().filter(|v| !v.is_empty()) {
+        events.push((*range.start(), true));
+        events.push((range.end().saturating_add(1), false));
+    }
+    events.sort_by_key(|(position, open)| (*position, *open));
+    let (mut depth, mut max_depth) = (0usize, 0);
+    for (_, open) in events {
+        if open {
+            depth += 1;
+            max_depth = max_depth.max(depth);
+        } else {
+            depth -= 1;
+        }
+    }
+    max_depth
+}
+
+#[test]
+fn interval_set_test() {
+    let a: IntervalSet = [1..=3, 10..=12, 5..=6, 4..=4].into_iter().collect();
+    assert_eq!(a.ranges().collect::<Vec<_>>(), vec![1..=6, 10..=12]);
+    assert_eq!(a.len(), 9);
+    let b: IntervalSet = [0..=1, 3..=10, 12..=20].into_iter().collect();
+    assert_eq!(a.union(&b).ranges().collect::<Vec<_>>(), vec![0..=20]);
+    assert_eq!(
+        a.intersection(&b).ranges().collect::<Vec<_>>(),
+        vec![1..=1, 3..=6, 10..=10, 12..=12]
+    );
+    assert_eq!(
+        a.difference(&b).ranges().collect::<Vec<_>>(),
+        vec![2..=2, 11..=11]
+    );
+    assert_eq!(
+        b.difference(&a).ranges().collect::<Vec<_>>(),
+        vec![0..=0, 7..=9, 13..=20]
+    );
+    assert_eq!(
+        a.gaps(0..=14).ranges().collect::<Vec<_>>(),
+        vec![0..=0, 7..=9, 13..=14]
+    );
+    assert!(a.contains(5) && !a.contains(8));
+    assert_eq!(find_max_depth(&[1..=3, 2..=5, 3..=3, 4..=6]), 3);
+}
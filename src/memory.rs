@@ -0,0 +1,78 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+pub struct TrackingAllocator;
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        sub(layout.size());
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                add(new_size - layout.size());
+            } else {
+                sub(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+fn add(size: usize) {
+    if ENABLED.load(Ordering::Relaxed) {
+        let allocated = ALLOCATED.fetch_add(size as isize, Ordering::Relaxed) + size as isize;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+    }
+}
+
+fn sub(size: usize) {
+    if ENABLED.load(Ordering::Relaxed) {
+        ALLOCATED.fetch_sub(size as isize, Ordering::Relaxed);
+    }
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn allocated() -> isize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+pub fn reset_peak() -> isize {
+    let allocated = allocated();
+    PEAK.store(allocated, Ordering::Relaxed);
+    allocated
+}
+
+pub fn peak() -> isize {
+    PEAK.load(Ordering::Relaxed)
+}
+
+pub fn usage_since(base: isize, value: isize) -> usize {
+    value.saturating_sub(base).max(0) as usize
+}
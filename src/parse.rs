@@ -0,0 +1,197 @@
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub expected: String,
+    pub end_of_input: bool,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, token: &str, expected: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            token: token.to_string(),
+            expected: expected.into(),
+            end_of_input: false,
+        }
+    }
+
+    pub fn end_of_input(line: usize, expected: impl Into<String>) -> Self {
+        Self {
+            end_of_input: true,
+            ..Self::new(line, 1, "", expected)
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, ",
+            self.line, self.column, self.expected
+        )?;
+        if self.end_of_input {
+            write!(f, "found end of input")
+        } else if self.token.is_empty() {
+            write!(f, "found end of line")
+        } else {
+            write!(f, "found {:?}", self.token)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub number: usize,
+    pub text: String,
+}
+
+impl Line {
+    pub fn new(number: usize, text: String) -> Self {
+        Self { number, text }
+    }
+
+    pub fn column(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let position = token.as_ptr() as usize;
+        if (start..=start + self.text.len()).contains(&position) {
+            self.text[..position - start].chars().count() + 1
+        } else {
+            1
+        }
+    }
+
+    pub fn error(&self, token: &str, expected: impl Into<String>) -> ParseError {
+        ParseError::new(self.number, self.column(token), token, expected)
+    }
+
+    pub fn error_at_end(&self, expected: impl Into<String>) -> ParseError {
+        self.error(&self.text[self.text.len()..], expected)
+    }
+
+    pub fn strip_prefix<'a>(&self, value: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+        value
+            .strip_prefix(prefix)
+            .ok_or_else(|| self.error(first_token(value), format!("{:?}", prefix.trim())))
+    }
+
+    pub fn split_once<'a>(
+        &self,
+        value: &'a str,
+        delimiter: &str,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        value
+            .split_once(delimiter)
+            .ok_or_else(|| self.error(value, format!("{:?}", delimiter)))
+    }
+
+    pub fn parse<T: FromStr>(&self, value: &str, expected: &str) -> Result<T, ParseError> {
+        T::from_str(value).map_err(|_| self.error(value, expected))
+    }
+}
+
+pub fn lines(input: impl BufRead) -> impl Iterator<Item = Result<Line, ParseError>> {
+    input.lines().enumerate().map(|(i, line)| match line {
+        Ok(text) => Ok(Line::new(i + 1, text)),
+        Err(e) => Err(ParseError::new(
+            i + 1,
+            1,
+            "",
+            format!("readable line ({})", e),
+        )),
+    })
+}
+
+pub fn read_to_end(mut input: impl BufRead) -> Result<Vec<u8>, ParseError> {
+    let mut buffer = Vec::new();
+    input
+        .read_to_end(&mut buffer)
+        .map_err(|e| ParseError::new(1, 1, "", format!("readable input ({})", e)))?;
+    Ok(buffer)
+}
+
+pub fn position(buffer: &[u8], index: usize) -> (usize, usize) {
+    let line = buffer[..index].iter().filter(|v| **v == b'\n').count() + 1;
+    let line_start = buffer[..index]
+        .iter()
+        .rposition(|v| *v == b'\n')
+        .map(|v| v + 1)
+        .unwrap_or(0);
+    (line, index - line_start + 1)
+}
+
+#[cfg(test)]
+pub struct ChunkedReader<'a> {
+    data: &'a [u8],
+    chunk: usize,
+}
+
+#[cfg(test)]
+impl<'a> ChunkedReader<'a> {
+    pub fn new(data: &'a [u8], chunk: usize) -> Self {
+        Self { data, chunk }
+    }
+}
+
+#[cfg(test)]
+impl std::io::Read for ChunkedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let size = available.len().min(buf.len());
+        buf[..size].copy_from_slice(&available[..size]);
+        self.consume(size);
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+impl BufRead for ChunkedReader<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        Ok(&self.data[..self.chunk.min(self.data.len())])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.data = &self.data[amount..];
+    }
+}
+
+fn first_token(value: &str) -> &str {
+    let value = value.trim_start();
+    value.split(char::is_whitespace).next().unwrap_or(value)
+}
+
+#[test]
+fn line_error_test() {
+    let line = Line::new(3, String::from("move 1 from x to 2"));
+    let (_, tail) = line.split_once(&line.text, " from ").unwrap();
+    let (src, _) = line.split_once(tail, " to ").unwrap();
+    assert_eq!(
+        line.parse::<usize>(src, "stack number"),
+        Err(ParseError::new(3, 13, "x", "stack number"))
+    );
+    assert_eq!(
+        line.strip_prefix(&line.text, "take ")
+            .unwrap_err()
+            .to_string(),
+        "line 3, column 1: expected \"take\", found \"move\""
+    );
+    assert_eq!(
+        line.error_at_end("digit").to_string(),
+        "line 3, column 19: expected digit, found end of line"
+    );
+}
+
+#[test]
+fn position_test() {
+    assert_eq!(position(b"ab\ncd\n", 0), (1, 1));
+    assert_eq!(position(b"ab\ncd\n", 4), (2, 2));
+}
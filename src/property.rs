@@ -0,0 +1,130 @@
+use std::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::generate::Rng;
+use crate::runner::panic_message;
+
+const MAX_SHRINKS: usize = 1000;
+
+pub fn check<T, G, S, P>(name: &str, cases: usize, mut generate: G, shrink: S, property: P)
+where
+    T: Clone + Debug,
+    G: FnMut(&mut Rng) -> T,
+    S: Fn(&T) -> Vec<T>,
+    P: Fn(&T) -> Result<(), String>,
+{
+    for seed in 0..cases as u64 {
+        let value = generate(&mut Rng::new(seed));
+        let Err(error) = run_property(&property, &value) else {
+            continue;
+        };
+        let (value, error, shrinks) = shrink_failure(value, error, &shrink, &property);
+        panic!(
+            "{}: property failed for seed {} after {} shrinks: {}\nminimal case: {:?}",
+            name, seed, shrinks, error, value
+        );
+    }
+}
+
+fn run_property<T, P>(property: &P, value: &T) -> Result<(), String>
+where
+    P: Fn(&T) -> Result<(), String>,
+{
+    match catch_unwind(AssertUnwindSafe(|| property(value))) {
+        Ok(result) => result,
+        Err(panic) => Err(panic_message(panic.as_ref())),
+    }
+}
+
+fn shrink_failure<T, S, P>(
+    mut value: T,
+    mut error: String,
+    shrink: &S,
+    property: &P,
+) -> (T, String, usize)
+where
+    S: Fn(&T) -> Vec<T>,
+    P: Fn(&T) -> Result<(), String>,
+{
+    let mut shrinks = 0;
+    'outer: while shrinks < MAX_SHRINKS {
+        for candidate in shrink(&value) {
+            if let Err(candidate_error) = run_property(property, &candidate) {
+                value = candidate;
+                error = candidate_error;
+                shrinks += 1;
+                continue 'outer;
+            }
+        }
+        break;
+    }
+    (value, error, shrinks)
+}
+
+pub fn expect_eq<T: PartialEq + Debug>(actual: T, expected: T) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("expected {:?}, got {:?}", expected, actual))
+    }
+}
+
+pub fn shrink_integer(value: i64) -> Vec<i64> {
+    let mut result = Vec::new();
+    if value != 0 {
+        result.push(0);
+    }
+    let mut step = value / 2;
+    while step != 0 {
+        result.push(value - step);
+        step /= 2;
+    }
+    result
+}
+
+pub fn shrink_vec<T, F>(values: &[T], shrink_value: F) -> Vec<Vec<T>>
+where
+    T: Clone,
+    F: Fn(&T) -> Vec<T>,
+{
+    let mut result = Vec::new();
+    if values.len() > 1 {
+        result.push(values[..values.len() / 2].to_vec());
+        result.push(values[values.len() / 2..].to_vec());
+    }
+    for i in 0..values.len() {
+        let mut candidate = values.to_vec();
+        candidate.remove(i);
+        result.push(candidate);
+    }
+    for (i, value) in values.iter().enumerate() {
+        for shrunk in shrink_value(value) {
+            let mut candidate = values.to_vec();
+            candidate[i] = shrunk;
+            result.push(candidate);
+        }
+    }
+    result
+}
+
+#[test]
+fn shrink_test() {
+    let result = std::panic::catch_unwind(|| {
+        check(
+            "small values",
+            100,
+            |rng| (0..10).map(|_| rng.range(-100, 100)).collect::<Vec<_>>(),
+            |v| shrink_vec(v, |v| shrink_integer(*v)),
+            |v| {
+                if v.iter().all(|v| *v < 50) {
+                    Ok(())
+                } else {
+                    Err(String::from("value is too large"))
+                }
+            },
+        )
+    });
+    let message = *result.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.ends_with("minimal case: [50]"), "{}", message);
+    assert_eq!(shrink_integer(-7), vec![0, -4, -6]);
+}
@@ -0,0 +1,443 @@
+use std::any::{Any, TypeId};
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::parse::ParseError;
+use crate::solution::{Answer, NoAnswer, Solution};
+use crate::*;
+
+pub const DAYS: std::ops::RangeInclusive<u8> = 1..=25;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            v => Err(format!("invalid format: {}", v)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub day: u8,
+    pub parse: Duration,
+    pub parse_peak_memory: usize,
+    pub parts: Vec<PartReport>,
+}
+
+impl Report {
+    pub fn elapsed(&self) -> Duration {
+        self.parse + self.parts.iter().map(|v| v.elapsed).sum::<Duration>()
+    }
+
+    pub fn format_json(&self) -> String {
+        let mut result = format!("{{\"day\":{}", self.day);
+        for part in self.parts.iter() {
+            result += &format!(",\"part{}\":{}", part.part.number(), part.answer.to_json());
+        }
+        let elapsed_ms = Value::from(self.elapsed().as_secs_f64() * 1000.0);
+        result += &format!(",\"elapsed_ms\":{}}}", elapsed_ms);
+        result
+    }
+}
+
+#[derive(Debug)]
+pub struct PartReport {
+    pub part: Part,
+    pub answer: Answer,
+    pub elapsed: Duration,
+    pub peak_memory: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RunError {
+    UnknownDay(u8),
+    Parameter(String),
+    Parse(ParseError),
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::UnknownDay(day) => write!(f, "day {} is not implemented", day),
+            RunError::Parameter(error) => write!(f, "{}", error),
+            RunError::Parse(error) => write!(f, "invalid input at {}", error),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+impl From<ParseError> for RunError {
+    fn from(error: ParseError) -> Self {
+        RunError::Parse(error)
+    }
+}
+
+pub trait Runner {
+    fn parameters(&self) -> &'static [&'static str];
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String>;
+
+    fn run(&self, input: &mut dyn BufRead, parts: &[Part]) -> Result<RunReport, ParseError>;
+
+    fn validate(&self, input: &mut dyn BufRead) -> Result<Vec<String>, ParseError>;
+}
+
+pub struct RunReport {
+    pub parse: Duration,
+    pub parse_peak_memory: usize,
+    pub parts: Vec<PartReport>,
+}
+
+impl<S: Solution> Runner for S {
+    fn parameters(&self) -> &'static [&'static str] {
+        S::PARAMETERS
+    }
+
+    fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        Solution::set_parameter(self, name, value)
+    }
+
+    fn run(&self, input: &mut dyn BufRead, parts: &[Part]) -> Result<RunReport, ParseError> {
+        let allocated = memory::reset_peak();
+        let start = Instant::now();
+        let puzzle = self.parse(input)?;
+        let parse = start.elapsed();
+        let parse_peak_memory = memory::usage_since(allocated, memory::peak());
+        let parts = parts
+            .iter()
+            .map(|part| {
+                let allocated = memory::reset_peak();
+                let start = Instant::now();
+                let answer = match part {
+                    Part::One => self.part_one(&puzzle).into(),
+                    Part::Two => self.part_two(&puzzle).into(),
+                };
+                PartReport {
+                    part: *part,
+                    answer,
+                    elapsed: start.elapsed(),
+                    peak_memory: memory::usage_since(allocated, memory::peak()),
+                }
+            })
+            .collect();
+        Ok(RunReport {
+            parse,
+            parse_peak_memory,
+            parts,
+        })
+    }
+
+    fn validate(&self, input: &mut dyn BufRead) -> Result<Vec<String>, ParseError> {
+        Ok(Solution::validate(self, &self.parse(input)?))
+    }
+}
+
+pub fn get_runner(day: u8) -> Option<Box<dyn Runner>> {
+    Some(match day {
+        1 => Box::<day_1::Day1>::default(),
+        2 => Box::<day_2::Day2>::default(),
+        3 => Box::<day_3::Day3>::default(),
+        4 => Box::<day_4::Day4>::default(),
+        5 => Box::<day_5::Day5>::default(),
+        6 => Box::<day_6::Day6>::default(),
+        7 => Box::<day_7::Day7>::default(),
+        8 => Box::<day_8::Day8>::default(),
+        9 => Box::<day_9::Day9>::default(),
+        10 => Box::<day_10::Day10>::default(),
+        11 => Box::<day_11::Day11>::default(),
+        12 => Box::<day_12::Day12>::default(),
+        13 => Box::<day_13::Day13>::default(),
+        14 => Box::<day_14::Day14>::default(),
+        15 => Box::<day_15::Day15>::default(),
+        16 => Box::<day_16::Day16>::default(),
+        17 => Box::<day_17::Day17>::default(),
+        18 => Box::<day_18::Day18>::default(),
+        19 => Box::<day_19::Day19>::default(),
+        20 => Box::<day_20::Day20>::default(),
+        21 => Box::<day_21::Day21>::default(),
+        22 => Box::<day_22::Day22>::default(),
+        23 => Box::<day_23::Day23>::default(),
+        24 => Box::<day_24::Day24>::default(),
+        25 => Box::<day_25::Day25>::default(),
+        _ => return None,
+    })
+}
+
+pub fn run_day(day: u8, input: &mut dyn BufRead, parts: &[Part]) -> Result<Report, RunError> {
+    run_day_with_parameters(day, &[], input, parts)
+}
+
+pub fn run_day_with_parameters(
+    day: u8,
+    parameters: &[(String, String)],
+    input: &mut dyn BufRead,
+    parts: &[Part],
+) -> Result<Report, RunError> {
+    let mut runner = get_runner(day).ok_or(RunError::UnknownDay(day))?;
+    for (name, value) in parameters.iter() {
+        if runner.parameters().contains(&name.as_str()) {
+            runner
+                .set_parameter(name, value)
+                .map_err(RunError::Parameter)?;
+        }
+    }
+    let report = runner.run(input, parts)?;
+    Ok(Report {
+        day,
+        parse: report.parse,
+        parse_peak_memory: report.parse_peak_memory,
+        parts: report.parts,
+    })
+}
+
+pub fn run_days_parallel<T, R, C>(days: &[u8], workers: usize, run: R, mut complete: C)
+where
+    T: Send,
+    R: Fn(u8) -> Result<T, String> + Sync,
+    C: FnMut(u8, Result<T, String>),
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, days.len().max(1)) {
+            let sender = sender.clone();
+            let (next, run) = (&next, &run);
+            std::thread::Builder::new()
+                .stack_size(WORKER_STACK_SIZE)
+                .spawn_scoped(scope, move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&day) = days.get(index) else {
+                        break;
+                    };
+                    let result = catch_unwind(AssertUnwindSafe(|| run(day)))
+                        .unwrap_or_else(|panic| Err(panic_message(panic.as_ref())));
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                })
+                .expect("worker thread is spawned");
+        }
+        drop(sender);
+        let mut pending: Vec<Option<Result<T, String>>> = days.iter().map(|_| None).collect();
+        let mut completed = 0;
+        for (index, result) in receiver {
+            pending[index] = Some(result);
+            while let Some(result) = pending.get_mut(completed).and_then(Option::take) {
+                complete(days[completed], result);
+                completed += 1;
+            }
+        }
+    });
+}
+
+const WORKER_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+pub fn panic_message(panic: &(dyn Any + Send)) -> String {
+    match panic.downcast_ref::<&str>() {
+        Some(message) => format!("panicked: {}", message),
+        None => match panic.downcast_ref::<String>() {
+            Some(message) => format!("panicked: {}", message),
+            None => String::from("panicked"),
+        },
+    }
+}
+
+pub fn parse_options<S: Solution>(solution: &mut S, args: &[String]) -> Result<Format, String> {
+    let mut format = Format::Text;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(name) = arg.strip_prefix("--") else {
+            return Err(format!("unknown option: {}", arg));
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+        match name {
+            "format" => format = value.parse()?,
+            _ => solution.set_parameter(name, value)?,
+        }
+    }
+    Ok(format)
+}
+
+pub fn usage<S: Solution>() -> String {
+    let mut result = format!("Usage: day_{} [--format text|json]", S::DAY);
+    for name in S::PARAMETERS.iter() {
+        result += &format!(" [--{} VALUE]", name);
+    }
+    result
+}
+
+pub fn run_main<S: Solution>(mut solution: S)
+where
+    S::PartTwo: 'static,
+{
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let format = match parse_options(&mut solution, &args) {
+        Ok(v) => v,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, usage::<S>());
+            std::process::exit(1);
+        }
+    };
+    let report = match solution.run(&mut std::io::stdin().lock(), &[Part::One, Part::Two]) {
+        Ok(report) => Report {
+            day: S::DAY,
+            parse: report.parse,
+            parse_peak_memory: report.parse_peak_memory,
+            parts: report.parts,
+        },
+        Err(error) => {
+            eprintln!("error: invalid input at {}", error);
+            std::process::exit(1);
+        }
+    };
+    if format == Format::Json {
+        println!("{}", report.format_json());
+        return;
+    }
+    let mut answers = report.parts.into_iter().map(|v| v.answer);
+    let (part_one, part_two) = (answers.next().unwrap(), answers.next().unwrap());
+    let is_plain =
+        |v: &Answer| !matches!(v, Answer::Image(..) | Answer::Incomplete(..) | Answer::None);
+    if !is_plain(&part_one) || !is_plain(&part_two) {
+        println!("{}", part_one);
+        if TypeId::of::<S::PartTwo>() != TypeId::of::<NoAnswer>() {
+            println!("{}", part_two);
+        }
+    } else {
+        println!("{:?}", (part_one, part_two));
+    }
+}
+
+#[test]
+fn run_day_test() {
+    let report = run_day(
+        1,
+        &mut "1000\n2000\n\n4000\n".as_bytes(),
+        &[Part::One, Part::Two],
+    )
+    .unwrap();
+    assert_eq!(report.day, 1);
+    assert_eq!(
+        report
+            .parts
+            .iter()
+            .map(|v| (v.part, v.answer.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Part::One, Answer::Unsigned(4000)),
+            (Part::Two, Answer::Unsigned(7000))
+        ]
+    );
+}
+
+#[test]
+fn format_json_test() {
+    let report = Report {
+        day: 10,
+        parse: Duration::from_millis(1),
+        parse_peak_memory: 0,
+        parts: vec![
+            PartReport {
+                part: Part::One,
+                answer: Answer::Signed(-3),
+                elapsed: Duration::from_millis(2),
+                peak_memory: 0,
+            },
+            PartReport {
+                part: Part::Two,
+                answer: Answer::Text(String::from("ABC")),
+                elapsed: Duration::from_micros(500),
+                peak_memory: 0,
+            },
+        ],
+    };
+    assert_eq!(
+        report.format_json(),
+        r#"{"day":10,"part1":-3,"part2":"ABC","elapsed_ms":3.5}"#
+    );
+}
+
+#[test]
+fn parse_options_test() {
+    let args = |v: &[&str]| v.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    let mut solution = day_15::Day15::default();
+    assert_eq!(
+        parse_options(&mut solution, &args(&["--row", "10", "--max", "20"])),
+        Ok(Format::Text)
+    );
+    assert_eq!((solution.y, solution.min, solution.max), (10, 0, 20));
+    assert_eq!(
+        parse_options(&mut solution, &args(&["--rows", "1"])),
+        Err(String::from("unknown option: --rows"))
+    );
+    assert_eq!(
+        parse_options(&mut solution, &args(&["--min", "x"])),
+        Err(String::from("invalid value for --min: x"))
+    );
+    assert_eq!(
+        parse_options(&mut solution, &args(&["--format"])),
+        Err(String::from("missing value for --format"))
+    );
+}
+
+#[test]
+fn run_days_parallel_test() {
+    let mut results = Vec::new();
+    run_days_parallel(
+        &[3, 1, 2, 4],
+        3,
+        |day| match day {
+            2 => panic!("day {} is broken", day),
+            4 => Err(String::from("invalid input")),
+            _ => {
+                std::thread::sleep(Duration::from_millis(day as u64 * 10));
+                Ok(day * 10)
+            }
+        },
+        |day, result| results.push((day, result)),
+    );
+    assert_eq!(
+        results,
+        vec![
+            (3, Ok(30)),
+            (1, Ok(10)),
+            (2, Err(String::from("panicked: day 2 is broken"))),
+            (4, Err(String::from("invalid input"))),
+        ]
+    );
+}
@@ -0,0 +1,396 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::ops::Add;
+use std::time::{Duration, Instant};
+
+use crate::memory;
+use crate::solution::{parse_parameter, unknown_parameter};
+
+pub trait Problem {
+    type State: Clone;
+    type Key: Eq + Hash;
+    type Cost: Copy + Ord + Default + Add<Output = Self::Cost>;
+
+    fn key(&self, state: &Self::State) -> Self::Key;
+
+    fn cost(&self, state: &Self::State) -> Self::Cost;
+
+    fn heuristic(&self, _state: &Self::State) -> Self::Cost {
+        Self::Cost::default()
+    }
+
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    fn fallback(&self, _state: &Self::State) -> Option<Self::State> {
+        None
+    }
+
+    fn successors(&mut self, state: &Self::State, successors: &mut Vec<Self::State>);
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Budget {
+    pub max_iterations: usize,
+    pub max_states: usize,
+    pub deadline: Option<Instant>,
+    pub max_memory: usize,
+    pub memory_base: isize,
+}
+
+impl Budget {
+    pub const UNLIMITED: Budget = Budget {
+        max_iterations: usize::MAX,
+        max_states: usize::MAX,
+        deadline: None,
+        max_memory: usize::MAX,
+        memory_base: 0,
+    };
+
+    pub fn iterations(max_iterations: usize) -> Self {
+        Self {
+            max_iterations,
+            ..Self::UNLIMITED
+        }
+    }
+
+    pub fn states(max_states: usize) -> Self {
+        Self {
+            max_states,
+            ..Self::UNLIMITED
+        }
+    }
+
+    fn exceeded(&self, iterations: usize, states: usize) -> Option<Limit> {
+        if iterations >= self.max_iterations {
+            Some(Limit::Iterations)
+        } else if states >= self.max_states {
+            Some(Limit::States)
+        } else if !iterations.is_multiple_of(CHECK_INTERVAL) {
+            None
+        } else if self.deadline.is_some_and(|v| Instant::now() >= v) {
+            Some(Limit::Time)
+        } else if memory::usage_since(self.memory_base, memory::allocated()) > self.max_memory {
+            Some(Limit::Memory)
+        } else {
+            None
+        }
+    }
+}
+
+const CHECK_INTERVAL: usize = 1024;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Limits {
+    pub max_iterations: Option<usize>,
+    pub max_states: Option<usize>,
+    pub timeout: Option<Duration>,
+    pub max_memory: Option<usize>,
+}
+
+impl Limits {
+    pub fn set_parameter(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "max-iterations" => self.max_iterations = Some(parse_parameter(name, value)?),
+            "max-states" => self.max_states = Some(parse_parameter(name, value)?),
+            "timeout" => {
+                let seconds: f64 = parse_parameter(name, value)?;
+                self.timeout = Some(
+                    Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| format!("invalid value for --{}: {}", name, value))?,
+                );
+            }
+            "max-memory" => {
+                let megabytes: usize = parse_parameter(name, value)?;
+                self.max_memory = Some(megabytes.saturating_mul(1024 * 1024));
+                memory::enable();
+            }
+            _ => return Err(unknown_parameter(name)),
+        }
+        Ok(())
+    }
+
+    pub fn start(&self, default: Budget) -> Budget {
+        Budget {
+            max_iterations: self.max_iterations.unwrap_or(default.max_iterations),
+            max_states: self.max_states.unwrap_or(default.max_states),
+            deadline: self
+                .timeout
+                .and_then(|v| Instant::now().checked_add(v))
+                .or(default.deadline),
+            max_memory: self.max_memory.unwrap_or(default.max_memory),
+            memory_base: memory::allocated(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Limit {
+    Iterations,
+    States,
+    Time,
+    Memory,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Iterations => write!(f, "iterations"),
+            Limit::States => write!(f, "states"),
+            Limit::Time => write!(f, "time"),
+            Limit::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Stats {
+    pub iterations: usize,
+    pub states: usize,
+    pub elapsed: Duration,
+}
+
+impl Add for Stats {
+    type Output = Stats;
+
+    fn add(self, other: Stats) -> Stats {
+        Stats {
+            iterations: self.iterations + other.iterations,
+            states: self.states + other.states,
+            elapsed: self.elapsed + other.elapsed,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SearchError<T = ()> {
+    BudgetExhausted {
+        limit: Limit,
+        best: Option<T>,
+        stats: Stats,
+    },
+    NotFound,
+}
+
+impl<T> SearchError<T> {
+    pub fn best(&self) -> Option<&T> {
+        match self {
+            SearchError::BudgetExhausted { best, .. } => best.as_ref(),
+            SearchError::NotFound => None,
+        }
+    }
+
+    pub fn split_best(mut self) -> (SearchError, Option<T>) {
+        let best = match &mut self {
+            SearchError::BudgetExhausted { best, .. } => best.take(),
+            SearchError::NotFound => None,
+        };
+        (self.without_best(), best)
+    }
+
+    pub fn without_best<U>(self) -> SearchError<U> {
+        match self {
+            SearchError::BudgetExhausted { limit, stats, .. } => SearchError::BudgetExhausted {
+                limit,
+                best: None,
+                stats,
+            },
+            SearchError::NotFound => SearchError::NotFound,
+        }
+    }
+
+    pub fn map_best<U>(self, f: impl FnOnce(T) -> U) -> SearchError<U> {
+        match self {
+            SearchError::BudgetExhausted { limit, best, stats } => SearchError::BudgetExhausted {
+                limit,
+                best: best.map(f),
+                stats,
+            },
+            SearchError::NotFound => SearchError::NotFound,
+        }
+    }
+}
+
+impl<T> Display for SearchError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::BudgetExhausted { limit, stats, .. } => write!(
+                f,
+                "{} budget exhausted after {} iterations with {} states in {:.3} ms",
+                limit,
+                stats.iterations,
+                stats.states,
+                stats.elapsed.as_secs_f64() * 1000.0
+            ),
+            SearchError::NotFound => write!(f, "goal is unreachable"),
+        }
+    }
+}
+
+impl<T: Debug> std::error::Error for SearchError<T> {}
+
+#[derive(Debug, Clone)]
+pub struct Found<S> {
+    pub state: S,
+    pub path: Vec<S>,
+}
+
+pub fn search<P: Problem>(
+    problem: &mut P,
+    initial_state: P::State,
+    budget: Budget,
+) -> Result<Found<P::State>, SearchError<P::State>> {
+    let start = Instant::now();
+    let mut visited = HashMap::new();
+    visited.insert(problem.key(&initial_state), 0);
+    let mut incoming = BinaryHeap::new();
+    incoming.push((
+        Reverse(problem.cost(&initial_state) + problem.heuristic(&initial_state)),
+        0,
+    ));
+    let mut states = vec![initial_state];
+    let mut parents = vec![NO_PARENT];
+    let mut successors = Vec::new();
+    let mut iterations = 0;
+    while let Some((_, state_index)) = incoming.pop() {
+        if problem.is_goal(&states[state_index]) {
+            return Ok(reconstruct_path(states, &parents, state_index));
+        }
+        if let Some(limit) = budget.exceeded(iterations, states.len()) {
+            return Err(SearchError::BudgetExhausted {
+                limit,
+                stats: Stats {
+                    iterations,
+                    states: states.len(),
+                    elapsed: start.elapsed(),
+                },
+                best: find_best_fallback(problem, &states),
+            });
+        }
+        iterations += 1;
+        problem.successors(&states[state_index], &mut successors);
+        for new_state in successors.drain(..) {
+            let cost = problem.cost(&new_state);
+            let priority = Reverse(cost + problem.heuristic(&new_state));
+            match visited.entry(problem.key(&new_state)) {
+                Entry::Occupied(v) => {
+                    if problem.cost(&states[*v.get()]) > cost {
+                        incoming.push((priority, *v.get()));
+                        states[*v.get()] = new_state;
+                        parents[*v.get()] = state_index;
+                    }
+                }
+                Entry::Vacant(v) => {
+                    v.insert(states.len());
+                    incoming.push((priority, states.len()));
+                    states.push(new_state);
+                    parents.push(state_index);
+                }
+            }
+        }
+    }
+    Err(SearchError::NotFound)
+}
+
+fn find_best_fallback<P: Problem>(problem: &P, states: &[P::State]) -> Option<P::State> {
+    states
+        .iter()
+        .filter_map(|v| problem.fallback(v))
+        .min_by_key(|v| problem.cost(v))
+}
+
+const NO_PARENT: usize = usize::MAX;
+
+fn reconstruct_path<S: Clone>(
+    mut states: Vec<S>,
+    parents: &[usize],
+    state_index: usize,
+) -> Found<S> {
+    let mut path = Vec::new();
+    let mut index = state_index;
+    while index != NO_PARENT {
+        if path.len() > states.len() {
+            break;
+        }
+        path.push(states[index].clone());
+        index = parents[index];
+    }
+    path.reverse();
+    Found {
+        state: states.swap_remove(state_index),
+        path,
+    }
+}
+
+#[cfg(test)]
+struct Line {
+    length: i64,
+    dst: i64,
+}
+
+#[cfg(test)]
+impl Problem for Line {
+    type State = (i64, u64);
+    type Key = i64;
+    type Cost = u64;
+
+    fn key(&self, state: &Self::State) -> Self::Key {
+        state.0
+    }
+
+    fn cost(&self, state: &Self::State) -> Self::Cost {
+        state.1
+    }
+
+    fn heuristic(&self, state: &Self::State) -> Self::Cost {
+        (self.dst - state.0).unsigned_abs()
+    }
+
+    fn is_goal(&self, state: &Self::State) -> bool {
+        state.0 == self.dst
+    }
+
+    fn successors(&mut self, state: &Self::State, successors: &mut Vec<Self::State>) {
+        for next in [state.0 - 1, state.0 + 1] {
+            if (0..self.length).contains(&next) {
+                successors.push((next, state.1 + 1));
+            }
+        }
+    }
+}
+
+#[test]
+fn search_test() {
+    let mut problem = Line { length: 10, dst: 3 };
+    let found = search(&mut problem, (0, 0), Budget::UNLIMITED).unwrap();
+    assert_eq!(found.state, (3, 3));
+    assert_eq!(found.path, vec![(0, 0), (1, 1), (2, 2), (3, 3)]);
+    let SearchError::BudgetExhausted { limit, best, stats } =
+        search(&mut problem, (0, 0), Budget::iterations(2)).unwrap_err()
+    else {
+        panic!("budget is not exhausted");
+    };
+    assert_eq!(
+        (limit, best, stats.iterations, stats.states),
+        (Limit::Iterations, None, 2, 3)
+    );
+    let budget = Limits {
+        timeout: Some(Duration::ZERO),
+        ..Default::default()
+    }
+    .start(Budget::UNLIMITED);
+    assert!(matches!(
+        search(&mut problem, (0, 0), budget),
+        Err(SearchError::BudgetExhausted {
+            limit: Limit::Time,
+            ..
+        })
+    ));
+    let mut problem = Line { length: 2, dst: 3 };
+    assert_eq!(
+        search(&mut problem, (0, 0), Budget::UNLIMITED).unwrap_err(),
+        SearchError::NotFound
+    );
+}
@@ -0,0 +1,223 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::io::BufRead;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::parse::ParseError;
+use crate::search::SearchError;
+
+pub trait Solution {
+    const DAY: u8;
+    const PARAMETERS: &'static [&'static str] = &[];
+
+    type Puzzle;
+    type PartOne: Into<Answer>;
+    type PartTwo: Into<Answer>;
+
+    fn parse(&self, input: impl BufRead) -> Result<Self::Puzzle, ParseError>;
+
+    fn part_one(&self, puzzle: &Self::Puzzle) -> Self::PartOne;
+
+    fn part_two(&self, puzzle: &Self::Puzzle) -> Self::PartTwo;
+
+    fn validate(&self, _puzzle: &Self::Puzzle) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn set_parameter(&mut self, name: &str, _value: &str) -> Result<(), String> {
+        Err(unknown_parameter(name))
+    }
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub enum Answer {
+    Unsigned(u64),
+    Signed(i64),
+    Text(String),
+    Image(Image),
+    Incomplete(Incomplete),
+    None,
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Unsigned(v) => write!(f, "{}", v),
+            Answer::Signed(v) => write!(f, "{}", v),
+            Answer::Text(v) => write!(f, "{}", v),
+            Answer::Image(v) => write!(f, "{}", v),
+            Answer::Incomplete(v) => write!(f, "{}", v),
+            Answer::None => write!(f, "-"),
+        }
+    }
+}
+
+impl Debug for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Unsigned(v) => write!(f, "{:?}", v),
+            Answer::Signed(v) => write!(f, "{:?}", v),
+            Answer::Text(v) => write!(f, "{:?}", v),
+            Answer::Image(v) => write!(f, "{:?}", v.rows),
+            Answer::Incomplete(v) => write!(f, "{:?}", v),
+            Answer::None => write!(f, "None"),
+        }
+    }
+}
+
+impl Answer {
+    pub fn to_json(&self) -> Value {
+        match self {
+            Answer::Unsigned(v) => Value::from(*v),
+            Answer::Signed(v) => Value::from(*v),
+            Answer::Text(v) => Value::from(v.as_str()),
+            Answer::Image(v) => Value::from(v.rows.clone()),
+            Answer::Incomplete(v) => serde_json::json!({
+                "best": v.best.as_ref().map_or(Value::Null, |v| v.to_json()),
+                "error": v.error.to_string(),
+            }),
+            Answer::None => Value::Null,
+        }
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::Unsigned(value as u64)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Answer::Signed(value)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<Image> for Answer {
+    fn from(value: Image) -> Self {
+        Answer::Image(value)
+    }
+}
+
+impl From<NoAnswer> for Answer {
+    fn from(_: NoAnswer) -> Self {
+        Answer::None
+    }
+}
+
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Answer::None, |v| v.into())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image {
+    pub rows: Vec<String>,
+}
+
+impl Display for Image {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows.iter() {
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Incomplete {
+    pub best: Option<Box<Answer>>,
+    pub error: SearchError,
+}
+
+impl Display for Incomplete {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.best {
+            Some(best) => write!(f, "{} (incomplete: {})", best, self.error),
+            None => write!(f, "- (incomplete: {})", self.error),
+        }
+    }
+}
+
+impl<T: Into<Answer>> From<Result<T, SearchError<T>>> for Answer {
+    fn from(value: Result<T, SearchError<T>>) -> Self {
+        match value {
+            Ok(v) => v.into(),
+            Err(error) => {
+                let (error, best) = error.split_best();
+                Answer::Incomplete(Incomplete {
+                    best: best.map(|v| Box::new(v.into())),
+                    error,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NoAnswer;
+
+pub fn solve<S: Solution>(
+    solution: &S,
+    input: impl BufRead,
+) -> Result<(Answer, Answer), ParseError> {
+    let puzzle = solution.parse(input)?;
+    Ok((
+        solution.part_one(&puzzle).into(),
+        solution.part_two(&puzzle).into(),
+    ))
+}
+
+pub fn unknown_parameter(name: &str) -> String {
+    format!("unknown option: --{}", name)
+}
+
+pub fn parse_parameter<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for --{}: {}", name, value))
+}
+
+pub fn parse_parameter_list<T: FromStr>(name: &str, value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(|v| parse_parameter(name, v)).collect()
+}
+
+#[test]
+fn answer_to_json_test() {
+    assert_eq!(Answer::Unsigned(42).to_json().to_string(), "42");
+    assert_eq!(Answer::Signed(-1).to_json().to_string(), "-1");
+    assert_eq!(
+        Answer::Text(String::from("AB")).to_json().to_string(),
+        "\"AB\""
+    );
+    assert_eq!(
+        Answer::Image(Image {
+            rows: vec![String::from("#."), String::from(".#")]
+        })
+        .to_json()
+        .to_string(),
+        r##"["#.",".#"]"##
+    );
+    assert_eq!(Answer::None.to_json().to_string(), "null");
+    let answer: Answer = Err::<u64, _>(SearchError::NotFound).into();
+    assert_eq!(
+        answer.to_json().to_string(),
+        r#"{"best":null,"error":"goal is unreachable"}"#
+    );
+}
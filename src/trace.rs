@@ -0,0 +1,118 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use serde_json::Value;
+
+#[derive(Default)]
+pub struct Trace {
+    output: RefCell<Option<Box<dyn Write + Send>>>,
+}
+
+impl Trace {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file =
+            File::create(path).map_err(|e| format!("failed to create trace {}: {}", path, e))?;
+        Ok(Self::to_writer(BufWriter::new(file)))
+    }
+
+    pub fn to_writer(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            output: RefCell::new(Some(Box::new(writer))),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.output.borrow().is_some()
+    }
+
+    pub fn part(&self, part: u8) -> Tracer<'_> {
+        Tracer {
+            trace: if self.is_enabled() { Some(self) } else { None },
+            part,
+        }
+    }
+
+    fn write(&self, part: u8, step: usize, event: Value) {
+        let mut output = self.output.borrow_mut();
+        let Some(writer) = output.as_mut() else {
+            return;
+        };
+        let mut event = match event {
+            Value::Object(v) => v,
+            v => [(String::from("value"), v)].into_iter().collect(),
+        };
+        event.insert(String::from("part"), Value::from(part));
+        event.insert(String::from("step"), Value::from(step));
+        if let Err(error) = writeln!(writer, "{}", Value::Object(event)) {
+            eprintln!("warning: failed to write trace: {}", error);
+            *output = None;
+        }
+    }
+}
+
+impl Drop for Trace {
+    fn drop(&mut self) {
+        if let Some(writer) = self.output.get_mut().as_mut() {
+            if let Err(error) = writer.flush() {
+                eprintln!("warning: failed to write trace: {}", error);
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Tracer<'a> {
+    trace: Option<&'a Trace>,
+    part: u8,
+}
+
+impl Tracer<'_> {
+    pub fn disabled() -> Self {
+        Self {
+            trace: None,
+            part: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    pub fn event(&self, step: usize, event: impl FnOnce() -> Value) {
+        if let Some(trace) = self.trace {
+            trace.write(self.part, step, event());
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Clone, Default)]
+struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn trace_test() {
+    let buffer = SharedBuffer::default();
+    let trace = Trace::to_writer(buffer.clone());
+    trace
+        .part(2)
+        .event(3, || serde_json::json!({"event": "move", "x": 1}));
+    Trace::default()
+        .part(1)
+        .event(0, || unreachable!("disabled trace builds no events"));
+    assert_eq!(
+        String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap(),
+        "{\"event\":\"move\",\"part\":2,\"step\":3,\"x\":1}\n"
+    );
+}
@@ -0,0 +1,36 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::runner::{get_runner, panic_message, RunError, DAYS};
+
+pub fn validate_day(day: u8, input: &[u8]) -> Result<Vec<String>, String> {
+    let runner = get_runner(day).ok_or_else(|| RunError::UnknownDay(day).to_string())?;
+    match catch_unwind(AssertUnwindSafe(|| runner.validate(&mut &input[..]))) {
+        Ok(Ok(problems)) => Ok(problems),
+        Ok(Err(error)) => Err(RunError::Parse(error).to_string()),
+        Err(panic) => Err(format!("parser {}", panic_message(panic.as_ref()))),
+    }
+}
+
+pub fn detect_days(input: &[u8]) -> Vec<u8> {
+    DAYS.filter(|day| validate_day(*day, input).is_ok())
+        .collect()
+}
+
+#[test]
+fn validate_day_test() {
+    let input = b"    [A]\n[B] [C]\n 1   2\n\nmove 2 from 1 to 2\nmove 1 from 3 to 1\n";
+    assert_eq!(
+        validate_day(5, input),
+        Ok(vec![
+            String::from("command 1: move 2 from 1 to 2, but stack 1 has 1 crate"),
+            String::from("command 2: move 1 from 3 to 1, but there is no stack 3"),
+        ])
+    );
+    assert_eq!(
+        validate_day(16, input),
+        Err(String::from(
+            "invalid input at line 1, column 5: expected \"Valve\", found \"[A]\""
+        ))
+    );
+    assert_eq!(detect_days(input), vec![5]);
+}
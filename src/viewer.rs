@@ -0,0 +1,273 @@
+use std::fs::File;
+use std::io::{BufRead, Read, Write};
+use std::ops::Range;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::parse::ParseError;
+use crate::runner::Part;
+use crate::*;
+
+const MAX_SPEED: usize = 1 << 16;
+
+pub struct Bounds {
+    pub x: Range<i64>,
+    pub y: Range<i64>,
+}
+
+pub trait Scene {
+    fn bounds(&self) -> Bounds;
+
+    fn focus(&self) -> (i64, i64);
+
+    fn tile(&self, x: i64, y: i64) -> char;
+
+    fn status(&self) -> String;
+
+    fn step(&mut self) -> bool;
+}
+
+pub fn get_scene(
+    day: u8,
+    input: &mut dyn BufRead,
+    part: Part,
+) -> Result<Option<Box<dyn Scene>>, ParseError> {
+    Ok(Some(match day {
+        14 => Box::new(day_14::SandScene::new(
+            day_14::parse_map(input)?,
+            part == Part::Two,
+        )),
+        17 => Box::new(day_17::RockScene::new(
+            day_17::parse_jet_directions(input)?,
+            match part {
+                Part::One => day_17::Day17::default().rocks,
+                Part::Two => usize::MAX,
+            },
+        )),
+        23 => Box::new(day_23::ElvesScene::new(
+            day_23::parse_map(input)?,
+            match part {
+                Part::One => 10,
+                Part::Two => usize::MAX,
+            },
+        )),
+        24 => Box::new(day_24::ValleyScene::new(
+            day_24::parse_map(input)?,
+            match part {
+                Part::One => 1,
+                Part::Two => 3,
+            },
+        )),
+        _ => return Ok(None),
+    }))
+}
+
+pub fn viewport(bounds: &Bounds, focus: (i64, i64), size: (usize, usize)) -> (i64, i64) {
+    let clamp = |range: &Range<i64>, focus: i64, size: usize| {
+        let size = size as i64;
+        if range.end - range.start <= size {
+            range.start
+        } else {
+            (focus - size / 2).clamp(range.start, range.end - size)
+        }
+    };
+    (
+        clamp(&bounds.x, focus.0, size.0),
+        clamp(&bounds.y, focus.1, size.1),
+    )
+}
+
+pub fn render(scene: &dyn Scene, origin: (i64, i64), size: (usize, usize)) -> String {
+    let bounds = scene.bounds();
+    let mut result = String::new();
+    let mut color = "";
+    for y in origin.1..origin.1 + size.1 as i64 {
+        for x in origin.0..origin.0 + size.0 as i64 {
+            let tile = if bounds.x.contains(&x) && bounds.y.contains(&y) {
+                scene.tile(x, y)
+            } else {
+                ' '
+            };
+            let tile_color = get_color(tile);
+            if tile_color != color {
+                result += tile_color;
+                color = tile_color;
+            }
+            result.push(tile);
+        }
+        result += "\x1b[0m\x1b[K\n";
+        color = "";
+    }
+    result
+}
+
+fn get_color(tile: char) -> &'static str {
+    match tile {
+        '#' | '|' | '-' | '+' => "\x1b[90m",
+        'o' | '@' => "\x1b[33m",
+        'E' => "\x1b[1;32m",
+        '<' | '>' | '^' | 'v' | '2'..='9' => "\x1b[36m",
+        _ => "\x1b[0m",
+    }
+}
+
+pub struct Options {
+    pub speed: usize,
+    pub frame: Duration,
+    pub paused: bool,
+}
+
+pub fn play(scene: &mut dyn Scene, options: Options) -> std::io::Result<()> {
+    let keys = read_keys();
+    let _terminal = Terminal::enter(keys.is_some())?;
+    let (width, height) = terminal_size();
+    let size = (width, height.saturating_sub(2).max(1));
+    let mut speed = options.speed.clamp(1, MAX_SPEED);
+    let mut paused = options.paused && keys.is_some();
+    let mut finished = false;
+    let mut frames = 0;
+    let mut stdout = std::io::stdout().lock();
+    loop {
+        let origin = viewport(&scene.bounds(), scene.focus(), size);
+        let state = if finished {
+            "finished"
+        } else if paused {
+            "paused"
+        } else {
+            "playing"
+        };
+        write!(
+            stdout,
+            "\x1b[H{}{} | frame {} | speed x{} | {}\x1b[K\n\
+             space: pause  n: step  +/-: speed  q: quit\x1b[K",
+            render(scene, origin, size),
+            scene.status(),
+            frames,
+            speed,
+            state
+        )?;
+        stdout.flush()?;
+        let key = match &keys {
+            Some(keys) if paused || finished => keys.recv().ok(),
+            Some(keys) => match keys.recv_timeout(options.frame) {
+                Ok(key) => Some(key),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => Some(b'q'),
+            },
+            None if finished => return Ok(()),
+            None => {
+                std::thread::sleep(options.frame);
+                None
+            }
+        };
+        let steps = match key {
+            Some(b'q') => return Ok(()),
+            Some(b' ') => {
+                paused = !paused;
+                0
+            }
+            Some(b'n' | b'.') => 1,
+            Some(b'+' | b'=') => {
+                speed = (speed * 2).min(MAX_SPEED);
+                0
+            }
+            Some(b'-') => {
+                speed = (speed / 2).max(1);
+                0
+            }
+            Some(_) => 0,
+            None if paused => 0,
+            None => speed,
+        };
+        for _ in 0..steps {
+            if finished {
+                break;
+            }
+            finished = !scene.step();
+            frames += 1;
+        }
+    }
+}
+
+fn read_keys() -> Option<Receiver<u8>> {
+    let mut tty = File::open("/dev/tty").ok()?;
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 1];
+        while let Ok(1) = tty.read(&mut buffer) {
+            if sender.send(buffer[0]).is_err() {
+                break;
+            }
+        }
+    });
+    Some(receiver)
+}
+
+fn terminal_size() -> (usize, usize) {
+    let from_stty = File::open("/dev/tty").ok().and_then(|tty| {
+        let output = Command::new("stty")
+            .arg("size")
+            .stdin(tty)
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        let output = String::from_utf8(output.stdout).ok()?;
+        let (rows, columns) = output.trim().split_once(' ')?;
+        Some((columns.parse().ok()?, rows.parse().ok()?))
+    });
+    from_stty.unwrap_or_else(|| {
+        let get = |name, default| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        (get("COLUMNS", 80), get("LINES", 24))
+    })
+}
+
+struct Terminal {
+    raw: bool,
+}
+
+impl Terminal {
+    fn enter(raw: bool) -> std::io::Result<Self> {
+        let raw = raw && stty(&["-icanon", "-echo", "min", "1"]);
+        print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+        std::io::stdout().flush()?;
+        Ok(Self { raw })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+        if self.raw {
+            stty(&["icanon", "echo"]);
+        }
+    }
+}
+
+fn stty(args: &[&str]) -> bool {
+    let Ok(tty) = File::open("/dev/tty") else {
+        return false;
+    };
+    Command::new("stty")
+        .args(args)
+        .stdin(tty)
+        .status()
+        .is_ok_and(|v| v.success())
+}
+
+#[test]
+fn viewport_test() {
+    let bounds = Bounds {
+        x: 0..100,
+        y: -10..5,
+    };
+    assert_eq!(viewport(&bounds, (50, 0), (20, 30)), (40, -10));
+    assert_eq!(viewport(&bounds, (2, 0), (20, 5)), (0, -2));
+    assert_eq!(viewport(&bounds, (99, 4), (20, 5)), (80, 0));
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use adventofcode_2022::runner::{run_day, Part, DAYS};
+use adventofcode_2022::solution::Answer;
+
+const MANIFEST_HEADER: &str = "# Expected answers for inputs from var/day_N.txt.
+# Each line is `<day> <part> <answer>`, multi-line answers repeat `<day> <part>` for every line.
+# Update with `cargo test --release --test answers -- --bless [day_N...]`.
+";
+
+const SLOW_DEBUG_DAYS: [u8; 4] = [15, 16, 19, 20];
+
+type Manifest = BTreeMap<(u8, u8), Vec<String>>;
+
+fn main() -> ExitCode {
+    let mut bless = false;
+    let mut filters = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            v if v.starts_with('-') => (),
+            v => filters.push(v.to_string()),
+        }
+    }
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let manifest_path = root.join("tests").join("answers.txt");
+    let mut manifest = match read_manifest(&manifest_path) {
+        Ok(v) => v,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut failed = 0;
+    let mut passed = 0;
+    let mut skipped = 0;
+    for day in DAYS.filter(|day| matches_filters(*day, &filters)) {
+        if cfg!(debug_assertions) && filters.is_empty() && SLOW_DEBUG_DAYS.contains(&day) {
+            println!("day_{} ... skipped in debug build", day);
+            skipped += 1;
+            continue;
+        }
+        let answers = match solve_day(day, &root.join("var").join(format!("day_{}.txt", day))) {
+            Ok(v) => v,
+            Err(error) => {
+                println!("day_{} ... FAILED: {}", day, error);
+                failed += 1;
+                continue;
+            }
+        };
+        for (part, answer) in answers {
+            let key = (day, part);
+            let name = format!("day_{} part_{}", day, part);
+            if bless {
+                let changed = match answer {
+                    Some(answer) if manifest.get(&key) != Some(&answer) => {
+                        manifest.insert(key, answer);
+                        true
+                    }
+                    Some(_) => false,
+                    None => manifest.remove(&key).is_some(),
+                };
+                if changed {
+                    println!("{} ... blessed", name);
+                }
+                passed += 1;
+                continue;
+            }
+            match (manifest.get(&key), answer) {
+                (Some(expected), Some(answer)) if *expected == answer => {
+                    println!("{} ... ok", name);
+                    passed += 1;
+                }
+                (Some(expected), answer) => {
+                    println!(
+                        "{} ... FAILED\n  expected: {}\n  found: {}",
+                        name,
+                        expected.join("\n    "),
+                        answer.map_or(String::from("no answer"), |v| v.join("\n    "))
+                    );
+                    failed += 1;
+                }
+                (None, Some(_)) => {
+                    println!("{} ... FAILED: no expected answer, run with --bless", name);
+                    failed += 1;
+                }
+                (None, None) => {
+                    println!("{} ... skipped: no answer", name);
+                    skipped += 1;
+                }
+            }
+        }
+    }
+    if bless {
+        if let Err(error) = write_manifest(&manifest_path, &manifest) {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    }
+    println!(
+        "\nanswers: {} passed; {} failed; {} skipped",
+        passed, failed, skipped
+    );
+    if cfg!(debug_assertions) && skipped > 0 {
+        println!("run `cargo test --release --test answers` to check every day");
+    }
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn matches_filters(day: u8, filters: &[String]) -> bool {
+    let name = format!("day_{}", day);
+    filters.is_empty() || filters.iter().any(|v| *v == name || *v == day.to_string())
+}
+
+type Answers = Vec<(u8, Option<Vec<String>>)>;
+
+fn solve_day(day: u8, path: &Path) -> Result<Answers, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+    let report = run_day(
+        day,
+        &mut std::io::BufReader::new(file),
+        &[Part::One, Part::Two],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(report
+        .parts
+        .into_iter()
+        .map(|v| (v.part.number(), answer_lines(v.answer)))
+        .collect())
+}
+
+fn answer_lines(answer: Answer) -> Option<Vec<String>> {
+    match answer {
+        Answer::Image(image) => Some(image.rows),
+        Answer::None => None,
+        v => Some(vec![v.to_string()]),
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Manifest::new()),
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    let mut manifest = Manifest::new();
+    for (number, line) in content.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || format!("{}:{}: invalid line: {}", path.display(), number + 1, line);
+        let (day, tail) = line.split_once(' ').ok_or_else(invalid)?;
+        let (part, answer) = tail.split_once(' ').ok_or_else(invalid)?;
+        let day = u8::from_str(day).map_err(|_| invalid())?;
+        let part = u8::from_str(part).map_err(|_| invalid())?;
+        manifest
+            .entry((day, part))
+            .or_default()
+            .push(answer.to_string());
+    }
+    Ok(manifest)
+}
+
+fn write_manifest(path: &Path, manifest: &Manifest) -> Result<(), String> {
+    let mut content = String::from(MANIFEST_HEADER);
+    for ((day, part), answer) in manifest.iter() {
+        for line in answer.iter() {
+            content.push_str(&format!("{} {} {}\n", day, part, line));
+        }
+    }
+    std::fs::write(path, content).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}